@@ -0,0 +1,345 @@
+use cgmath::{Quaternion, Vector3, VectorSpace};
+
+use super::skeleton::{Pose, Skeleton, normalize_rotation};
+
+/**
+ * 关键帧之间的插值方式
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// 保持前一个关键帧的值直到下一个关键帧
+    Step,
+    /// 平移、缩放线性插值，旋转球面插值
+    Linear,
+}
+
+/**
+ * 能够在两个关键帧之间插值的值
+ */
+pub trait Keyframe: Copy {
+    fn interpolate(&self, other: &Self, amount: f32) -> Self;
+}
+
+impl Keyframe for f32 {
+    fn interpolate(&self, other: &Self, amount: f32) -> Self {
+        self + (other - self) * amount
+    }
+}
+
+impl Keyframe for Vector3<f32> {
+    fn interpolate(&self, other: &Self, amount: f32) -> Self {
+        self.lerp(*other, amount)
+    }
+}
+
+impl Keyframe for Quaternion<f32> {
+    /// slerp会沿最短路径插值（点积为负时翻转目标四元数）
+    fn interpolate(&self, other: &Self, amount: f32) -> Self {
+        self.slerp(*other, amount)
+    }
+}
+
+/**
+ * 一条关键帧曲线，times按升序排列，与values一一对应
+ */
+#[derive(Clone, Debug)]
+pub struct Keyframes<T> {
+    pub times: Vec<f32>,
+    pub values: Vec<T>,
+    pub interpolation: Interpolation,
+}
+
+impl<T: Keyframe> Keyframes<T> {
+
+    pub fn new(times: Vec<f32>, values: Vec<T>, interpolation: Interpolation) -> Result<Self, String> {
+        if times.len() != values.len() {
+            return Err(format!("关键帧时间数量{}与值数量{}不一致", times.len(), values.len()));
+        }
+        if times.iter().any(|t| !t.is_finite()) || times.windows(2).any(|pair| pair[1] < pair[0]) {
+            return Err("关键帧时间没有按升序排列".to_string());
+        }
+        Ok(Keyframes { times, values, interpolation })
+    }
+
+    /**
+     * 由三次样条关键帧创建曲线，values按（入切线，值，出切线）排列
+     * 不支持三次样条插值，丢弃切线后退化为线性插值
+     */
+    pub fn from_cubic_spline(times: Vec<f32>, values: Vec<T>) -> Result<Self, String> {
+        if values.len() != times.len() * 3 {
+            return Err(format!("三次样条关键帧时间数量{}与值数量{}不匹配", times.len(), values.len()));
+        }
+        let values = values.chunks_exact(3).map(|triple| triple[1]).collect();
+        Self::new(times, values, Interpolation::Linear)
+    }
+
+    /// 最后一个关键帧的时间
+    pub fn end_time(&self) -> f32 {
+        self.times.last().copied().unwrap_or(0.0)
+    }
+
+    /**
+     * 采样指定时间的值，超出范围时取首尾关键帧的值，没有关键帧时返回None
+     */
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let first = *self.values.first()?;
+        if self.times.len() == 1 || time <= self.times[0] {
+            return Some(first);
+        }
+        // 第一个时间大于time的关键帧
+        let next = self.times.partition_point(|t| *t <= time);
+        if next >= self.times.len() {
+            return self.values.last().copied();
+        }
+        let prev = next - 1;
+        match self.interpolation {
+            Interpolation::Step => Some(self.values[prev]),
+            Interpolation::Linear => {
+                let span = self.times[next] - self.times[prev];
+                let amount = if span > 0.0 { (time - self.times[prev]) / span } else { 0.0 };
+                Some(self.values[prev].interpolate(&self.values[next], amount))
+            },
+        }
+    }
+}
+
+/**
+ * 驱动一个关节的动画通道，没有的分量保持关节的静止变换
+ */
+#[derive(Clone, Debug)]
+pub struct JointChannel {
+    /// 关节在骨架中的索引
+    pub joint: usize,
+    pub translation: Option<Keyframes<Vector3<f32>>>,
+    pub rotation: Option<Keyframes<Quaternion<f32>>>,
+    pub scale: Option<Keyframes<Vector3<f32>>>,
+}
+
+impl JointChannel {
+
+    pub fn new(joint: usize) -> Self {
+        JointChannel { joint, translation: None, rotation: None, scale: None }
+    }
+
+    fn end_time(&self) -> f32 {
+        let translation = self.translation.as_ref().map(Keyframes::end_time).unwrap_or(0.0);
+        let rotation = self.rotation.as_ref().map(Keyframes::end_time).unwrap_or(0.0);
+        let scale = self.scale.as_ref().map(Keyframes::end_time).unwrap_or(0.0);
+        translation.max(rotation).max(scale)
+    }
+}
+
+/**
+ * 动画片段，由若干关节通道组成
+ */
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    pub name: String,
+    /// 片段时长（秒），为所有关键帧的最大时间
+    pub duration: f32,
+    pub channels: Vec<JointChannel>,
+}
+
+impl AnimationClip {
+
+    pub fn new(name: &str, channels: Vec<JointChannel>) -> Self {
+        let duration = channels.iter().map(JointChannel::end_time).fold(0.0, f32::max);
+        AnimationClip { name: name.to_string(), duration, channels }
+    }
+
+    /**
+     * 采样指定时间的姿态写入pose，pose会先重置为骨架的静止姿态
+     * looping为true时时间按片段时长取模，否则超出时长时停在最后一帧
+     */
+    pub fn sample(&self, skeleton: &Skeleton, time: f32, looping: bool, pose: &mut Pose) {
+        let time = if looping && self.duration > 0.0 { time.rem_euclid(self.duration) } else { time };
+
+        pose.locals.clear();
+        pose.locals.extend(skeleton.joints.iter().map(|joint| joint.rest));
+        for channel in self.channels.iter() {
+            let local = match pose.locals.get_mut(channel.joint) {
+                Some(local) => local,
+                None => continue,
+            };
+            if let Some(translation) = channel.translation.as_ref().and_then(|keys| keys.sample(time)) {
+                local.translation = translation;
+            }
+            if let Some(rotation) = channel.rotation.as_ref().and_then(|keys| keys.sample(time)) {
+                local.rotation = normalize_rotation(rotation);
+            }
+            if let Some(scale) = channel.scale.as_ref().and_then(|keys| keys.sample(time)) {
+                local.scale = scale;
+            }
+        }
+    }
+
+    /// 采样并返回新的姿态
+    pub fn sample_pose(&self, skeleton: &Skeleton, time: f32, looping: bool) -> Pose {
+        let mut pose = Pose { locals: Vec::with_capacity(skeleton.len()) };
+        self.sample(skeleton, time, looping, &mut pose);
+        pose
+    }
+}
+
+/**
+ * 动画播放状态：当前片段、播放时间与速度
+ */
+#[derive(Clone, Debug)]
+pub struct AnimationPlayer {
+    pub clip: usize,
+    pub time: f32,
+    pub speed: f32,
+    pub looping: bool,
+    pub paused: bool,
+}
+
+impl AnimationPlayer {
+
+    pub fn new(clip: usize) -> Self {
+        AnimationPlayer { clip, time: 0.0, speed: 1.0, looping: true, paused: false }
+    }
+
+    /// 切换片段并从头播放
+    pub fn play(&mut self, clip: usize) {
+        self.clip = clip;
+        self.time = 0.0;
+        self.paused = false;
+    }
+
+    /// 推进播放时间，delta为帧间隔（秒）
+    pub fn advance(&mut self, delta: f32) {
+        if !self.paused {
+            self.time += delta * self.speed;
+        }
+    }
+
+    /// 按当前播放时间采样姿态，片段索引无效时返回静止姿态
+    pub fn sample(&self, skeleton: &Skeleton, clips: &[AnimationClip], pose: &mut Pose) {
+        match clips.get(self.clip) {
+            Some(clip) => clip.sample(skeleton, self.time, self.looping, pose),
+            None => *pose = skeleton.rest_pose(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, InnerSpace, Matrix4, Rotation3, SquareMatrix};
+
+    use super::*;
+    use crate::animation::skeleton::{Joint, Transform};
+
+    fn keys(interpolation: Interpolation) -> Keyframes<Vector3<f32>> {
+        Keyframes::new(
+            vec![1.0, 2.0, 4.0],
+            vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(2.0, 0.0, 0.0), Vector3::new(2.0, 4.0, 0.0)],
+            interpolation,
+        ).unwrap()
+    }
+
+    fn skeleton() -> Skeleton {
+        Skeleton::new(vec![
+            Joint { name: "root".to_string(), parent: None, inverse_bind: Matrix4::identity(), rest: Transform::identity() },
+            Joint {
+                name: "arm".to_string(),
+                parent: Some(0),
+                inverse_bind: Matrix4::identity(),
+                rest: Transform { translation: Vector3::new(1.0, 0.0, 0.0), ..Transform::identity() },
+            },
+        ]).unwrap()
+    }
+
+    fn assert_same_rotation(actual: Quaternion<f32>, expected: Quaternion<f32>) {
+        // q与-q表示同一旋转
+        assert!((actual.dot(expected).abs() - 1.0).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn step_holds_previous_key() {
+        let keys = keys(Interpolation::Step);
+        assert_eq!(keys.sample(1.0), Some(Vector3::new(0.0, 0.0, 0.0)));
+        assert_eq!(keys.sample(1.99), Some(Vector3::new(0.0, 0.0, 0.0)));
+        assert_eq!(keys.sample(2.0), Some(Vector3::new(2.0, 0.0, 0.0)));
+        assert_eq!(keys.sample(3.5), Some(Vector3::new(2.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn linear_interpolates_between_keys() {
+        let keys = keys(Interpolation::Linear);
+        assert_eq!(keys.sample(1.5), Some(Vector3::new(1.0, 0.0, 0.0)));
+        assert_eq!(keys.sample(2.0), Some(Vector3::new(2.0, 0.0, 0.0)));
+        assert_eq!(keys.sample(3.0), Some(Vector3::new(2.0, 2.0, 0.0)));
+    }
+
+    #[test]
+    fn slerp_interpolates_rotation() {
+        let from = Quaternion::from_angle_z(Deg(0.0));
+        let to = Quaternion::from_angle_z(Deg(90.0));
+        let keys = Keyframes::new(vec![0.0, 1.0], vec![from, to], Interpolation::Linear).unwrap();
+        assert_same_rotation(keys.sample(0.5).unwrap(), Quaternion::from_angle_z(Deg(45.0)));
+
+        // 目标四元数取反时仍沿最短路径插值
+        let keys = Keyframes::new(vec![0.0, 1.0], vec![from, -to], Interpolation::Linear).unwrap();
+        assert_same_rotation(keys.sample(0.5).unwrap(), Quaternion::from_angle_z(Deg(45.0)));
+    }
+
+    #[test]
+    fn sample_clamps_outside_key_range() {
+        for interpolation in [Interpolation::Step, Interpolation::Linear] {
+            let keys = keys(interpolation);
+            assert_eq!(keys.sample(-10.0), Some(Vector3::new(0.0, 0.0, 0.0)));
+            assert_eq!(keys.sample(0.5), Some(Vector3::new(0.0, 0.0, 0.0)));
+            assert_eq!(keys.sample(4.0), Some(Vector3::new(2.0, 4.0, 0.0)));
+            assert_eq!(keys.sample(100.0), Some(Vector3::new(2.0, 4.0, 0.0)));
+        }
+        let empty = Keyframes::<f32>::new(Vec::new(), Vec::new(), Interpolation::Linear).unwrap();
+        assert_eq!(empty.sample(1.0), None);
+    }
+
+    #[test]
+    fn new_rejects_mismatched_or_unsorted_keys() {
+        assert!(Keyframes::new(vec![0.0, 1.0], vec![0.0], Interpolation::Linear).is_err());
+        assert!(Keyframes::new(vec![1.0, 0.0], vec![0.0, 1.0], Interpolation::Linear).is_err());
+        assert!(Keyframes::new(vec![0.0, f32::NAN], vec![0.0, 1.0], Interpolation::Linear).is_err());
+    }
+
+    #[test]
+    fn cubic_spline_falls_back_to_linear() {
+        // （入切线，值，出切线）
+        let keys = Keyframes::from_cubic_spline(vec![0.0, 2.0], vec![-5.0, 0.0, 5.0, 7.0, 4.0, -7.0]).unwrap();
+        assert_eq!(keys.interpolation, Interpolation::Linear);
+        assert_eq!(keys.values, vec![0.0, 4.0]);
+        assert_eq!(keys.sample(1.0), Some(2.0));
+
+        assert!(Keyframes::from_cubic_spline(vec![0.0, 2.0], vec![0.0, 1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn clip_sample_clamps_and_loops() {
+        let skeleton = skeleton();
+        let mut channel = JointChannel::new(1);
+        channel.rotation = Some(Keyframes::new(
+            vec![0.0, 2.0],
+            vec![Quaternion::from_angle_z(Deg(0.0)), Quaternion::from_angle_z(Deg(90.0))],
+            Interpolation::Linear,
+        ).unwrap());
+        let clip = AnimationClip::new("wave", vec![channel]);
+        assert_eq!(clip.duration, 2.0);
+
+        let pose = clip.sample_pose(&skeleton, 1.0, false);
+        assert_same_rotation(pose.locals[1].rotation, Quaternion::from_angle_z(Deg(45.0)));
+        // 没有通道驱动的分量与关节保持静止变换
+        assert_eq!(pose.locals[1].translation, Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(pose.locals[0], Transform::identity());
+
+        // 不循环时停在最后一帧
+        let pose = clip.sample_pose(&skeleton, 5.0, false);
+        assert_same_rotation(pose.locals[1].rotation, Quaternion::from_angle_z(Deg(90.0)));
+
+        // 循环时按时长取模，负时间同样回绕
+        let pose = clip.sample_pose(&skeleton, 5.0, true);
+        assert_same_rotation(pose.locals[1].rotation, Quaternion::from_angle_z(Deg(45.0)));
+        let pose = clip.sample_pose(&skeleton, -0.5, true);
+        assert_same_rotation(pose.locals[1].rotation, Quaternion::from_angle_z(Deg(67.5)));
+    }
+}
@@ -0,0 +1,7 @@
+//! 骨骼动画：骨架与姿态、动画片段采样以及GPU蒙皮；形变目标（blend shape）动画
+//! 骨架与动画片段只在CPU端计算，不需要OpenGL上下文
+
+pub mod skeleton;
+pub mod clip;
+pub mod skinning;
+pub mod morph;
@@ -0,0 +1,298 @@
+use glium::{Display, VertexBuffer, texture::buffer_texture::{BufferTexture, BufferTextureType}, uniforms::UniformValue};
+
+use crate::{Vertex, uniforms::{DynamicUniforms, add_to_uniforms, add_value_to_uniforms}, error::LoadError};
+
+use super::clip::Keyframes;
+
+/// 顶点着色器混合时同时生效的形变目标上限，需要与morph.vert中的MAX_MORPH_TARGETS保持一致
+pub const MAX_MORPH_TARGETS: usize = 32;
+
+/// 形变顶点着色器，顶点格式为`Vertex`，形变uniform前缀为`morph`
+pub const MORPH_VERTEX_SHADER_PATH: &str = "src/animation/morph.vert";
+
+/**
+ * 一个形变目标（blend shape），保存相对基础网格的逐顶点偏移
+ */
+#[derive(Clone, Debug)]
+pub struct MorphTarget {
+    pub name: String,
+    /// 位置偏移，与基础网格的顶点一一对应
+    pub positions: Vec<[f32; 3]>,
+    /// 法向量偏移，为空时该目标不影响法向量
+    pub normals: Vec<[f32; 3]>,
+}
+
+impl MorphTarget {
+
+    pub fn validate(&self, vertex_count: usize) -> Result<(), String> {
+        if self.positions.len() != vertex_count {
+            return Err(format!("形变目标{}有{}个位置偏移，基础网格有{}个顶点", self.name, self.positions.len(), vertex_count));
+        }
+        if !self.normals.is_empty() && self.normals.len() != vertex_count {
+            return Err(format!("形变目标{}有{}个法向量偏移，基础网格有{}个顶点", self.name, self.normals.len(), vertex_count));
+        }
+        Ok(())
+    }
+}
+
+/**
+ * 在CPU端混合形变目标：顶点 = 基础顶点 + Σ权重 × 偏移，法向量混合后重新归一化
+ * 结果写入output，output的长度会被调整为基础网格的顶点数
+ */
+pub fn blend_morph_targets(base: &[Vertex], targets: &[MorphTarget], weights: &[f32], output: &mut Vec<Vertex>) {
+    output.clear();
+    output.extend_from_slice(base);
+    let mut normals_changed = false;
+    for (target, weight) in targets.iter().zip(weights.iter()) {
+        if *weight == 0.0 {
+            continue;
+        }
+        for (vertex, delta) in output.iter_mut().zip(target.positions.iter()) {
+            for (value, delta) in vertex.position.iter_mut().zip(delta.iter()) {
+                *value += weight * delta;
+            }
+        }
+        for (vertex, delta) in output.iter_mut().zip(target.normals.iter()) {
+            for (value, delta) in vertex.normal.iter_mut().zip(delta.iter()) {
+                *value += weight * delta;
+            }
+            normals_changed = true;
+        }
+    }
+    if normals_changed {
+        for vertex in output.iter_mut() {
+            let [x, y, z] = vertex.normal;
+            let length = (x * x + y * y + z * z).sqrt();
+            if length > 0.0 {
+                vertex.normal = [x / length, y / length, z / length];
+            }
+        }
+    }
+}
+
+/**
+ * 形变目标的混合方式
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MorphMode {
+    /// 权重变化后在CPU端混合并写入`MorphTargets`自己的动态顶点缓冲，可以搭配任意着色器
+    Cpu,
+    /// 偏移保存在缓冲纹理中，由`MORPH_VERTEX_SHADER_PATH`顶点着色器混合
+    VertexShader,
+}
+
+/**
+ * 绘制单位上的形变目标及其当前权重
+ */
+pub struct MorphTargets {
+    /// 当前权重，与形变目标一一对应，修改后需要调用`Model::update_morph`
+    pub weights: Vec<f32>,
+    mode: MorphMode,
+    base: Vec<Vertex>,
+    targets: Vec<MorphTarget>,
+    /// CPU混合的结果
+    blended: Vec<Vertex>,
+    /// CPU模式下保存混合结果的动态顶点缓冲
+    vertex_buffer: Option<VertexBuffer<Vertex>>,
+    /// 上一次写入顶点缓冲时的权重，权重没有变化时跳过混合
+    applied: Vec<f32>,
+    /// 顶点着色器混合使用的偏移，第i个目标第v个顶点的位置偏移在`(i * 顶点数 + v) * 2`，法向量偏移紧随其后
+    deltas: Option<BufferTexture<[f32; 4]>>,
+}
+
+impl MorphTargets {
+
+    pub fn new(display: &Display, base: &[Vertex], targets: Vec<MorphTarget>, mode: MorphMode) -> Result<Self, LoadError> {
+        for target in targets.iter() {
+            target.validate(base.len()).map_err(|message| LoadError::invalid("形变目标", message))?;
+        }
+        let (vertex_buffer, deltas) = match mode {
+            MorphMode::Cpu => (Some(VertexBuffer::dynamic(display, base)?), None),
+            MorphMode::VertexShader => {
+                if targets.len() > MAX_MORPH_TARGETS {
+                    return Err(LoadError::invalid("形变目标", format!("{}个目标超过顶点着色器上限{}", targets.len(), MAX_MORPH_TARGETS)));
+                }
+                let mut data = Vec::with_capacity(targets.len() * base.len() * 2);
+                for target in targets.iter() {
+                    for (i, position) in target.positions.iter().enumerate() {
+                        let normal = target.normals.get(i).copied().unwrap_or([0.0; 3]);
+                        data.push([position[0], position[1], position[2], 0.0]);
+                        data.push([normal[0], normal[1], normal[2], 0.0]);
+                    }
+                }
+                // 没有目标时缓冲纹理不能为空
+                if data.is_empty() {
+                    data.push([0.0; 4]);
+                }
+                (None, Some(BufferTexture::new(display, &data, BufferTextureType::Float).map_err(|err| LoadError::gpu("形变缓冲纹理", format!("{:?}", err)))?))
+            },
+        };
+        Ok(MorphTargets {
+            weights: vec![0.0; targets.len()],
+            mode,
+            base: base.to_vec(),
+            targets,
+            blended: Vec::new(),
+            vertex_buffer,
+            applied: Vec::new(),
+            deltas,
+        })
+    }
+
+    pub fn mode(&self) -> MorphMode {
+        self.mode
+    }
+
+    pub fn targets(&self) -> &[MorphTarget] {
+        &self.targets
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.targets.iter().position(|target| target.name == name)
+    }
+
+    /// 按名称设置权重，目标不存在时返回false
+    pub fn set_weight(&mut self, name: &str, weight: f32) -> bool {
+        match self.find(name) {
+            Some(index) => {
+                self.weights[index] = weight;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// CPU模式下保存混合结果的顶点缓冲，顶点着色器模式下为None
+    pub fn vertex_buffer(&self) -> Option<&VertexBuffer<Vertex>> {
+        self.vertex_buffer.as_ref()
+    }
+
+    /**
+     * CPU模式下按当前权重混合并写入顶点缓冲，权重没有变化时跳过
+     * 顶点着色器模式下什么也不做
+     */
+    pub fn update(&mut self) {
+        let vertex_buffer = match &self.vertex_buffer {
+            Some(vertex_buffer) if self.applied != self.weights => vertex_buffer,
+            _ => return,
+        };
+        blend_morph_targets(&self.base, &self.targets, &self.weights, &mut self.blended);
+        vertex_buffer.write(&self.blended);
+        self.applied.clone_from(&self.weights);
+    }
+
+    /**
+     * 添加顶点着色器混合需要的uniform，key通常为`morph`
+     * 包括`Deltas`缓冲纹理、`Weights[i]`、`Count`、`VertexCount`，CPU模式下`Count`为0
+     */
+    pub fn add_to_uniforms<'a: 'b, 'b>(&'a self, key: &str, uniforms: &'b mut DynamicUniforms<'a>) {
+        let count = match &self.deltas {
+            Some(deltas) => {
+                add_to_uniforms(key, "Deltas", deltas, uniforms);
+                for (i, weight) in self.weights.iter().enumerate() {
+                    add_to_uniforms(key, &format!("Weights[{}]", i), weight, uniforms);
+                }
+                self.targets.len()
+            },
+            None => 0,
+        };
+        add_value_to_uniforms(key, "Count", UniformValue::SignedInt(count as i32), uniforms);
+        add_value_to_uniforms(key, "VertexCount", UniformValue::SignedInt(self.base.len() as i32), uniforms);
+    }
+}
+
+/**
+ * 驱动一个形变目标权重的关键帧通道
+ */
+#[derive(Clone, Debug)]
+pub struct MorphWeightChannel {
+    /// 形变目标索引
+    pub target: usize,
+    pub weights: Keyframes<f32>,
+}
+
+/**
+ * 形变权重动画
+ */
+#[derive(Clone, Debug)]
+pub struct MorphAnimation {
+    pub name: String,
+    pub duration: f32,
+    pub channels: Vec<MorphWeightChannel>,
+}
+
+impl MorphAnimation {
+
+    pub fn new(name: &str, channels: Vec<MorphWeightChannel>) -> Self {
+        let duration = channels.iter().map(|channel| channel.weights.end_time()).fold(0.0, f32::max);
+        MorphAnimation { name: name.to_string(), duration, channels }
+    }
+
+    /**
+     * 采样指定时间的权重写入weights，没有通道的目标保持原来的权重
+     * looping为true时时间按时长取模，否则超出时长时停在最后一帧
+     */
+    pub fn sample(&self, time: f32, looping: bool, weights: &mut [f32]) {
+        let time = if looping && self.duration > 0.0 { time.rem_euclid(self.duration) } else { time };
+        for channel in self.channels.iter() {
+            if let (Some(weight), Some(value)) = (weights.get_mut(channel.target), channel.weights.sample(time)) {
+                *weight = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Vec<Vertex> {
+        vec![
+            Vertex { position: [0.0, 0.0, 0.0], normal: [0.0, 0.0, 1.0], texture: [0.0, 0.0] },
+            Vertex { position: [1.0, 0.0, 0.0], normal: [0.0, 0.0, 1.0], texture: [1.0, 0.0] },
+        ]
+    }
+
+    fn targets() -> Vec<MorphTarget> {
+        vec![
+            MorphTarget { name: "raise".to_string(), positions: vec![[0.0, 1.0, 0.0], [0.0, 2.0, 0.0]], normals: vec![[0.0, 1.0, -1.0], [0.0, 1.0, -1.0]] },
+            MorphTarget { name: "push".to_string(), positions: vec![[0.0, 0.0, 1.0], [0.0, 0.0, 1.0]], normals: Vec::new() },
+        ]
+    }
+
+    #[test]
+    fn zero_weights_give_base_mesh() {
+        let base = base();
+        let mut output = Vec::new();
+        blend_morph_targets(&base, &targets(), &[0.0, 0.0], &mut output);
+        assert_eq!(output.len(), base.len());
+        for (blended, vertex) in output.iter().zip(base.iter()) {
+            assert_eq!(blended.position, vertex.position);
+            assert_eq!(blended.normal, vertex.normal);
+        }
+    }
+
+    #[test]
+    fn full_weight_gives_target() {
+        let base = base();
+        let mut output = Vec::new();
+        blend_morph_targets(&base, &targets(), &[1.0, 0.0], &mut output);
+        assert_eq!(output[0].position, [0.0, 1.0, 0.0]);
+        assert_eq!(output[1].position, [1.0, 2.0, 0.0]);
+        // 法向量 (0,0,1) + (0,1,-1) = (0,1,0)
+        assert_eq!(output[0].normal, [0.0, 1.0, 0.0]);
+        assert_eq!(output[1].texture, [1.0, 0.0]);
+
+        // 没有法向量偏移的目标不改变法向量
+        blend_morph_targets(&base, &targets(), &[0.0, 1.0], &mut output);
+        assert_eq!(output[1].position, [1.0, 0.0, 1.0]);
+        assert_eq!(output[1].normal, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn validate_rejects_mismatched_lengths() {
+        let target = &targets()[0];
+        assert!(target.validate(2).is_ok());
+        assert!(target.validate(3).is_err());
+    }
+}
@@ -0,0 +1,43 @@
+#version 330 core
+// 形变目标顶点着色器，输出与pbr.vert一致，可以直接搭配PBR或其他光照片段着色器
+// MAX_MORPH_TARGETS需要与animation::morph::MAX_MORPH_TARGETS保持一致
+#define MAX_MORPH_TARGETS 32
+
+in vec3 position;
+in vec3 normal;
+in vec2 texture;
+
+out vec2 TexCoords;
+out vec3 WorldPos;
+out vec3 Normal;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform mat4 model;
+
+// 第i个目标第v个顶点的位置偏移在(i * morphVertexCount + v) * 2，法向量偏移紧随其后
+uniform samplerBuffer morphDeltas;
+uniform float morphWeights[MAX_MORPH_TARGETS];
+uniform int morphCount;
+uniform int morphVertexCount;
+
+void main()
+{
+    vec3 morphedPosition = position;
+    vec3 morphedNormal = normal;
+    for (int i = 0; i < morphCount; i++)
+    {
+        float weight = morphWeights[i];
+        if (weight == 0.0)
+            continue;
+        int offset = (i * morphVertexCount + gl_VertexID) * 2;
+        morphedPosition += weight * texelFetch(morphDeltas, offset).xyz;
+        morphedNormal += weight * texelFetch(morphDeltas, offset + 1).xyz;
+    }
+
+    TexCoords = texture;
+    WorldPos = vec3(model * vec4(morphedPosition, 1.0));
+    Normal = mat3(transpose(inverse(model))) * normalize(morphedNormal);
+
+    gl_Position = projection * view * vec4(WorldPos, 1.0);
+}
@@ -0,0 +1,161 @@
+use cgmath::{Matrix4, Quaternion, Vector3, SquareMatrix, One, VectorSpace, InnerSpace};
+
+/**
+ * 关节的局部变换：平移、旋转、缩放（TRS）
+ * 组合顺序为先缩放，再旋转，最后平移
+ */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Transform {
+
+    pub fn identity() -> Self {
+        Transform {
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn to_matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+
+    /// 两个变换之间插值，平移与缩放线性插值，旋转球面插值
+    pub fn interpolate(&self, other: &Transform, amount: f32) -> Transform {
+        Transform {
+            translation: self.translation.lerp(other.translation, amount),
+            rotation: self.rotation.slerp(other.rotation, amount),
+            scale: self.scale.lerp(other.scale, amount),
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+/**
+ * 骨架中的一个关节
+ */
+#[derive(Clone, Debug)]
+pub struct Joint {
+    pub name: String,
+    /// 父关节在骨架中的索引，根关节为None
+    pub parent: Option<usize>,
+    /// 绑定姿态的逆矩阵，将模型空间顶点变换到关节空间
+    pub inverse_bind: Matrix4<f32>,
+    /// 没有动画驱动时使用的局部变换
+    pub rest: Transform,
+}
+
+/**
+ * 骨架（关节层级）
+ * 关节按父关节在前的顺序保存，计算全局变换时只需顺序遍历一次
+ */
+#[derive(Clone, Debug)]
+pub struct Skeleton {
+    pub joints: Vec<Joint>,
+    /// 根关节之上的变换，例如glTF中骨架节点自身的全局变换
+    pub root_transform: Matrix4<f32>,
+}
+
+impl Skeleton {
+
+    /**
+     * 创建骨架，要求每个关节的父关节都排在它前面
+     */
+    pub fn new(joints: Vec<Joint>) -> Result<Self, String> {
+        for (i, joint) in joints.iter().enumerate() {
+            if let Some(parent) = joint.parent {
+                if parent >= i {
+                    return Err(format!("关节{}的父关节{}没有排在它前面", joint.name, parent));
+                }
+            }
+        }
+        Ok(Skeleton { joints, root_transform: Matrix4::identity() })
+    }
+
+    pub fn len(&self) -> usize {
+        self.joints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.joints.is_empty()
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|joint| joint.name == name)
+    }
+
+    /// 所有关节都处于静止变换的姿态
+    pub fn rest_pose(&self) -> Pose {
+        Pose { locals: self.joints.iter().map(|joint| joint.rest).collect() }
+    }
+}
+
+/**
+ * 骨架姿态：每个关节的局部变换，与骨架的关节一一对应
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pose {
+    pub locals: Vec<Transform>,
+}
+
+impl Pose {
+
+    /// 计算每个关节在模型空间中的全局变换
+    pub fn global_matrices(&self, skeleton: &Skeleton) -> Vec<Matrix4<f32>> {
+        let mut globals: Vec<Matrix4<f32>> = Vec::with_capacity(skeleton.joints.len());
+        for (joint, local) in skeleton.joints.iter().zip(self.locals.iter()) {
+            let parent = match joint.parent {
+                Some(parent) => globals[parent],
+                None => skeleton.root_transform,
+            };
+            globals.push(parent * local.to_matrix());
+        }
+        globals
+    }
+
+    /**
+     * 计算蒙皮矩阵：全局变换乘以绑定姿态的逆矩阵
+     * 顶点着色器使用该矩阵把绑定姿态下的顶点变换到当前姿态
+     */
+    pub fn skinning_matrices(&self, skeleton: &Skeleton) -> Vec<Matrix4<f32>> {
+        self.global_matrices(skeleton).into_iter().zip(skeleton.joints.iter())
+            .map(|(global, joint)| global * joint.inverse_bind)
+            .collect()
+    }
+
+    /// 两个姿态逐关节插值，用于动画之间的过渡
+    pub fn blend(&self, other: &Pose, amount: f32) -> Pose {
+        Pose {
+            locals: self.locals.iter().zip(other.locals.iter())
+                .map(|(a, b)| a.interpolate(b, amount))
+                .collect(),
+        }
+    }
+}
+
+/// 将顶点的四个骨骼权重归一化，权重全为零时全部绑定到第一个关节
+pub fn normalize_weights(weights: [f32; 4]) -> [f32; 4] {
+    let sum: f32 = weights.iter().sum();
+    if sum > 0.0 {
+        weights.map(|w| w / sum)
+    } else {
+        [1.0, 0.0, 0.0, 0.0]
+    }
+}
+
+/// 四元数归一化，长度为零时返回单位四元数
+pub(crate) fn normalize_rotation(rotation: Quaternion<f32>) -> Quaternion<f32> {
+    if rotation.magnitude2() > 0.0 { rotation.normalize() } else { Quaternion::one() }
+}
@@ -0,0 +1,34 @@
+#version 330 core
+// 蒙皮顶点着色器，输出与pbr.vert一致，可以直接搭配PBR或其他光照片段着色器
+// MAX_JOINTS需要与animation::skinning::MAX_JOINTS保持一致
+#define MAX_JOINTS 100
+
+in vec3 position;
+in vec3 normal;
+in vec2 texture;
+in uvec4 joints;
+in vec4 weights;
+
+out vec2 TexCoords;
+out vec3 WorldPos;
+out vec3 Normal;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform mat4 model;
+uniform mat4 jointMatrices[MAX_JOINTS];
+
+void main()
+{
+    mat4 skin = weights.x * jointMatrices[joints.x]
+              + weights.y * jointMatrices[joints.y]
+              + weights.z * jointMatrices[joints.z]
+              + weights.w * jointMatrices[joints.w];
+    mat4 skinnedModel = model * skin;
+
+    TexCoords = texture;
+    WorldPos = vec3(skinnedModel * vec4(position, 1.0));
+    Normal = mat3(transpose(inverse(skinnedModel))) * normal;
+
+    gl_Position = projection * view * vec4(WorldPos, 1.0);
+}
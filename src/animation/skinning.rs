@@ -0,0 +1,99 @@
+use std::rc::Rc;
+
+use glium::{Display, Program, index::IndexBufferAny, vertex::VertexBufferAny};
+
+use crate::{create_program, try_create_program, utils::matrix4_to_raw, uniforms::{DynamicUniforms, add_to_uniforms}, material::PbrMaterial, mesh::MeshData, objectsv2::RawVertexPNTJW, error::LoadError};
+
+use super::{skeleton::{Pose, Skeleton}, clip::AnimationClip};
+
+/// 着色器中关节矩阵数组的长度，超过该数量的骨架无法在GPU上蒙皮
+pub const MAX_JOINTS: usize = 100;
+
+/// 蒙皮顶点着色器，顶点格式为`RawVertexPNTJW`，关节矩阵uniform为`jointMatrices`
+pub const SKINNED_VERTEX_SHADER_PATH: &str = "src/animation/skinned.vert";
+
+/**
+ * 使用蒙皮顶点着色器与指定片段着色器创建程序
+ */
+pub fn create_skinned_program(frag_source_path: &str, display: &Display) -> Program {
+    create_program(SKINNED_VERTEX_SHADER_PATH, frag_source_path, display)
+}
+
+pub fn try_create_skinned_program(frag_source_path: &str, display: &Display) -> Result<Program, LoadError> {
+    try_create_program(SKINNED_VERTEX_SHADER_PATH, frag_source_path, display)
+}
+
+/**
+ * 传给着色器的关节矩阵（蒙皮矩阵）
+ */
+pub struct JointMatrices {
+    matrices: Vec<[[f32; 4]; 4]>,
+}
+
+impl JointMatrices {
+
+    pub fn new(skeleton: &Skeleton) -> Self {
+        let mut result = JointMatrices { matrices: Vec::with_capacity(skeleton.len()) };
+        result.update(skeleton, &skeleton.rest_pose());
+        result
+    }
+
+    /// 根据姿态重新计算蒙皮矩阵，每帧采样动画后调用
+    pub fn update(&mut self, skeleton: &Skeleton, pose: &Pose) {
+        self.matrices.clear();
+        self.matrices.extend(pose.skinning_matrices(skeleton).into_iter().take(MAX_JOINTS).map(matrix4_to_raw));
+    }
+
+    pub fn as_slice(&self) -> &[[[f32; 4]; 4]] {
+        &self.matrices
+    }
+
+    /// 按`key[i]`添加到uniforms，key通常为`jointMatrices`
+    pub fn add_to_uniforms<'a: 'b, 'b>(&'a self, key: &str, uniforms: &'b mut DynamicUniforms<'a>) {
+        for (i, matrix) in self.matrices.iter().enumerate() {
+            add_to_uniforms(key, &format!("[{}]", i), matrix, uniforms);
+        }
+    }
+}
+
+/**
+ * 骨架及其动画片段
+ */
+pub struct Rig {
+    pub skeleton: Skeleton,
+    pub clips: Vec<AnimationClip>,
+}
+
+impl Rig {
+
+    pub fn find_clip(&self, name: &str) -> Option<usize> {
+        self.clips.iter().position(|clip| clip.name == name)
+    }
+}
+
+/**
+ * 蒙皮绘制单位，顶点处于绑定姿态，由rig指定的骨架驱动
+ */
+pub struct SkinnedModel {
+    pub vertex_buffer: VertexBufferAny,
+    pub index_buffer: IndexBufferAny,
+    pub pbr_material: Option<Rc<PbrMaterial>>,
+    /// 驱动该绘制单位的骨架在`AnimatedModel::rigs`中的索引
+    pub rig: usize,
+}
+
+impl SkinnedModel {
+
+    pub fn from_mesh(display: &Display, mesh: &MeshData<RawVertexPNTJW>, rig: usize) -> Result<Self, LoadError> {
+        let (vertex_buffer, index_buffer) = mesh.upload(display)?;
+        Ok(SkinnedModel { vertex_buffer, index_buffer, pbr_material: None, rig })
+    }
+}
+
+/**
+ * 带骨骼动画的模型
+ */
+pub struct AnimatedModel {
+    pub models: Vec<SkinnedModel>,
+    pub rigs: Vec<Rig>,
+}
@@ -7,7 +7,7 @@ use cgmath::{SquareMatrix, Point3, Matrix4};
 use glium::{glutin::{self, event, window, event_loop}, Surface};
 use glium::{glutin::{event::{KeyboardInput, VirtualKeyCode, ElementState, Event}, window::CursorGrabMode}};
 
-use rust_opengl_learn::{camera::{Camera, CameraController}, uniforms::DynamicUniforms, objects::{Plane}, material, create_program, start_loop, Action, event::{keyboard::{KeyboardInteract}}, context::{LoopContext, CONTEXT_STORE, ContextValue}, lights::PointLight};
+use rust_opengl_learn::{camera::{Camera, CameraController}, uniforms::{DynamicUniforms, TextureSampler}, objects::{Plane}, material, create_program, start_loop, Action, event::{keyboard::{KeyboardInteract}}, context::{LoopContext, CONTEXT_STORE, ContextValue}, lights::PointLight};
 
 fn main() {
     let event_loop = event_loop::EventLoop::new();
@@ -23,7 +23,8 @@ fn main() {
 
     let floor = Plane::new("plane", 10.0, 10.0, -0.5_f32, &display, Point3::new(0.0, 0.0, 0.0), Matrix4::identity());
 
-    let floor_texture = material::load_texture("src/wood.png".to_string(), &display).1;
+    // 地面在掠射角下容易模糊，开启各向异性过滤
+    let floor_texture = material::load_texture_with("src/wood.png".to_string(), &display, &TextureSampler::default().with_anisotropy(16)).1;
 
     // 点光源
     let point_light = PointLight::new([0.0, 0.0, 0.0], [1.0, 1.0, 1.0], 0.0, 0.0, 0.0, [0.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
@@ -5,11 +5,11 @@ extern crate cgmath;
 use cgmath::{Matrix4, Vector3};
 #[allow(unused_imports)]
 use glium::{glutin::{self, event, window, event_loop}, Surface};
-use glium::{glutin::{event::{Event}, window::CursorGrabMode, dpi::LogicalSize}, uniforms::{UniformValue, MagnifySamplerFilter}, framebuffer::{DepthRenderBuffer, MultiOutputFrameBuffer}, texture::{Texture2d, UncompressedFloatFormat, DepthFormat, MipmapsOption}, index::PrimitiveType, BlitTarget, Rect, BlitMask};
+use glium::{glutin::{event::{Event}, window::CursorGrabMode, dpi::LogicalSize}, uniforms::{UniformValue, MagnifySamplerFilter, AsUniformValue}, framebuffer::{DepthRenderBuffer, MultiOutputFrameBuffer}, texture::{Texture2d, UncompressedFloatFormat, DepthFormat, MipmapsOption}, index::PrimitiveType, BlitTarget, Rect, BlitMask};
 
 use ouroboros::self_referencing;
 use rand::{SeedableRng, rngs::StdRng, Rng};
-use rust_opengl_learn::{camera::{Camera, CameraController}, uniforms::DynamicUniforms, objects::{Cube, Plane}, create_program, start_loop, Action, context::{LoopContext}, lights::PointLight, load_wavefront_obj_as_models};
+use rust_opengl_learn::{camera::{Camera, CameraController}, uniforms::{DynamicUniforms, with_sampler}, objects::{Cube, Plane}, create_program, start_loop, Action, context::{LoopContext}, lights::PointLight, load_wavefront_obj_as_models};
 
 pub struct Dt {
     position_texture: Texture2d,
@@ -157,10 +157,10 @@ fn main() {
             for model in models.iter() {    
                 if let Some(material) = &model.material {
                     if let Some(diffuse_map) = &material.diffuse_map {
-                        uniforms.add_str_key("texture_diffuse1", diffuse_map.as_ref());
+                        uniforms.add_str_key_value("texture_diffuse1", with_sampler(diffuse_map.as_uniform_value(), material.sampler.behavior()));
                     }
                     if let Some(specular_map) = &material.specular_map {
-                        uniforms.add_str_key("texture_specular1", specular_map.as_ref());
+                        uniforms.add_str_key_value("texture_specular1", with_sampler(specular_map.as_uniform_value(), material.sampler.behavior()));
                     }
                 }
                 for model_translation in model_translations.iter() {
@@ -24,8 +24,8 @@ fn main() {
     let light_program = create_program("src/bin/advanced_lighting_parallax_mapping/light.vert", "src/bin/advanced_lighting_parallax_mapping/light.frag", &display);
 
     let texture = material::load_texture("src/bricks2/bricks2.jpg".to_string(), &display).1;
-    let normal_texture = material::load_texture("src/bricks2/bricks2_normal.jpg".to_string(), &display).1;
-    let disp_texture = material::load_texture("src/bricks2/bricks2_disp.jpg".to_string(), &display).1;
+    let normal_texture = material::load_linear_texture("src/bricks2/bricks2_normal.jpg".to_string(), &display).1;
+    let disp_texture = material::load_linear_texture("src/bricks2/bricks2_disp.jpg".to_string(), &display).1;
 
     // 点光源
     let point_light = PointLight::new_simple([0.5, 1.0, 0.3], [1.0, 1.0, 1.0]);
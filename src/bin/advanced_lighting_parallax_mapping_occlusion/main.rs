@@ -27,8 +27,8 @@ fn main() {
     // let normal_texture = material::load_texture("src/bricks2/bricks2_normal.jpg".to_string(), &display).1;
     // let disp_texture = material::load_texture("src/bricks2/bricks2_disp.jpg".to_string(), &display).1;
     let texture = material::load_texture("src/toy_box/toy_box_diffuse.png".to_string(), &display).1;
-    let normal_texture = material::load_texture("src/toy_box/toy_box_normal.png".to_string(), &display).1;
-    let disp_texture = material::load_texture("src/toy_box/toy_box_disp.png".to_string(), &display).1;
+    let normal_texture = material::load_linear_texture("src/toy_box/toy_box_normal.png".to_string(), &display).1;
+    let disp_texture = material::load_linear_texture("src/toy_box/toy_box_disp.png".to_string(), &display).1;
 
     // 点光源
     let point_light = PointLight::new_simple([0.5, 1.0, 0.3], [1.0, 1.0, 1.0]);
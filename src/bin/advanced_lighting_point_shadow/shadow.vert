@@ -2,8 +2,12 @@
 in vec3 position;
 
 uniform mat4 model;
+uniform mat4 lightSpaceMatrix;
+
+out vec4 FragPos;
 
 void main()
 {
-    gl_Position = model * vec4(position, 1.0);
-}  
\ No newline at end of file
+    FragPos = model * vec4(position, 1.0);
+    gl_Position = lightSpaceMatrix * FragPos;
+}
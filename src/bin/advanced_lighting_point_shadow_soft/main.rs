@@ -2,12 +2,12 @@
 extern crate glium;
 extern crate cgmath;
 
-use cgmath::{Point3, Matrix4, Vector3, Rad, InnerSpace, Deg};
+use cgmath::{Point3, Matrix4, Vector3, Rad, InnerSpace};
 #[allow(unused_imports)]
 use glium::{glutin::{self, event, window, event_loop}, Surface};
-use glium::{glutin::{event::{Event, VirtualKeyCode, KeyboardInput, ElementState}, window::CursorGrabMode, dpi::LogicalSize}, framebuffer::SimpleFrameBuffer, texture::{DepthCubemap}, uniforms::{UniformValue, Sampler, MagnifySamplerFilter, SamplerWrapFunction, MinifySamplerFilter}};
+use glium::{glutin::{event::{Event, VirtualKeyCode, KeyboardInput, ElementState}, window::CursorGrabMode, dpi::LogicalSize}, uniforms::{UniformValue}};
 
-use rust_opengl_learn::{camera::{Camera, CameraController}, uniforms::DynamicUniforms, objects::{Cube}, material, create_program, start_loop, Action, context::{LoopContext, CONTEXT_STORE, ContextValue}, create_program_vgf, event::keyboard::KeyboardInteract};
+use rust_opengl_learn::{camera::{Camera, CameraController}, uniforms::DynamicUniforms, objects::{Cube}, material, create_program, start_loop, Action, context::{LoopContext, CONTEXT_STORE, ContextValue}, event::keyboard::KeyboardInteract, lights::PointLight, shadow::PointShadow};
 
 /// 点光源阴影映射demo - 处理阴影锯齿
 fn main() {
@@ -21,7 +21,7 @@ fn main() {
     display.gl_window().window().set_cursor_visible(false);
 
     let obj_program = create_program("src/bin/advanced_lighting_point_shadow_soft/formal.vert", "src/bin/advanced_lighting_point_shadow_soft/formal.frag", &display);
-    let shadow_program = create_program_vgf("src/bin/advanced_lighting_point_shadow_soft/shadow.vert", "src/bin/advanced_lighting_point_shadow_soft/shadow.geom", "src/bin/advanced_lighting_point_shadow_soft/shadow.frag", &display);
+    let shadow_program = create_program("src/bin/advanced_lighting_point_shadow_soft/shadow.vert", "src/bin/advanced_lighting_point_shadow_soft/shadow.frag", &display);
 
     let texture = material::load_texture("src/wood.png".to_string(), &display).1;
 
@@ -36,42 +36,10 @@ fn main() {
         Cube::new("cube5", 2.0, &display, [0.0, 0.0, 0.0], Point3::new(-1.5, 2.0, -3.0), Matrix4::from_axis_angle(Vector3::new(1.0, 0.0, 1.0).normalize(), Rad(60.0)) * Matrix4::from_scale(0.75))
     ];
 
-    // 阴影贴图
-    let shadow_size: f32 = 1024.0;
-    let shadow_cubemap = DepthCubemap::empty(&display, shadow_size as u32).unwrap();
-
-    // 光源视角位置
-    let light_view_position = Point3::new(0.0, 0.0, 0.0);
-
-    // 本例中光源为平行定向光，所以使用正交投影矩阵，这样透视图不会变形
-    let light_far_plane: f32 = 25.0;
-    let light_projection = cgmath::perspective(Deg(90.0), shadow_size / shadow_size, 1.0, light_far_plane);
-    // 光空间矩阵
-    let light_space_matrixes: Vec<[[f32; 4]; 4]> = {
-        let mut arr: Vec<[[f32; 4]; 4]> = Vec::with_capacity(6);
-        // 根据cubemap各个面的观察矩阵创建各面的光空间变换矩阵
-        // right
-        let light_view = Matrix4::look_at_rh(light_view_position, light_view_position + Vector3::new(1.0, 0.0, 0.0), -Vector3::unit_y());
-        arr.push((light_projection * light_view).into());
-        // left
-        let light_view = Matrix4::look_at_rh(light_view_position, light_view_position + Vector3::new(-1.0, 0.0, 0.0), -Vector3::unit_y());
-        arr.push((light_projection * light_view).into());
-        // top
-        let light_view = Matrix4::look_at_rh(light_view_position, light_view_position + Vector3::new(0.0, 1.0, 0.0), Vector3::unit_z());
-        arr.push((light_projection * light_view).into());
-        // bottom
-        let light_view = Matrix4::look_at_rh(light_view_position, light_view_position + Vector3::new(0.0, -1.0, 0.0), -Vector3::unit_z());
-        arr.push((light_projection * light_view).into());
-        // near
-        let light_view = Matrix4::look_at_rh(light_view_position, light_view_position + Vector3::new(0.0, 0.0, 1.0), -Vector3::unit_y());
-        arr.push((light_projection * light_view).into());
-        // far
-        let light_view = Matrix4::look_at_rh(light_view_position, light_view_position + Vector3::new(0.0, 0.0, -1.0), -Vector3::unit_y());
-        arr.push((light_projection * light_view).into());
-
-        arr
-        
-    };
+    // 点光源及其阴影贴图，远平面以外的物体不产生阴影
+    let point_light = PointLight::new_simple([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]);
+    let mut point_shadow = PointShadow::new(&display, 1024, 25.0);
+    point_shadow.update(&point_light);
 
     // 摄像机初始位置(0, 0, 3), pitch = 0°, yaw = -90°;
     let camera = Camera::new(
@@ -96,26 +64,14 @@ fn main() {
     loop_context.register_keyboard(Box::new(KeyboardInteractor{}));
 
     start_loop(event_loop, loop_context, move |_: Option<Event<()>>, ctx| {
-        // 创建深度贴图的帧缓冲
-        let mut depth_framebuffer = SimpleFrameBuffer::depth_only(&display, &shadow_cubemap).unwrap();
-        depth_framebuffer.clear_color_and_depth((1.0, 1.0, 1.0, 1.0), 1.0);
-
-        // 渲染场景到帧缓冲
-        let mut uniforms = DynamicUniforms::new();
-        for (i, light_space_matrix) in light_space_matrixes.iter().enumerate() {
-            let light_key = format!("lightSpaceMatrixes[{}]", i);
-            uniforms.add(light_key, light_space_matrix);
-        }
-        uniforms.add_str_key("far_plane", &light_far_plane);
-        uniforms.add_str_key_value("lightPos", UniformValue::Vec3(light_view_position.into()));
-        
-        uniforms.add_str_key_value("model", UniformValue::Mat4(outter_cube.calc_model().into()));
-        depth_framebuffer.draw(&outter_cube.vertex_buffer, &outter_cube.index_buffer, &shadow_program, &uniforms, &draw_parameters).unwrap();
-        // cubes
-        for cube in &cubes {
-            uniforms.add_str_key_value("model", UniformValue::Mat4(cube.calc_model().into()));
-            depth_framebuffer.draw(&cube.vertex_buffer, &cube.index_buffer, &shadow_program, &uniforms, &draw_parameters).unwrap();
-        }
+        // 渲染场景到阴影立方体贴图
+        point_shadow.render(&display, &shadow_program, |pass| {
+            pass.draw(&outter_cube.vertex_buffer, &outter_cube.index_buffer, outter_cube.calc_model())?;
+            for cube in &cubes {
+                pass.draw(&cube.vertex_buffer, &cube.index_buffer, cube.calc_model())?;
+            }
+            Ok(())
+        }).unwrap();
 
         // 渲染主要场景
         // 摄像机观察矩阵
@@ -125,14 +81,16 @@ fn main() {
         let mut target = display.draw();
         target.clear_color_and_depth((0.05, 0.05, 0.05, 1.0), 1.0);
         
+        let mut uniforms = DynamicUniforms::new();
         uniforms.add_str_key("projection", &projection_matrix);
         uniforms.add_str_key("view", &view_matrix);
         uniforms.add_str_key("viewPos", &camera_position);
         uniforms.add_str_key("diffuseTexture", &texture);
 
-        let shadow_sampler = Sampler::new(&shadow_cubemap).magnify_filter(MagnifySamplerFilter::Nearest)
-            .minify_filter(MinifySamplerFilter::Nearest).wrap_function(SamplerWrapFunction::Clamp);
+        let shadow_sampler = point_shadow.sampler();
         uniforms.add_str_key("depthMap", &shadow_sampler);
+        uniforms.add_str_key_value("far_plane", UniformValue::Float(point_shadow.far_plane()));
+        uniforms.add_str_key_value("lightPos", UniformValue::Vec3(point_light.position));
 
         let store = CONTEXT_STORE.lock().unwrap();
         if let Some(ContextValue::BOOL(v)) = store.get_value("shadows") {
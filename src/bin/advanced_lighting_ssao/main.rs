@@ -11,7 +11,7 @@ use glium::{glutin::{event::{Event}, window::CursorGrabMode, dpi::LogicalSize},
 
 use ouroboros::self_referencing;
 use rand::{Rng};
-use rust_opengl_learn::{camera::{Camera, CameraController}, uniforms::DynamicUniforms, objects::{Cube, Plane}, create_program, start_loop, Action, context::{LoopContext}, lights::PointLight, load_wavefront_obj_as_models};
+use rust_opengl_learn::{camera::{Camera, CameraController}, uniforms::DynamicUniforms, objects::{Cube, Plane}, create_program, start_loop, Action, context::{LoopContext}, lights::PointLight, load_wavefront_obj_as_models, Model};
 
 pub struct Dt {
     position_texture: Texture2d,
@@ -49,12 +49,18 @@ fn main() {
 
     // 加载模型
     let models = load_wavefront_obj_as_models(&display, "src/nanosuit/", "nanosuit.obj");
-    let model_translate = Matrix4::from_translation(Vector3::new(-7.0, -11.5, 0.0)) * Matrix4::from_angle_z(Deg(-90.0)) * Matrix4::from_angle_y(Deg(-90.0));
 
     let quad = Plane::new_vertical_center_plane("quad", 2.0, 2.0, &display, PrimitiveType::TrianglesList);
 
     let room_cube = Cube::new("room", 2.0, &display, [0.0, 0.0, 0.0], Point3::new(0.0, 7.0, 0.0), Matrix4::from_scale(20.0));
 
+    // 模型倒放后根据包围盒放到房间地面中央
+    let model_rotate = Matrix4::from_angle_z(Deg(-90.0)) * Matrix4::from_angle_y(Deg(-90.0));
+    let model_bounds = Model::bounds_of(&models).transform(&model_rotate);
+    let floor_y = room_cube.world_bounds().min.y;
+    let model_center = model_bounds.center();
+    let model_translate = Matrix4::from_translation(Vector3::new(-model_center.x, floor_y - model_bounds.min.y, -model_center.z)) * model_rotate;
+
     // 点光源
     let point_light = PointLight::new(
         [2.0, 4.0, -2.0], 
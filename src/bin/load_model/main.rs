@@ -4,14 +4,14 @@
 extern crate glium;
 extern crate cgmath;
 
-use std::{time::{self}, fs::{self}, io};
+use std::{time::{self}, io};
 
 use cgmath::{SquareMatrix, Point3, Matrix4, EuclideanSpace, Angle, Vector3, Zero};
 #[allow(unused_imports)]
 use glium::{glutin::{self, event, window, event_loop}, Surface};
-use glium::{index::PrimitiveType, glutin::{event::{KeyboardInput, VirtualKeyCode, ElementState}, window::CursorGrabMode}, VertexBuffer, IndexBuffer, program::{ProgramCreationInput, SourceCode}, Display, Program, vertex::VertexBufferAny, PolygonMode};
+use glium::{index::PrimitiveType, glutin::{event::{KeyboardInput, VirtualKeyCode, ElementState}, window::CursorGrabMode}, VertexBuffer, IndexBuffer, vertex::VertexBufferAny, PolygonMode};
 
-use rust_opengl_learn::{camera::{Camera, CameraController}, lights::{DirLight, PointLight, SpotLight}, uniforms::DynamicUniforms, utils::load_wavefront, Vertex, load_wavefront_obj_as_models};
+use rust_opengl_learn::{camera::{Camera, CameraController}, lights::{DirLight, PointLight, SpotLight}, uniforms::DynamicUniforms, utils::load_wavefront, Vertex, load_wavefront_obj_as_models, create_program};
 
 fn main() {
     let event_loop = event_loop::EventLoop::new();
@@ -326,20 +326,3 @@ impl Cube {
     }
 }
 
-
-fn create_program(vert_source_path: &str, frag_source_path: &str, display: &Display) -> Program {
-    let obj_vert_source = fs::read_to_string(vert_source_path).unwrap();
-    let obj_frag_source = fs::read_to_string(frag_source_path).unwrap();
-    let obj_shader_source = SourceCode {
-        vertex_shader: obj_vert_source.as_str(),
-        tessellation_control_shader: None,
-        tessellation_evaluation_shader: None,
-        geometry_shader: None,
-        fragment_shader: obj_frag_source.as_str(),
-    };
-
-    glium::Program::new(
-        display,
-        ProgramCreationInput::from(obj_shader_source)
-    ).unwrap()
-}
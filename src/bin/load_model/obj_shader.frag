@@ -1,11 +1,7 @@
 #version 330 core
 
 // 材质
-struct Material {
-    sampler2D diffuse;
-    sampler2D specular;
-    float shininess;
-};
+#include "../../shaders/material.glsl"
 
 // 定向光源
 struct DirLight {
@@ -61,7 +57,6 @@ uniform vec3 viewPos;
 uniform DirLight dirLight;
 uniform PointLight pointLights[NR_POINT_LIGHTS];
 uniform SpotLight spotLight;
-uniform Material material;
 
 out vec4 FragColor;
 
@@ -79,7 +74,10 @@ void main()
     // 第三阶段：聚光
     result += CalcSpotLight(spotLight, norm, FragPos, viewDir);
 
-    FragColor = vec4(result, 1.0);
+    // 第四阶段：自发光
+    result += MaterialEmissive(TexCoords);
+
+    FragColor = vec4(result, MaterialAlpha(TexCoords));
 }
 
 vec3 CalcDirLight(DirLight light, vec3 normal, vec3 viewDir)
@@ -89,11 +87,11 @@ vec3 CalcDirLight(DirLight light, vec3 normal, vec3 viewDir)
     float diff = max(dot(normal, lightDir), 0.0);
     // 镜面光着色
     vec3 reflectDir = reflect(-lightDir, normal);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+    float spec = pow(max(dot(viewDir, reflectDir), 0.0), MaterialShininess(TexCoords));
     // 合并结果
-    vec3 ambient  = light.ambient  * vec3(texture(material.diffuse, TexCoords));
-    vec3 diffuse  = light.diffuse  * diff * vec3(texture(material.diffuse, TexCoords));
-    vec3 specular = light.specular * spec * vec3(texture(material.specular, TexCoords));
+    vec3 ambient  = light.ambient  * MaterialDiffuse(TexCoords);
+    vec3 diffuse  = light.diffuse  * diff * MaterialDiffuse(TexCoords);
+    vec3 specular = light.specular * spec * MaterialSpecular(TexCoords);
     return (ambient + diffuse + specular);
 }
 
@@ -104,15 +102,15 @@ vec3 CalcPointLight(PointLight light, vec3 normal, vec3 fragPos, vec3 viewDir)
     float diff = max(dot(normal, lightDir), 0.0);
     // 镜面光着色
     vec3 reflectDir = reflect(-lightDir, normal);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+    float spec = pow(max(dot(viewDir, reflectDir), 0.0), MaterialShininess(TexCoords));
     // 衰减
     float distance    = length(light.position - fragPos);
     float attenuation = 1.0 / (light.constant + light.linear * distance + 
                 light.quadratic * (distance * distance));
     // 合并结果
-    vec3 ambient  = light.ambient  * vec3(texture(material.diffuse, TexCoords));
-    vec3 diffuse  = light.diffuse  * diff * vec3(texture(material.diffuse, TexCoords));
-    vec3 specular = light.specular * spec * vec3(texture(material.specular, TexCoords));
+    vec3 ambient  = light.ambient  * MaterialDiffuse(TexCoords);
+    vec3 diffuse  = light.diffuse  * diff * MaterialDiffuse(TexCoords);
+    vec3 specular = light.specular * spec * MaterialSpecular(TexCoords);
     ambient  *= attenuation;
     diffuse  *= attenuation;
     specular *= attenuation;
@@ -127,7 +125,7 @@ vec3 CalcSpotLight(SpotLight light, vec3 normal, vec3 fragPos, vec3 viewDir)
     float diff = max(dot(normal, lightDir), 0.0);
     // specular shading
     vec3 reflectDir = reflect(-lightDir, normal);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+    float spec = pow(max(dot(viewDir, reflectDir), 0.0), MaterialShininess(TexCoords));
     // attenuation
     float distance = length(light.position - fragPos);
     float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));
@@ -136,9 +134,9 @@ vec3 CalcSpotLight(SpotLight light, vec3 normal, vec3 fragPos, vec3 viewDir)
     float epsilon = light.cutOff - light.outerCutOff;
     float intensity = clamp((theta - light.outerCutOff) / epsilon, 0.0, 1.0);
     // combine results
-    vec3 ambient = light.ambient * vec3(texture(material.diffuse, TexCoords));
-    vec3 diffuse = light.diffuse * diff * vec3(texture(material.diffuse, TexCoords));
-    vec3 specular = light.specular * spec * vec3(texture(material.specular, TexCoords));
+    vec3 ambient = light.ambient * MaterialDiffuse(TexCoords);
+    vec3 diffuse = light.diffuse * diff * MaterialDiffuse(TexCoords);
+    vec3 specular = light.specular * spec * MaterialSpecular(TexCoords);
     ambient *= attenuation * intensity;
     diffuse *= attenuation * intensity;
     specular *= attenuation * intensity;
@@ -2,14 +2,17 @@
 extern crate glium;
 extern crate cgmath;
 
-use cgmath::{Matrix4, Vector3};
+use cgmath::{Matrix4, Vector3, SquareMatrix};
 #[allow(unused_imports)]
 use glium::{glutin::{self, event, window, event_loop}, Surface};
 use glium::{glutin::{event::{Event}, window::CursorGrabMode, dpi::LogicalSize}, uniforms::{UniformValue}};
 
-use rust_opengl_learn::{camera::{Camera, CameraController}, uniforms::DynamicUniforms, objects::{Sphere}, create_program, start_loop, Action, context::{LoopContext}, lights::PointLight};
+use rust_opengl_learn::{camera::{Camera, CameraController}, uniforms::DynamicUniforms, objects::{Sphere}, create_program, start_loop, Action, context::{LoopContext}, lights::PointLight, loader::gltf::load_gltf_as_models, load_wavefront_obj_as_models, ibl::{Ibl, IblOptions, load_ibl}};
 
 /// PBR lighting demo
+/// 运行参数可以传入一个.gltf/.glb/.obj模型路径，此时渲染该模型代替球体阵列
+/// OBJ模型的PBR参数来自MTL中的`Pr`、`Pm`、`map_Pr`、`map_Pm`、`Ke`、`norm`等扩展指令
+/// 运行参数中的.hdr/.exr文件作为等距柱状投影的环境贴图提供IBL环境光，没有时使用常量环境光
 fn main() {
     let event_loop = event_loop::EventLoop::new();
     let size = LogicalSize::<u32>::new(800, 600);
@@ -21,6 +24,32 @@ fn main() {
     display.gl_window().window().set_cursor_visible(false);
 
     let pbr_program = create_program("src/bin/pbr_lighting/pbr.vert", "src/bin/pbr_lighting/pbr.frag", &display);
+    let pbr_material_program = create_program("src/bin/pbr_lighting/pbr.vert", "src/bin/pbr_lighting/pbr_material.frag", &display);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let is_environment = |path: &String| {
+        let path = path.to_lowercase();
+        path.ends_with(".hdr") || path.ends_with(".exr")
+    };
+
+    let ibl_options = IblOptions::default();
+    let ibl = match args.iter().find(|path| is_environment(path)) {
+        Some(path) => load_ibl(&display, path, &ibl_options),
+        None => Ibl::from_color(&display, [0.03, 0.03, 0.03], &ibl_options).unwrap(),
+    };
+
+    let models = match args.into_iter().find(|path| !is_environment(path)) {
+        Some(path) if path.to_lowercase().ends_with(".obj") => {
+            // 材质与贴图路径相对于OBJ所在目录
+            let (basepath, file) = match path.rfind(['/', '\\']) {
+                Some(index) => path.split_at(index + 1),
+                None => ("", path.as_str()),
+            };
+            load_wavefront_obj_as_models(&display, basepath, file)
+        },
+        Some(path) => load_gltf_as_models(&display, &path),
+        None => Vec::new(),
+    };
 
     let sphere = Sphere::new_simple(&display);
     let sphere_rows = 7;
@@ -65,6 +94,7 @@ fn main() {
         uniforms.add_str_key("projection", &projection_matrix);
         uniforms.add_str_key("view", &view_matrix);
         uniforms.add_str_key("camPos", &camera_position);
+        ibl.add_to_uniforms("ibl", &mut uniforms);
         uniforms.add_str_key_value("albedo", UniformValue::Vec3([0.5, 0.0, 0.0]));
         uniforms.add_str_key_value("ao", UniformValue::Float(1.0));
 
@@ -72,20 +102,36 @@ fn main() {
         let mut target = display.draw();
         target.clear_color_and_depth((0.1, 0.1, 0.1, 1.0), 1.0);
 
-        // 渲染球体
-        for r in 0..sphere_rows {
-            uniforms.add_str_key_value("metallic", UniformValue::Float(r as f32 / sphere_rows as f32));
-            for c in 0..sphere_columns {
-                let roughness = (c as f32 / sphere_columns as f32).max(0.05).min(1.0);
-                uniforms.add_str_key_value("roughness", UniformValue::Float(roughness));
-
-                let model = Matrix4::from_translation(Vector3::new(
-                    (c - (sphere_columns / 2)) as f32 * sphere_spacing,
-                    (r - (sphere_rows / 2)) as f32 * sphere_spacing,
-                    0.0
-                ));
-                uniforms.add_str_key_value("model", UniformValue::Mat4(model.into()));
-                target.draw(&sphere.vertex_buffer, &sphere.index_buffer, &pbr_program, &uniforms, &draw_parameters).unwrap();
+        // 渲染加载的模型，材质参数来自模型本身
+        for model in models.iter() {
+            let mut uniforms = uniforms.clone();
+            uniforms.add_str_key_value("model", UniformValue::Mat4(Matrix4::<f32>::identity().into()));
+            for (i, point_light) in point_lights.iter().enumerate() {
+                let light_key = format!("lights[{}]", i);
+                point_light.add_to_uniforms(light_key.as_str(), &mut uniforms);
+            }
+            if let Some(material) = &model.pbr_material {
+                material.add_to_uniforms("material", &mut uniforms);
+            }
+            target.draw(model.vertices(), &model.index_buffer, &pbr_material_program, &uniforms, &draw_parameters).unwrap();
+        }
+
+        // 渲染球体，加载了模型时不再渲染
+        if models.is_empty() {
+            for r in 0..sphere_rows {
+                uniforms.add_str_key_value("metallic", UniformValue::Float(r as f32 / sphere_rows as f32));
+                for c in 0..sphere_columns {
+                    let roughness = (c as f32 / sphere_columns as f32).clamp(0.05, 1.0);
+                    uniforms.add_str_key_value("roughness", UniformValue::Float(roughness));
+
+                    let model = Matrix4::from_translation(Vector3::new(
+                        (c - (sphere_columns / 2)) as f32 * sphere_spacing,
+                        (r - (sphere_rows / 2)) as f32 * sphere_spacing,
+                        0.0
+                    ));
+                    uniforms.add_str_key_value("model", UniformValue::Mat4(model.into()));
+                    target.draw(&sphere.vertex_buffer, &sphere.index_buffer, &pbr_program, &uniforms, &draw_parameters).unwrap();
+                }
             }
         }
 
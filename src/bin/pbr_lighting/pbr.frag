@@ -19,6 +19,8 @@ uniform PointLight lights[4];
 
 uniform vec3 camPos;
 
+#include "../../ibl/ibl.glsl"
+
 const float PI = 3.14159265359;
 // ----------------------------------------------------------------------------
 float DistributionGGX(vec3 N, vec3 H, float roughness)
@@ -109,9 +111,8 @@ void main()
         Lo += (kD * albedo / PI + specular) * radiance * NdotL;  // note that we already multiplied the BRDF by the Fresnel (kS) so we won't multiply by kS again
     }   
     
-    // ambient lighting (note that the next IBL tutorial will replace 
-    // this ambient lighting with environment lighting).
-    vec3 ambient = vec3(0.03) * albedo * ao;
+    // 环境光来自IBL预计算结果
+    vec3 ambient = AmbientIBL(N, V, albedo, metallic, roughness, ao);
 
     vec3 color = ambient + Lo;
 
@@ -0,0 +1,176 @@
+#version 330 core
+out vec4 FragColor;
+in vec2 TexCoords;
+in vec3 WorldPos;
+in vec3 Normal;
+
+// 金属度-粗糙度材质，贴图与因子相乘
+struct PbrMaterial {
+    vec4 albedo;
+    float metallic;
+    float roughness;
+    float ao;
+    vec3 emissive;
+
+    bool has_albedo_map;
+    sampler2D albedo_map;
+    bool has_metallic_map;
+    sampler2D metallic_map;
+    bool has_roughness_map;
+    sampler2D roughness_map;
+    bool has_ao_map;
+    sampler2D ao_map;
+    bool has_emissive_map;
+    sampler2D emissive_map;
+    bool has_normal_map;
+    sampler2D normal_map;
+};
+uniform PbrMaterial material;
+
+// lights
+struct PointLight {
+    vec3 position;
+    vec3 color;
+};
+uniform PointLight lights[4];
+
+uniform vec3 camPos;
+
+#include "../../ibl/ibl.glsl"
+
+const float PI = 3.14159265359;
+// ----------------------------------------------------------------------------
+// 没有切线时通过屏幕空间导数构建TBN矩阵
+vec3 getNormalFromMap()
+{
+    vec3 tangentNormal = texture(material.normal_map, TexCoords).xyz * 2.0 - 1.0;
+
+    vec3 Q1  = dFdx(WorldPos);
+    vec3 Q2  = dFdy(WorldPos);
+    vec2 st1 = dFdx(TexCoords);
+    vec2 st2 = dFdy(TexCoords);
+
+    vec3 N   = normalize(Normal);
+    vec3 T  = normalize(Q1*st2.t - Q2*st1.t);
+    vec3 B  = -normalize(cross(N, T));
+    mat3 TBN = mat3(T, B, N);
+
+    return normalize(TBN * tangentNormal);
+}
+// ----------------------------------------------------------------------------
+float DistributionGGX(vec3 N, vec3 H, float roughness)
+{
+    float a = roughness*roughness;
+    float a2 = a*a;
+    float NdotH = max(dot(N, H), 0.0);
+    float NdotH2 = NdotH*NdotH;
+
+    float nom   = a2;
+    float denom = (NdotH2 * (a2 - 1.0) + 1.0);
+    denom = PI * denom * denom;
+
+    return nom / denom;
+}
+// ----------------------------------------------------------------------------
+float GeometrySchlickGGX(float NdotV, float roughness)
+{
+    float r = (roughness + 1.0);
+    float k = (r*r) / 8.0;
+
+    float nom   = NdotV;
+    float denom = NdotV * (1.0 - k) + k;
+
+    return nom / denom;
+}
+// ----------------------------------------------------------------------------
+float GeometrySmith(vec3 N, vec3 V, vec3 L, float roughness)
+{
+    float NdotV = max(dot(N, V), 0.0);
+    float NdotL = max(dot(N, L), 0.0);
+    float ggx2 = GeometrySchlickGGX(NdotV, roughness);
+    float ggx1 = GeometrySchlickGGX(NdotL, roughness);
+
+    return ggx1 * ggx2;
+}
+// ----------------------------------------------------------------------------
+vec3 fresnelSchlick(float cosTheta, vec3 F0)
+{
+    return F0 + (1.0 - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+// ----------------------------------------------------------------------------
+void main()
+{		
+    vec3 albedo = material.albedo.rgb;
+    if (material.has_albedo_map)
+        albedo *= texture(material.albedo_map, TexCoords).rgb;
+    float metallic = material.metallic;
+    if (material.has_metallic_map)
+        metallic *= texture(material.metallic_map, TexCoords).b;
+    float roughness = material.roughness;
+    if (material.has_roughness_map)
+        roughness *= texture(material.roughness_map, TexCoords).g;
+    float ao = material.ao;
+    if (material.has_ao_map)
+        ao *= texture(material.ao_map, TexCoords).r;
+    vec3 emissive = material.emissive;
+    if (material.has_emissive_map)
+        emissive *= texture(material.emissive_map, TexCoords).rgb;
+
+    vec3 N = material.has_normal_map ? getNormalFromMap() : normalize(Normal);
+    vec3 V = normalize(camPos - WorldPos);
+
+    // calculate reflectance at normal incidence; if dia-electric (like plastic) use F0 
+    // of 0.04 and if it's a metal, use the albedo color as F0 (metallic workflow)    
+    vec3 F0 = vec3(0.04); 
+    F0 = mix(F0, albedo, metallic);
+
+    // reflectance equation
+    vec3 Lo = vec3(0.0);
+    for(int i = 0; i < 4; ++i) 
+    {
+        // calculate per-light radiance
+        vec3 L = normalize(lights[i].position - WorldPos);
+        vec3 H = normalize(V + L);
+        float distance = length(lights[i].position - WorldPos);
+        float attenuation = 1.0 / (distance * distance);
+        vec3 radiance = lights[i].color * attenuation;
+
+        // Cook-Torrance BRDF
+        float NDF = DistributionGGX(N, H, roughness);   
+        float G   = GeometrySmith(N, V, L, roughness);      
+        vec3 F    = fresnelSchlick(clamp(dot(H, V), 0.0, 1.0), F0);
+           
+        vec3 numerator    = NDF * G * F; 
+        float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0) + 0.0001; // + 0.0001 to prevent divide by zero
+        vec3 specular = numerator / denominator;
+        
+        // kS is equal to Fresnel
+        vec3 kS = F;
+        // for energy conservation, the diffuse and specular light can't
+        // be above 1.0 (unless the surface emits light); to preserve this
+        // relationship the diffuse component (kD) should equal 1.0 - kS.
+        vec3 kD = vec3(1.0) - kS;
+        // multiply kD by the inverse metalness such that only non-metals 
+        // have diffuse lighting, or a linear blend if partly metal (pure metals
+        // have no diffuse light).
+        kD *= 1.0 - metallic;	  
+
+        // scale light by NdotL
+        float NdotL = max(dot(N, L), 0.0);        
+
+        // add to outgoing radiance Lo
+        Lo += (kD * albedo / PI + specular) * radiance * NdotL;  // note that we already multiplied the BRDF by the Fresnel (kS) so we won't multiply by kS again
+    }   
+    
+    // 环境光来自IBL预计算结果
+    vec3 ambient = AmbientIBL(N, V, albedo, metallic, roughness, ao);
+
+    vec3 color = ambient + Lo + emissive;
+
+    // HDR tonemapping
+    color = color / (color + vec3(1.0));
+    // gamma correct
+    color = pow(color, vec3(1.0/2.2)); 
+
+    FragColor = vec4(color, 1.0);
+}
@@ -138,7 +138,8 @@ fn main() {
         
         let model_matrix = Into::<[[f32; 4]; 4]>::into(Matrix4::identity());
         box_uniforms.add_str_key("model", &model_matrix);
-        box_uniforms.add_str_key("material.skybox", &skybox_texture);
+        // 物体反射与天空盒使用同一张立方体贴图
+        box_uniforms.add_str_key("skybox", &skybox_texture);
         // 循环渲染模型
         for model in models.iter() {
             if let Some(material) = &model.material {
@@ -148,11 +149,9 @@ fn main() {
         }
 
         box_uniforms.remove("model");
-        box_uniforms.remove("material.skybox");
 
         // 绘制skybox
         // 绘制skybox时需要移除观察矩阵的位移性质，使摄像机无论如何移动，始终在天空盒内
-        box_uniforms.add_str_key("skybox", &skybox_texture);
         let view_matrix = Into::<[[f32; 4]; 4]>::into(camera.calc_matrix_no_move());
         box_uniforms.add_str_key("view", &view_matrix);
         target.draw(&skybox.vertex_buffer, &skybox.index_buffer, &skybox_program, &box_uniforms, &draw_parameters).unwrap();
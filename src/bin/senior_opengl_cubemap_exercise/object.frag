@@ -1,11 +1,7 @@
 #version 330 core
 
 // 材质
-struct Material {
-    sampler2D diffuse;
-    sampler2D reflection;
-    samplerCube skybox;
-};
+#include "../../shaders/material.glsl"
 
 out vec4 FragColor;
 
@@ -14,7 +10,7 @@ in vec2 TexCoords;
 in vec3 Position;
 
 uniform vec3 viewPos;
-uniform Material material;
+uniform samplerCube skybox;
 
 void main()
 {
@@ -22,11 +18,11 @@ void main()
     vec3 normal = normalize(Normal);
 
     vec3 R = reflect(-viewDir, normal);
-    vec3 reflectMap = vec3(texture(material.reflection, TexCoords));
-    vec3 reflection = vec3(texture(material.skybox, R).rgb) * reflectMap;
+    vec3 reflectMap = MaterialReflection(TexCoords);
+    vec3 reflection = vec3(texture(skybox, R).rgb) * reflectMap;
 
     float diff = max(normalize(dot(normal, viewDir)), 0.0f);
-    vec3 diffuse = diff * vec3(texture(material.diffuse, TexCoords));
+    vec3 diffuse = diff * MaterialDiffuse(TexCoords);
 
     FragColor = vec4(diffuse + reflection, 1.0f);
-}
\ No newline at end of file
+}
@@ -9,9 +9,9 @@ use cgmath::{Matrix4, SquareMatrix};
 use chrono::Local;
 #[allow(unused_imports)]
 use glium::{glutin::{self, event, window, event_loop}, Surface};
-use glium::{glutin::{window::CursorGrabMode}, PolygonMode, uniforms::UniformValue};
+use glium::{glutin::{window::CursorGrabMode}, PolygonMode, uniforms::{UniformValue, AsUniformValue}};
 
-use rust_opengl_learn::{camera::{CameraController, Camera}, uniforms::DynamicUniforms, keyboard, create_program_vgf, load_wavefront_obj_as_models};
+use rust_opengl_learn::{camera::{CameraController, Camera}, uniforms::{DynamicUniforms, with_sampler}, keyboard, create_program_vgf, load_wavefront_obj_as_models};
 
 fn main() {
     let event_loop = event_loop::EventLoop::new();
@@ -140,7 +140,7 @@ fn main() {
         for model in models.iter() {
             if let Some(material) = &model.material {
                 if let Some(diffuse_map) = &material.diffuse_map {
-                    uniforms.add_str_key("texture_diffuse", diffuse_map.as_ref());
+                    uniforms.add_str_key_value("texture_diffuse", with_sampler(diffuse_map.as_uniform_value(), material.sampler.behavior()));
                 }
             }
             target.draw(&model.vertex_buffer, &model.index_buffer, &program, &uniforms, &draw_parameters).unwrap();
@@ -8,10 +8,10 @@ use std::{time::{self, SystemTime}};
 use cgmath::{Matrix4, SquareMatrix, Vector3, Deg};
 #[allow(unused_imports)]
 use glium::{glutin::{self, event, window, event_loop}, Surface};
-use glium::{draw_parameters::{Depth}, glutin::{window::CursorGrabMode}, PolygonMode, uniforms::UniformValue, DepthTest, VertexBuffer};
+use glium::{draw_parameters::{Depth}, glutin::{window::CursorGrabMode}, PolygonMode, uniforms::{UniformValue, AsUniformValue}, DepthTest, VertexBuffer};
 
 use rand::{rngs::StdRng, SeedableRng, Rng};
-use rust_opengl_learn::{camera::{CameraController, Camera}, uniforms::DynamicUniforms, keyboard, create_program_vgf, load_wavefront_obj_as_models, create_program, objectsv2::RawInstanceDataM4};
+use rust_opengl_learn::{camera::{CameraController, Camera}, uniforms::{DynamicUniforms, with_sampler}, keyboard, create_program_vgf, load_wavefront_obj_as_models, create_program, objectsv2::RawInstanceDataM4, try_load_wavefront_obj_as_models_with, ObjLoadOptions, Model, geometry::lod::LodSelector};
 
 fn main() {
     let event_loop = event_loop::EventLoop::new();
@@ -38,15 +38,21 @@ fn main() {
         &display);
 
     let models = load_wavefront_obj_as_models(&display, "src/planet/", "planet.obj");
-    let rocks = load_wavefront_obj_as_models(&display, "src/rock/", "rock.obj");
+    // 小行星生成3级LOD，远处的小行星使用简化网格
+    let rock_options = ObjLoadOptions { lods: vec![0.5, 0.2, 0.05], ..Default::default() };
+    let rocks = try_load_wavefront_obj_as_models_with(&display, "src/rock/", "rock.obj", &rock_options).unwrap();
+    let rock_sphere = Model::sphere_of(&rocks);
+    let lod_selector = LodSelector::new(vec![0.05, 0.02, 0.008]);
+    let lod_levels = lod_selector.thresholds.len() + 1;
 
     // 初始化1000个小行星的位置
-    let instances = {
+    let (rock_models, rock_spheres) = {
         let voffset = 5_f32;
         let hoffset = 20.0_f32;
         let radius = 60_f32;
         let amount = 20000;
         let mut rock_models = Vec::with_capacity(amount);
+        let mut rock_spheres = Vec::with_capacity(amount);
         let rotate_dir = Vector3::new(0.4_f32, 0.6, 0.8);
         for i in 0..amount {
             // 1. 位移：分布在半径为 'radius' 的圆形上，偏移的范围是 [-offset, offset]
@@ -64,10 +70,17 @@ fn main() {
 
             // 3. 旋转：绕着一个（半）随机选择的旋转轴向量进行随机的旋转
             let rotate = Matrix4::from_axis_angle(rotate_dir, Deg(rng.gen_range(0..360) as f32));
-            rock_models.push(RawInstanceDataM4 { model: Into::<[[f32; 4]; 4]>::into(translate * rotate * scale) });
+            let model = translate * rotate * scale;
+            rock_models.push(RawInstanceDataM4 { model: Into::<[[f32; 4]; 4]>::into(model) });
+            rock_spheres.push(rock_sphere.transform(&model));
         }
-        VertexBuffer::new(&display, &rock_models).unwrap()
+        (rock_models, rock_spheres)
     };
+    // 每级LOD一个实例缓冲，每帧按投影大小重新分配实例
+    let mut lod_instances: Vec<VertexBuffer<RawInstanceDataM4>> = (0..lod_levels)
+        .map(|_| VertexBuffer::empty_dynamic(&display, rock_models.len()).unwrap())
+        .collect();
+    let mut lod_buckets: Vec<Vec<RawInstanceDataM4>> = vec![Vec::with_capacity(rock_models.len()); lod_levels];
 
     let mut camera = Camera::new(
         cgmath::Point3::new(0_f32, 0_f32, 9_f32), 
@@ -165,21 +178,41 @@ fn main() {
         for model in models.iter() {
             if let Some(material) = &model.material {
                 if let Some(diffuse_map) = &material.diffuse_map {
-                    uniforms.add_str_key("texture_diffuse", diffuse_map.as_ref());
+                    uniforms.add_str_key_value("texture_diffuse", with_sampler(diffuse_map.as_uniform_value(), material.sampler.behavior()));
                 }
             }
             target.draw(&model.vertex_buffer, &model.index_buffer, &planet_program, &uniforms, &draw_parameters).unwrap();
         }
         uniforms.remove("model");
 
+        // 按投影大小将小行星分配到各级LOD
+        for bucket in lod_buckets.iter_mut() {
+            bucket.clear();
+        }
+        for (instance, sphere) in rock_models.iter().zip(rock_spheres.iter()) {
+            let level = lod_selector.select_for(sphere, camera.position, cgmath::Rad::from(cgmath::Deg(45.0)));
+            lod_buckets[level].push(*instance);
+        }
+        for (buffer, bucket) in lod_instances.iter_mut().zip(lod_buckets.iter()) {
+            if !bucket.is_empty() {
+                buffer.slice_mut(0..bucket.len()).unwrap().write(bucket);
+            }
+        }
+
         // 绘制小行星带
         for model in rocks.iter() {
             if let Some(material) = &model.material {
                 if let Some(diffuse_map) = &material.diffuse_map {
-                    uniforms.add_str_key("texture_diffuse", diffuse_map.as_ref());
+                    uniforms.add_str_key_value("texture_diffuse", with_sampler(diffuse_map.as_uniform_value(), material.sampler.behavior()));
+                }
+            }
+            for (level, bucket) in lod_buckets.iter().enumerate() {
+                if bucket.is_empty() {
+                    continue;
                 }
+                let instances = lod_instances[level].slice(0..bucket.len()).unwrap();
+                target.draw((&model.vertex_buffer, instances.per_instance().unwrap()), model.lod_index_buffer(level), &asteroid_program, &uniforms, &draw_parameters).unwrap();
             }
-            target.draw((&model.vertex_buffer, instances.per_instance().unwrap()), &model.index_buffer, &asteroid_program, &uniforms, &draw_parameters).unwrap();
         }
         
         target.finish().unwrap();
@@ -0,0 +1,141 @@
+use std::{fmt, io, error::Error};
+
+use glium::{ProgramCreationError, texture::TextureCreationError};
+
+/**
+ * 资源（模型、材质、着色器）加载错误
+ */
+#[derive(Debug)]
+pub enum LoadError {
+    /// 文件读取失败
+    Io { path: String, source: io::Error },
+    /// OBJ文件解析失败
+    Obj(obj::ObjError),
+    /// MTL材质库加载失败
+    Mtl(obj::MtlLibsLoadError),
+    /// glTF/GLB文件解析失败
+    Gltf(gltf::Error),
+    /// 模型文件（PLY、STL等）内容不符合格式规范
+    Parse { path: String, message: String },
+    /// 不支持的图片格式
+    UnsupportedImageFormat(String),
+    /// 图片解码失败
+    Image { path: String, source: image::ImageError },
+    /// 着色器编译或链接失败，file为出错的着色器文件，log为GLSL编译日志
+    ShaderCompile { file: String, log: String },
+    /// 显存资源（顶点缓冲、索引缓冲、纹理）创建失败
+    GpuAllocation(String),
+    /// 传入的数据不一致，例如形变目标与网格的顶点数量不同
+    InvalidData(String),
+}
+
+impl LoadError {
+
+    pub fn io(path: &str, source: io::Error) -> Self {
+        LoadError::Io { path: path.to_string(), source }
+    }
+
+    pub fn parse<M: Into<String>>(path: &str, message: M) -> Self {
+        LoadError::Parse { path: path.to_string(), message: message.into() }
+    }
+
+    pub fn gpu<E: fmt::Display>(what: &str, err: E) -> Self {
+        LoadError::GpuAllocation(format!("{}: {}", what, err))
+    }
+
+    pub fn invalid<E: fmt::Display>(what: &str, err: E) -> Self {
+        LoadError::InvalidData(format!("{}: {}", what, err))
+    }
+
+    /// 将glium的着色器程序创建错误转换为带文件名的编译错误
+    /// files依次为顶点、几何（可选）、片段着色器文件
+    pub fn shader(err: ProgramCreationError, vert: &str, geometry: Option<&str>, frag: &str) -> Self {
+        use glium::program::ShaderType;
+
+        match err {
+            ProgramCreationError::CompilationError(log, shader_type) => {
+                let file = match shader_type {
+                    ShaderType::Vertex => vert,
+                    ShaderType::Geometry => geometry.unwrap_or(vert),
+                    ShaderType::Fragment => frag,
+                    _ => vert,
+                };
+                LoadError::ShaderCompile { file: file.to_string(), log }
+            },
+            ProgramCreationError::LinkingError(log) => {
+                let file = match geometry {
+                    Some(geometry) => format!("{} + {} + {}", vert, geometry, frag),
+                    None => format!("{} + {}", vert, frag),
+                };
+                LoadError::ShaderCompile { file, log }
+            },
+            other => LoadError::ShaderCompile { file: vert.to_string(), log: other.to_string() },
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io { path, source } => write!(f, "读取文件{}失败: {}", path, source),
+            LoadError::Obj(err) => write!(f, "OBJ文件解析失败: {}", err),
+            LoadError::Mtl(err) => write!(f, "MTL材质库加载失败: {}", err),
+            LoadError::Gltf(err) => write!(f, "glTF文件解析失败: {}", err),
+            LoadError::Parse { path, message } => write!(f, "模型文件{}格式错误: {}", path, message),
+            LoadError::UnsupportedImageFormat(path) => write!(f, "不支持的图片格式: {}", path),
+            LoadError::Image { path, source } => write!(f, "图片{}解码失败: {}", path, source),
+            LoadError::ShaderCompile { file, log } => write!(f, "着色器{}编译失败:\n{}", file, log),
+            LoadError::GpuAllocation(msg) => write!(f, "显存资源创建失败: {}", msg),
+            LoadError::InvalidData(msg) => write!(f, "数据无效: {}", msg),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io { source, .. } => Some(source),
+            LoadError::Obj(err) => Some(err),
+            LoadError::Mtl(err) => Some(err),
+            LoadError::Gltf(err) => Some(err),
+            LoadError::Image { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<obj::ObjError> for LoadError {
+    fn from(err: obj::ObjError) -> Self {
+        LoadError::Obj(err)
+    }
+}
+
+impl From<obj::MtlLibsLoadError> for LoadError {
+    fn from(err: obj::MtlLibsLoadError) -> Self {
+        LoadError::Mtl(err)
+    }
+}
+
+impl From<gltf::Error> for LoadError {
+    fn from(err: gltf::Error) -> Self {
+        LoadError::Gltf(err)
+    }
+}
+
+impl From<glium::vertex::BufferCreationError> for LoadError {
+    fn from(err: glium::vertex::BufferCreationError) -> Self {
+        LoadError::gpu("顶点缓冲", err)
+    }
+}
+
+impl From<glium::index::BufferCreationError> for LoadError {
+    fn from(err: glium::index::BufferCreationError) -> Self {
+        LoadError::gpu("索引缓冲", err)
+    }
+}
+
+impl From<TextureCreationError> for LoadError {
+    fn from(err: TextureCreationError) -> Self {
+        LoadError::gpu("纹理", err)
+    }
+}
@@ -0,0 +1,463 @@
+//! 将内存中的网格与材质导出为Wavefront OBJ + MTL文件
+
+use std::{fs::File, io::{self, Write, BufWriter}, path::Path};
+
+use cgmath::{Matrix4, Matrix3, Vector3, Vector4, SquareMatrix, Matrix, InnerSpace};
+use glium::index::PrimitiveType;
+
+use crate::{Vertex, mesh::{MeshData, VertexPosition}, material::Material, error::LoadError, objectsv2::{RawVertexP, RawVertexPT, RawVertexPC, RawVertexPNC, RawVertexPNTTB, RawVertexPNTJW}};
+
+/**
+ * 可以导出到OBJ的顶点类型，OBJ不支持的属性（如顶点颜色）会被忽略
+ */
+pub trait ExportVertex: VertexPosition {
+    fn normal(&self) -> Option<[f32; 3]> {
+        None
+    }
+
+    fn tex_coords(&self) -> Option<[f32; 2]> {
+        None
+    }
+
+    /// 切线与副切线
+    fn tangent(&self) -> Option<([f32; 3], [f32; 3])> {
+        None
+    }
+}
+
+impl ExportVertex for Vertex {
+    fn normal(&self) -> Option<[f32; 3]> {
+        Some(self.normal)
+    }
+
+    fn tex_coords(&self) -> Option<[f32; 2]> {
+        Some(self.texture)
+    }
+}
+
+impl ExportVertex for RawVertexP {}
+
+impl ExportVertex for RawVertexPT {
+    fn tex_coords(&self) -> Option<[f32; 2]> {
+        Some(self.texture)
+    }
+}
+
+impl ExportVertex for RawVertexPC {}
+
+impl ExportVertex for RawVertexPNC {
+    fn normal(&self) -> Option<[f32; 3]> {
+        Some(self.normal)
+    }
+}
+
+impl ExportVertex for RawVertexPNTTB {
+    fn normal(&self) -> Option<[f32; 3]> {
+        Some(self.normal)
+    }
+
+    fn tex_coords(&self) -> Option<[f32; 2]> {
+        Some(self.tex_coords)
+    }
+
+    fn tangent(&self) -> Option<([f32; 3], [f32; 3])> {
+        Some((self.tangent, self.bitangent))
+    }
+}
+
+/// 蒙皮顶点按绑定姿态导出，关节与权重被忽略
+impl ExportVertex for RawVertexPNTJW {
+    fn normal(&self) -> Option<[f32; 3]> {
+        Some(self.normal)
+    }
+
+    fn tex_coords(&self) -> Option<[f32; 2]> {
+        Some(self.texture)
+    }
+}
+
+/**
+ * 导出到MTL的材质，贴图为相对MTL文件的路径
+ */
+#[derive(Clone, Debug, Default)]
+pub struct ExportMaterial {
+    pub name: String,
+    pub ambient: Option<[f32; 3]>,
+    pub diffuse: Option<[f32; 3]>,
+    pub specular: Option<[f32; 3]>,
+    pub emissive: Option<[f32; 3]>,
+    pub shininess: Option<f32>,
+    pub dissolve: Option<f32>,
+    pub optical_density: Option<f32>,
+    pub illumination_model: Option<i32>,
+    pub ambient_map: Option<String>,
+    pub diffuse_map: Option<String>,
+    pub specular_map: Option<String>,
+    pub emissive_map: Option<String>,
+    pub dissolve_map: Option<String>,
+    pub bump_map: Option<String>,
+}
+
+impl ExportMaterial {
+
+    /// 从已上传到GPU的材质导出颜色参数，贴图已无法还原文件路径，需要时手动设置
+    pub fn from_material(name: &str, material: &Material) -> Self {
+        ExportMaterial {
+            name: name.to_string(),
+            ambient: material.ambient,
+            diffuse: material.diffuse,
+            specular: material.specular,
+            emissive: material.emissive,
+            shininess: material.shininess,
+            dissolve: material.dissolve,
+            optical_density: material.optical_density,
+            illumination_model: material.illumination_model,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&obj::Material> for ExportMaterial {
+    fn from(material: &obj::Material) -> Self {
+        ExportMaterial {
+            name: material.name.clone(),
+            ambient: material.ka,
+            diffuse: material.kd,
+            specular: material.ks,
+            emissive: material.ke,
+            shininess: material.ns,
+            dissolve: material.d,
+            optical_density: material.ni,
+            illumination_model: material.illum,
+            ambient_map: material.map_ka.clone(),
+            diffuse_map: material.map_kd.clone(),
+            specular_map: material.map_ks.clone(),
+            emissive_map: material.map_ke.clone(),
+            dissolve_map: material.map_d.clone(),
+            bump_map: material.map_bump.clone(),
+        }
+    }
+}
+
+/// 变换到导出坐标系后的一个对象
+struct ExportObject {
+    name: String,
+    material: Option<String>,
+    positions: Vec<[f32; 3]>,
+    normals: Option<Vec<[f32; 3]>>,
+    tex_coords: Option<Vec<[f32; 2]>>,
+    tangents: Option<Vec<([f32; 3], [f32; 3])>>,
+    triangles: Vec<[u32; 3]>,
+}
+
+/**
+ * OBJ导出器，可以添加多个网格（各自带模型矩阵）组成一个场景，写入单个OBJ文件及同名MTL文件
+ * ```ignore
+ * let mut exporter = ObjExporter::new();
+ * exporter.add_mesh("sphere", &Sphere::mesh(1.0, 64, 64), Matrix4::from_translation(Vector3::new(0.0, 1.0, 0.0)));
+ * exporter.add_mesh("wall", &PlaneV2::vertical_mesh(2.0, 2.0), Matrix4::identity());
+ * exporter.write("scene.obj")?;
+ * ```
+ */
+#[derive(Default)]
+pub struct ObjExporter {
+    objects: Vec<ExportObject>,
+    materials: Vec<ExportMaterial>,
+}
+
+impl ObjExporter {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * 添加一个网格，顶点经过model变换，法向量使用逆转置矩阵变换
+     * 三角形带与三角形扇会转换为三角形列表，点与线图元不导出
+     */
+    pub fn add_mesh<V: ExportVertex>(&mut self, name: &str, mesh: &MeshData<V>, model: Matrix4<f32>) -> &mut Self {
+        let linear = Matrix3::from_cols(model.x.truncate(), model.y.truncate(), model.z.truncate());
+        let normal_matrix = linear.invert().map(|m| m.transpose()).unwrap_or(linear);
+        let flip_winding = linear.determinant() < 0.0;
+        let normalize = |v: Vector3<f32>| -> [f32; 3] {
+            if v.magnitude2() > 0.0 { v.normalize().into() } else { v.into() }
+        };
+
+        let positions = mesh.vertices.iter().map(|v| {
+            let p = model * Vector4::new(v.position()[0], v.position()[1], v.position()[2], 1.0);
+            [p.x, p.y, p.z]
+        }).collect();
+        let normals = mesh.vertices.iter()
+            .map(|v| v.normal().map(|n| normalize(normal_matrix * Vector3::from(n))))
+            .collect();
+        let tex_coords = mesh.vertices.iter().map(|v| v.tex_coords()).collect();
+        let tangents = mesh.vertices.iter()
+            .map(|v| v.tangent().map(|(t, b)| (normalize(linear * Vector3::from(t)), normalize(linear * Vector3::from(b)))))
+            .collect();
+
+        let mut triangles = to_triangles(&mesh.indices, mesh.primitive_type);
+        if flip_winding {
+            for triangle in triangles.iter_mut() {
+                triangle.swap(1, 2);
+            }
+        }
+
+        self.objects.push(ExportObject {
+            name: name.to_string(),
+            material: mesh.material.clone(),
+            positions,
+            normals,
+            tex_coords,
+            tangents,
+            triangles,
+        });
+        self
+    }
+
+    pub fn add_material(&mut self, material: ExportMaterial) -> &mut Self {
+        self.materials.push(material);
+        self
+    }
+
+    /**
+     * 写入OBJ文件，有材质时在同一目录写入同名的`.mtl`文件
+     */
+    pub fn write<P: AsRef<Path>>(&self, obj_path: P) -> Result<(), LoadError> {
+        let obj_path = obj_path.as_ref();
+        let mtl_path = obj_path.with_extension("mtl");
+        let display_path = |path: &Path| path.to_string_lossy().to_string();
+
+        let mtl_file = if self.materials.is_empty() {
+            None
+        } else {
+            self.write_mtl(&mtl_path).map_err(|err| LoadError::io(&display_path(&mtl_path), err))?;
+            mtl_path.file_name().map(|name| name.to_string_lossy().to_string())
+        };
+        self.write_obj(obj_path, mtl_file.as_deref()).map_err(|err| LoadError::io(&display_path(obj_path), err))
+    }
+
+    fn write_obj(&self, path: &Path, mtl_file: Option<&str>) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        writeln!(w, "# rust_opengl_learn OBJ导出")?;
+        if let Some(mtl_file) = mtl_file {
+            writeln!(w, "mtllib {}", mtl_file)?;
+        }
+
+        // OBJ的索引为全局、从1开始，v、vt、vn分别计数，有些对象没有纹理坐标或法向量
+        let (mut v_offset, mut vt_offset, mut vn_offset) = (1, 1, 1);
+        for object in self.objects.iter() {
+            writeln!(w, "o {}", object.name)?;
+            for p in object.positions.iter() {
+                writeln!(w, "v {} {} {}", p[0], p[1], p[2])?;
+            }
+            if let Some(tex_coords) = &object.tex_coords {
+                for t in tex_coords.iter() {
+                    writeln!(w, "vt {} {}", t[0], t[1])?;
+                }
+            }
+            if let Some(normals) = &object.normals {
+                for n in normals.iter() {
+                    writeln!(w, "vn {} {} {}", n[0], n[1], n[2])?;
+                }
+            }
+            // OBJ没有切线语法，按顶点顺序写为注释
+            if let Some(tangents) = &object.tangents {
+                writeln!(w, "# tangents: tx ty tz bx by bz，与本对象的顶点一一对应")?;
+                for (t, b) in tangents.iter() {
+                    writeln!(w, "#t {} {} {} {} {} {}", t[0], t[1], t[2], b[0], b[1], b[2])?;
+                }
+            }
+
+            if let Some(material) = &object.material {
+                writeln!(w, "usemtl {}", material)?;
+            }
+            for triangle in object.triangles.iter() {
+                write!(w, "f")?;
+                for index in triangle.iter() {
+                    let (v, vt, vn) = (index + v_offset, index + vt_offset, index + vn_offset);
+                    match (object.tex_coords.is_some(), object.normals.is_some()) {
+                        (true, true) => write!(w, " {}/{}/{}", v, vt, vn)?,
+                        (true, false) => write!(w, " {}/{}", v, vt)?,
+                        (false, true) => write!(w, " {}//{}", v, vn)?,
+                        (false, false) => write!(w, " {}", v)?,
+                    }
+                }
+                writeln!(w)?;
+            }
+            let count = object.positions.len() as u32;
+            v_offset += count;
+            if object.tex_coords.is_some() {
+                vt_offset += count;
+            }
+            if object.normals.is_some() {
+                vn_offset += count;
+            }
+        }
+        w.flush()
+    }
+
+    fn write_mtl(&self, path: &Path) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        writeln!(w, "# rust_opengl_learn MTL导出")?;
+        for material in self.materials.iter() {
+            writeln!(w)?;
+            writeln!(w, "newmtl {}", material.name)?;
+            let colors = [("Ka", material.ambient), ("Kd", material.diffuse), ("Ks", material.specular), ("Ke", material.emissive)];
+            for (key, color) in colors.iter() {
+                if let Some(c) = color {
+                    writeln!(w, "{} {} {} {}", key, c[0], c[1], c[2])?;
+                }
+            }
+            let scalars = [("Ns", material.shininess), ("d", material.dissolve), ("Ni", material.optical_density)];
+            for (key, value) in scalars.iter() {
+                if let Some(value) = value {
+                    writeln!(w, "{} {}", key, value)?;
+                }
+            }
+            if let Some(illum) = material.illumination_model {
+                writeln!(w, "illum {}", illum)?;
+            }
+            let maps = [
+                ("map_Ka", &material.ambient_map),
+                ("map_Kd", &material.diffuse_map),
+                ("map_Ks", &material.specular_map),
+                ("map_Ke", &material.emissive_map),
+                ("map_d", &material.dissolve_map),
+                ("bump", &material.bump_map),
+            ];
+            for (key, map) in maps.iter() {
+                if let Some(map) = map {
+                    writeln!(w, "{} {}", key, map)?;
+                }
+            }
+        }
+        w.flush()
+    }
+}
+
+/// 将三角形列表、带、扇转换为三角形列表，带的奇数三角形翻转绕序以保持一致
+fn to_triangles(indices: &[u32], primitive_type: PrimitiveType) -> Vec<[u32; 3]> {
+    let mut triangles = Vec::new();
+    match primitive_type {
+        PrimitiveType::TrianglesList => {
+            for t in indices.chunks_exact(3) {
+                triangles.push([t[0], t[1], t[2]]);
+            }
+        },
+        PrimitiveType::TriangleStrip => {
+            for i in 0..indices.len().saturating_sub(2) {
+                let t = if i % 2 == 0 {
+                    [indices[i], indices[i + 1], indices[i + 2]]
+                } else {
+                    [indices[i + 1], indices[i], indices[i + 2]]
+                };
+                triangles.push(t);
+            }
+        },
+        PrimitiveType::TriangleFan => {
+            for i in 1..indices.len().saturating_sub(1) {
+                triangles.push([indices[0], indices[i], indices[i + 1]]);
+            }
+        },
+        _ => {},
+    }
+    // 三角形带中用于连接的退化三角形不导出
+    triangles.retain(|t| t[0] != t[1] && t[1] != t[2] && t[0] != t[2]);
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_alternates_winding() {
+        let triangles = to_triangles(&[0, 1, 2, 3, 4], PrimitiveType::TriangleStrip);
+        // 奇数三角形交换前两个顶点，所有三角形与第一个绕序一致
+        assert_eq!(triangles, vec![[0, 1, 2], [2, 1, 3], [2, 3, 4]]);
+    }
+
+    #[test]
+    fn degenerate_triangles_are_dropped() {
+        // 两段带之间用重复索引连接，连接处的4个退化三角形被删除
+        let triangles = to_triangles(&[0, 1, 2, 3, 3, 4, 4, 5, 6, 7], PrimitiveType::TriangleStrip);
+        assert_eq!(triangles, vec![[0, 1, 2], [2, 1, 3], [4, 5, 6], [6, 5, 7]]);
+
+        let triangles = to_triangles(&[0, 1, 1, 2, 3, 4], PrimitiveType::TrianglesList);
+        assert_eq!(triangles, vec![[2, 3, 4]]);
+    }
+
+    #[test]
+    fn fan_shares_first_vertex() {
+        assert_eq!(to_triangles(&[0, 1, 2, 3], PrimitiveType::TriangleFan), vec![[0, 1, 2], [0, 2, 3]]);
+        assert!(to_triangles(&[0, 1], PrimitiveType::TriangleFan).is_empty());
+        assert!(to_triangles(&[0, 1, 2], PrimitiveType::LinesList).is_empty());
+    }
+
+    fn quad(material: &str) -> MeshData<Vertex> {
+        let vertex = |x: f32, y: f32| Vertex { position: [x, y, 0.0], normal: [0.0, 0.0, 1.0], texture: [x, y] };
+        MeshData::new(vec![vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(1.0, 1.0), vertex(0.0, 1.0)], vec![0, 1, 2, 0, 2, 3], PrimitiveType::TrianglesList)
+            .with_material(Some(material.to_string()))
+    }
+
+    #[test]
+    fn round_trip_through_obj_crate() {
+        let dir = std::env::temp_dir().join(format!("export_round_trip_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("scene.obj");
+
+        // 中间的对象没有纹理坐标和法向量，之后对象的vt、vn偏移不应计入它的顶点
+        let positions_only = MeshData::new(
+            vec![RawVertexP { position: [0.0, 0.0, 0.0] }, RawVertexP { position: [0.0, 1.0, 0.0] }, RawVertexP { position: [0.0, 0.0, 1.0] }],
+            vec![0, 1, 2],
+            PrimitiveType::TrianglesList,
+        );
+        let mut exporter = ObjExporter::new();
+        exporter
+            .add_mesh("first", &quad("red"), Matrix4::identity())
+            .add_mesh("bare", &positions_only, Matrix4::identity())
+            .add_mesh("moved", &quad("blue"), Matrix4::from_translation(Vector3::new(2.0, 0.0, 0.0)))
+            .add_material(ExportMaterial { name: "red".to_string(), diffuse: Some([1.0, 0.0, 0.0]), ..Default::default() })
+            .add_material(ExportMaterial { name: "blue".to_string(), diffuse: Some([0.0, 0.0, 1.0]), diffuse_map: Some("blue.png".to_string()), ..Default::default() });
+        exporter.write(&path).unwrap();
+
+        let mut obj = obj::Obj::load(&path).unwrap();
+        obj.load_mtls().unwrap();
+        let data = &obj.data;
+        assert_eq!(data.position.len(), 11);
+        assert_eq!(data.texture.len(), 8);
+        assert_eq!(data.normal.len(), 8);
+        assert_eq!(data.objects.iter().map(|o| o.name.as_str()).collect::<Vec<_>>(), vec!["first", "bare", "moved"]);
+
+        // 按对象还原每个三角形的顶点位置与纹理坐标
+        let corners = |object: usize| -> Vec<([f32; 3], Option<[f32; 2]>)> {
+            data.objects[object].groups.iter()
+                .flat_map(|group| group.polys.iter())
+                .flat_map(|poly| poly.0.iter())
+                .map(|obj::IndexTuple(v, vt, _)| (data.position[*v], vt.map(|vt| data.texture[vt])))
+                .collect()
+        };
+        let first = corners(0);
+        assert_eq!(first.len(), 6);
+        assert_eq!(first[2], ([1.0, 1.0, 0.0], Some([1.0, 1.0])));
+        assert_eq!(corners(1), vec![([0.0, 0.0, 0.0], None), ([0.0, 1.0, 0.0], None), ([0.0, 0.0, 1.0], None)]);
+        let moved = corners(2);
+        assert_eq!(moved[2], ([3.0, 1.0, 0.0], Some([1.0, 1.0])));
+        assert!(moved.iter().all(|(p, t)| p[0] - 2.0 == t.unwrap()[0] && p[1] == t.unwrap()[1]));
+        // 法向量索引同样按对象偏移
+        for group in data.objects[2].groups.iter() {
+            for poly in group.polys.iter() {
+                assert!(poly.0.iter().all(|obj::IndexTuple(_, _, vn)| vn.is_some_and(|vn| vn >= 4 && data.normal[vn] == [0.0, 0.0, 1.0])));
+            }
+        }
+
+        let material = |object: usize| match &data.objects[object].groups[0].material {
+            Some(obj::ObjMaterial::Mtl(material)) => material.clone(),
+            _ => panic!("对象{}没有材质", object),
+        };
+        assert_eq!(material(0).kd, Some([1.0, 0.0, 0.0]));
+        assert_eq!(material(2).map_kd.as_deref(), Some("blue.png"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+use cgmath::{Point3, Vector3, Matrix4, Transform, InnerSpace, EuclideanSpace};
+
+/**
+ * 轴对齐包围盒
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+
+    /// 空包围盒，min为正无穷、max为负无穷，扩展任意点后即为该点
+    pub fn empty() -> Self {
+        Aabb {
+            min: Point3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Point3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    pub fn from_points<I: IntoIterator<Item = [f32; 3]>>(points: I) -> Self {
+        let mut aabb = Self::empty();
+        for point in points {
+            aabb.extend(point);
+        }
+        aabb
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// 扩展包围盒使其包含指定点
+    pub fn extend(&mut self, point: [f32; 3]) {
+        self.min.x = self.min.x.min(point[0]);
+        self.min.y = self.min.y.min(point[1]);
+        self.min.z = self.min.z.min(point[2]);
+        self.max.x = self.max.x.max(point[0]);
+        self.max.y = self.max.y.max(point[1]);
+        self.max.z = self.max.z.max(point[2]);
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        self.min.midpoint(self.max)
+    }
+
+    /// 各轴方向的长度
+    pub fn size(&self) -> Vector3<f32> {
+        self.max - self.min
+    }
+
+    /// 8个角点
+    pub fn corners(&self) -> [Point3<f32>; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Point3::new(min.x, min.y, min.z),
+            Point3::new(max.x, min.y, min.z),
+            Point3::new(min.x, max.y, min.z),
+            Point3::new(max.x, max.y, min.z),
+            Point3::new(min.x, min.y, max.z),
+            Point3::new(max.x, min.y, max.z),
+            Point3::new(min.x, max.y, max.z),
+            Point3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    /// 同时包含两个包围盒的包围盒
+    pub fn merge(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Point3::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y), self.min.z.min(other.min.z)),
+            max: Point3::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y), self.max.z.max(other.max.z)),
+        }
+    }
+
+    /// 经过模型矩阵变换后的包围盒，变换8个角点后重新求轴对齐包围盒
+    pub fn transform(&self, model: &Matrix4<f32>) -> Aabb {
+        if self.is_empty() {
+            return *self;
+        }
+        Aabb::from_points(self.corners().iter().map(|corner| model.transform_point(*corner).into()))
+    }
+
+    /// 外接球
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        if self.is_empty() {
+            return BoundingSphere::empty();
+        }
+        BoundingSphere { center: self.center(), radius: self.size().magnitude() / 2.0 }
+    }
+}
+
+/**
+ * 包围球，半径小于0表示空
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Point3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+
+    pub fn empty() -> Self {
+        BoundingSphere { center: Point3::new(0.0, 0.0, 0.0), radius: -1.0 }
+    }
+
+    /**
+     * 以包围盒中心为球心，到最远点的距离为半径
+     * 比包围盒的外接球更紧，适合用于剔除与相机取景
+     */
+    pub fn from_points<I: IntoIterator<Item = [f32; 3]> + Clone>(points: I) -> Self {
+        let aabb = Aabb::from_points(points.clone());
+        if aabb.is_empty() {
+            return Self::empty();
+        }
+        let center = aabb.center();
+        let radius = points.into_iter()
+            .map(|point| (Point3::from(point) - center).magnitude2())
+            .fold(0.0_f32, f32::max)
+            .sqrt();
+        BoundingSphere { center, radius }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.radius < 0.0
+    }
+
+    /// 同时包含两个包围球的最小包围球
+    pub fn merge(&self, other: &BoundingSphere) -> BoundingSphere {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let offset = other.center - self.center;
+        let distance = offset.magnitude();
+        if distance + other.radius <= self.radius {
+            return *self;
+        }
+        if distance + self.radius <= other.radius {
+            return *other;
+        }
+        let radius = (distance + self.radius + other.radius) / 2.0;
+        let center = self.center + offset * ((radius - self.radius) / distance);
+        BoundingSphere { center, radius }
+    }
+
+    /// 经过模型矩阵变换后的包围球，非均匀缩放时按最大缩放轴放大半径
+    pub fn transform(&self, model: &Matrix4<f32>) -> BoundingSphere {
+        if self.is_empty() {
+            return *self;
+        }
+        let scale = model.x.truncate().magnitude()
+            .max(model.y.truncate().magnitude())
+            .max(model.z.truncate().magnitude());
+        BoundingSphere { center: model.transform_point(self.center), radius: self.radius * scale }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Deg;
+
+    use super::*;
+
+    fn contains_sphere(outer: &BoundingSphere, inner: &BoundingSphere) -> bool {
+        (inner.center - outer.center).magnitude() + inner.radius <= outer.radius + 1e-4
+    }
+
+    fn contains_point(aabb: &Aabb, point: Point3<f32>) -> bool {
+        let eps = 1e-4;
+        point.x >= aabb.min.x - eps && point.y >= aabb.min.y - eps && point.z >= aabb.min.z - eps
+            && point.x <= aabb.max.x + eps && point.y <= aabb.max.y + eps && point.z <= aabb.max.z + eps
+    }
+
+    fn assert_point_eq(a: Point3<f32>, b: Point3<f32>) {
+        assert!((a - b).magnitude() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    /// 单位球面上的采样点
+    fn sphere_points() -> Vec<Vector3<f32>> {
+        let mut points = Vec::new();
+        for i in 0..12 {
+            for j in 0..=6 {
+                let (theta, phi) = (i as f32 * 30.0_f32.to_radians(), j as f32 * 30.0_f32.to_radians());
+                points.push(Vector3::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin()));
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn aabb_transform_with_rotation_and_non_uniform_scale() {
+        let aabb = Aabb { min: Point3::new(-1.0, -2.0, -0.5), max: Point3::new(1.0, 2.0, 0.5) };
+        // 缩放后x∈[-3,3]，绕z轴旋转90°后x、y互换，再平移
+        let model = Matrix4::from_translation(Vector3::new(1.0, 2.0, 3.0))
+            * Matrix4::from_angle_z(Deg(90.0))
+            * Matrix4::from_nonuniform_scale(3.0, 1.0, 1.0);
+        let transformed = aabb.transform(&model);
+        assert_point_eq(transformed.min, Point3::new(-1.0, -1.0, 2.5));
+        assert_point_eq(transformed.max, Point3::new(3.0, 5.0, 3.5));
+
+        // 任意角度下变换后的角点都在新包围盒内
+        let model = Matrix4::from_angle_y(Deg(30.0)) * Matrix4::from_angle_x(Deg(45.0)) * Matrix4::from_nonuniform_scale(2.0, 0.5, 4.0);
+        let transformed = aabb.transform(&model);
+        for corner in aabb.corners() {
+            assert!(contains_point(&transformed, model.transform_point(corner)));
+        }
+        assert!(Aabb::empty().transform(&model).is_empty());
+    }
+
+    #[test]
+    fn sphere_merge_contains_both() {
+        let a = BoundingSphere { center: Point3::new(0.0, 0.0, 0.0), radius: 1.0 };
+        let b = BoundingSphere { center: Point3::new(4.0, 1.0, 0.0), radius: 2.0 };
+        for merged in [a.merge(&b), b.merge(&a)] {
+            assert!(contains_sphere(&merged, &a) && contains_sphere(&merged, &b));
+            // 两球分离时合并结果恰好与两球外切
+            assert!((merged.radius - (17.0_f32.sqrt() + 3.0) / 2.0).abs() < 1e-5);
+        }
+
+        // 一个球在另一个球内部时返回外面的球
+        let inner = BoundingSphere { center: Point3::new(0.5, 0.0, 0.0), radius: 0.25 };
+        assert_eq!(a.merge(&inner), a);
+        assert_eq!(inner.merge(&a), a);
+
+        // 同心球
+        let concentric = BoundingSphere { center: a.center, radius: 3.0 };
+        assert_eq!(a.merge(&concentric), concentric);
+
+        assert_eq!(a.merge(&BoundingSphere::empty()), a);
+        assert_eq!(BoundingSphere::empty().merge(&b), b);
+    }
+
+    #[test]
+    fn sphere_transform_contains_transformed_points() {
+        let sphere = BoundingSphere { center: Point3::new(1.0, -1.0, 2.0), radius: 1.5 };
+        let model = Matrix4::from_translation(Vector3::new(-3.0, 0.5, 2.0))
+            * Matrix4::from_angle_z(Deg(40.0))
+            * Matrix4::from_nonuniform_scale(1.0, 2.0, 3.0);
+        let transformed = sphere.transform(&model);
+        assert_point_eq(transformed.center, model.transform_point(sphere.center));
+        assert!((transformed.radius - 4.5).abs() < 1e-5);
+        for direction in sphere_points() {
+            let point = model.transform_point(sphere.center + direction * sphere.radius);
+            assert!((point - transformed.center).magnitude() <= transformed.radius + 1e-4);
+        }
+        assert!(BoundingSphere::empty().transform(&model).is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+use cgmath::{Point3, Rad, InnerSpace, Angle};
+
+use super::bounds::BoundingSphere;
+
+/**
+ * 根据物体在屏幕上的投影大小选择LOD级别
+ * thresholds按从大到小排列，投影大小小于第i个阈值时至少使用第i+1级，例如`[0.2, 0.08, 0.03]`：
+ * 不小于0.2使用第0级（原始网格），0.08到0.2使用第1级，依此类推
+ */
+#[derive(Clone, Debug)]
+pub struct LodSelector {
+    pub thresholds: Vec<f32>,
+}
+
+impl LodSelector {
+
+    pub fn new(thresholds: Vec<f32>) -> Self {
+        LodSelector { thresholds }
+    }
+
+    /// 返回LOD级别，0为最精细
+    pub fn select(&self, screen_size: f32) -> usize {
+        self.thresholds.iter().take_while(|threshold| screen_size < **threshold).count()
+    }
+
+    /// 根据世界坐标系下的包围球与相机参数选择LOD级别
+    pub fn select_for(&self, sphere: &BoundingSphere, camera_position: Point3<f32>, fovy: Rad<f32>) -> usize {
+        self.select(projected_size(sphere, camera_position, fovy))
+    }
+}
+
+/**
+ * 包围球在透视投影下的直径占屏幕高度的比例
+ * fovy为垂直视野角度，相机位于包围球内时返回1.0
+ */
+pub fn projected_size(sphere: &BoundingSphere, camera_position: Point3<f32>, fovy: Rad<f32>) -> f32 {
+    let distance = (sphere.center - camera_position).magnitude();
+    if distance <= sphere.radius {
+        return 1.0;
+    }
+    (sphere.radius / (distance * (fovy / 2.0).tan())).min(1.0)
+}
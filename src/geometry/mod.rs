@@ -0,0 +1,20 @@
+//! CPU端的几何处理，不依赖OpenGL上下文
+
+use cgmath::{Vector3, InnerSpace};
+
+pub mod triangulate;
+pub mod tangent;
+pub mod normals;
+pub mod bounds;
+pub mod simplify;
+pub mod lod;
+pub mod vcache;
+
+/// 归一化向量，长度为零（或小到无法可靠归一化）时返回fallback
+pub(crate) fn safe_normalize(v: Vector3<f32>, fallback: Vector3<f32>) -> Vector3<f32> {
+    if v.magnitude2() <= f32::MIN_POSITIVE {
+        fallback
+    } else {
+        v.normalize()
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use cgmath::{Vector3, InnerSpace, Zero, Deg, Rad, Angle};
+
+use super::safe_normalize;
+
+/**
+ * 法向量生成方式
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub enum NormalMode {
+    /// 面法向量，每个面的顶点互不共享
+    Flat,
+    /// 按面积加权平滑，共享位置的所有面参与平均
+    #[default]
+    Smooth,
+    /// 按面积加权平滑，但与当前面夹角超过阈值的面不参与平均，用于保留硬边
+    SmoothCrease(Deg<f32>),
+}
+
+/**
+ * 为三角形列表生成法向量
+ * positions为顶点位置，triangles为位置索引组成的三角形
+ * 返回每个三角形三个角的法向量，共享位置的角在平滑模式下得到相同的法向量
+ */
+pub fn generate_normals(positions: &[[f32; 3]], triangles: &[[usize; 3]], mode: NormalMode) -> Vec<[[f32; 3]; 3]> {
+    // 未归一化的面法向量，其长度为三角形面积的两倍，天然实现面积加权
+    let face_normals: Vec<Vector3<f32>> = triangles.iter().map(|triangle| {
+        let a = Vector3::from(positions[triangle[0]]);
+        let b = Vector3::from(positions[triangle[1]]);
+        let c = Vector3::from(positions[triangle[2]]);
+        (b - a).cross(c - a)
+    }).collect();
+
+    if let NormalMode::Flat = mode {
+        return face_normals.iter().map(|normal| {
+            let normal: [f32; 3] = safe_normalize(*normal, Vector3::zero()).into();
+            [normal; 3]
+        }).collect();
+    }
+
+    // 位置索引 -> 引用该位置的三角形
+    let mut adjacency: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (face, triangle) in triangles.iter().enumerate() {
+        for position in triangle.iter() {
+            adjacency.entry(*position).or_default().push(face);
+        }
+    }
+
+    match mode {
+        NormalMode::SmoothCrease(angle) => {
+            let cos_threshold = Rad::from(angle).cos();
+            let unit_normals: Vec<Vector3<f32>> = face_normals.iter().map(|normal| safe_normalize(*normal, Vector3::zero())).collect();
+            triangles.iter().enumerate().map(|(face, triangle)| {
+                triangle.map(|position| {
+                    let mut sum = Vector3::zero();
+                    for other in adjacency[&position].iter() {
+                        if *other == face || unit_normals[face].dot(unit_normals[*other]) >= cos_threshold {
+                            sum += face_normals[*other];
+                        }
+                    }
+                    safe_normalize(sum, Vector3::zero()).into()
+                })
+            }).collect()
+        },
+        _ => {
+            let mut vertex_normals: HashMap<usize, [f32; 3]> = HashMap::with_capacity(adjacency.len());
+            for (position, faces) in adjacency.iter() {
+                let sum = faces.iter().fold(Vector3::zero(), |sum, face| sum + face_normals[*face]);
+                vertex_normals.insert(*position, safe_normalize(sum, Vector3::zero()).into());
+            }
+            triangles.iter().map(|triangle| triangle.map(|position| vertex_normals[&position])).collect()
+        },
+    }
+}
+
+/**
+ * 为共享顶点的三角形列表生成逐顶点的平滑法向量，indices为顶点索引
+ * 用于glTF、PLY等顶点已经去重、缺少法向量的格式
+ */
+pub fn smooth_vertex_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    let triangles: Vec<[usize; 3]> = indices.chunks_exact(3)
+        .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+        .collect();
+    let corner_normals = generate_normals(positions, &triangles, NormalMode::Smooth);
+    let mut normals = vec![[0.0, 0.0, 0.0]; positions.len()];
+    for (triangle, corners) in triangles.iter().zip(corner_normals.iter()) {
+        for (position, normal) in triangle.iter().zip(corners.iter()) {
+            normals[*position] = *normal;
+        }
+    }
+    normals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 8个角点共享的单位立方体，每个面两个向外的三角形
+    fn cube() -> (Vec<[f32; 3]>, Vec<[usize; 3]>) {
+        let positions = (0..8).map(|i| [
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { -1.0 } else { 1.0 },
+        ]).collect();
+        let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+        let triangles = quads.iter().flat_map(|q| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]]).collect();
+        (positions, triangles)
+    }
+
+    /// 三角形所在面的外法向量，即三个角点坐标中相同的那个轴
+    fn face_axis(positions: &[[f32; 3]], triangle: &[usize; 3]) -> Vector3<f32> {
+        let mut axis = Vector3::zero();
+        for k in 0..3 {
+            let value = positions[triangle[0]][k];
+            if triangle.iter().all(|i| positions[*i][k] == value) {
+                axis[k] = value;
+            }
+        }
+        axis
+    }
+
+    #[test]
+    fn flat_normals_follow_faces() {
+        let (positions, triangles) = cube();
+        let normals = generate_normals(&positions, &triangles, NormalMode::Flat);
+        for (triangle, corners) in triangles.iter().zip(normals.iter()) {
+            let axis = face_axis(&positions, triangle);
+            for normal in corners.iter() {
+                assert!((Vector3::from(*normal) - axis).magnitude() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_normals_point_out_of_corners() {
+        let (positions, triangles) = cube();
+        let normals = generate_normals(&positions, &triangles, NormalMode::Smooth);
+        for (triangle, corners) in triangles.iter().zip(normals.iter()) {
+            for (position, normal) in triangle.iter().zip(corners.iter()) {
+                let normal = Vector3::from(*normal);
+                let diagonal = Vector3::from(positions[*position]).normalize();
+                assert!((normal.magnitude() - 1.0).abs() < 1e-5);
+                // 面积加权后每个面贡献1或2个三角形，法向量接近对角线方向
+                assert!(normal.dot(diagonal) > 0.9, "{:?}", normal);
+            }
+        }
+        // 共享位置的角得到相同的法向量
+        let smooth = smooth_vertex_normals(&positions, &triangles.iter().flatten().map(|i| *i as u32).collect::<Vec<u32>>());
+        for (triangle, corners) in triangles.iter().zip(normals.iter()) {
+            for (position, normal) in triangle.iter().zip(corners.iter()) {
+                assert_eq!(smooth[*position], *normal);
+            }
+        }
+    }
+
+    #[test]
+    fn crease_angle_selects_flat_or_smooth() {
+        let (positions, triangles) = cube();
+        let flat = generate_normals(&positions, &triangles, NormalMode::Flat);
+        let smooth = generate_normals(&positions, &triangles, NormalMode::Smooth);
+        let close = |a: &[[[f32; 3]; 3]], b: &[[[f32; 3]; 3]]| a.iter().flatten().zip(b.iter().flatten())
+            .all(|(a, b)| (Vector3::from(*a) - Vector3::from(*b)).magnitude() < 1e-5);
+
+        // 立方体相邻面夹角为90°，阈值小于90°时保留硬边
+        assert!(close(&generate_normals(&positions, &triangles, NormalMode::SmoothCrease(Deg(60.0))), &flat));
+        assert!(close(&generate_normals(&positions, &triangles, NormalMode::SmoothCrease(Deg(100.0))), &smooth));
+    }
+}
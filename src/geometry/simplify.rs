@@ -0,0 +1,436 @@
+use std::{collections::{BinaryHeap, HashMap}, cmp::Ordering};
+
+use cgmath::{Vector3, InnerSpace};
+
+/**
+ * 基于二次误差度量（Garland-Heckbert QEM）的网格简化
+ * 使用半边折叠：顶点u折叠到相邻顶点v上，不产生新顶点，简化结果只是原顶点数组上的一组新索引，
+ * 因此各级LOD可以共享同一个顶点缓冲
+ * 位于网格边界或纹理/法向量接缝（同一位置有多个顶点）上的顶点不会被折叠，以免产生裂缝
+ * 折叠前检查连接条件（link condition），不会产生被两个以上三角形共用的非流形边
+ *
+ * positions为顶点位置，indices为三角形列表索引，target_index_count为目标索引数量
+ * 无法继续折叠时返回的索引数量可能大于目标
+ */
+pub fn simplify(positions: &[[f32; 3]], indices: &[u32], target_index_count: usize) -> Vec<u32> {
+    let mut triangles: Vec<[u32; 3]> = indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+    let mut alive = vec![true; triangles.len()];
+    let mut live_count = triangles.len();
+    let target_triangles = target_index_count / 3;
+    if live_count <= target_triangles {
+        return indices.to_vec();
+    }
+
+    let vertex_count = positions.len();
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    let mut quadrics = vec![Quadric::default(); vertex_count];
+    for (face, triangle) in triangles.iter().enumerate() {
+        let quadric = Quadric::from_triangle(triangle.map(|i| positions[i as usize]));
+        for i in triangle.iter() {
+            adjacency[*i as usize].push(face);
+            quadrics[*i as usize].add(&quadric);
+        }
+    }
+
+    let locked = locked_vertices(positions, &triangles);
+    let mut removed = vec![false; vertex_count];
+    let mut versions = vec![0u32; vertex_count];
+
+    let mut heap = BinaryHeap::new();
+    for triangle in triangles.iter() {
+        for k in 0..3 {
+            let (a, b) = (triangle[k] as usize, triangle[(k + 1) % 3] as usize);
+            push_candidate(&mut heap, positions, &quadrics, &locked, &versions, a, b);
+            push_candidate(&mut heap, positions, &quadrics, &locked, &versions, b, a);
+        }
+    }
+
+    while live_count > target_triangles {
+        let collapse = match heap.pop() {
+            Some(collapse) => collapse,
+            None => break,
+        };
+        let (u, v) = (collapse.from, collapse.to);
+        if removed[u] || removed[v] || versions[u] != collapse.from_version || versions[v] != collapse.to_version {
+            continue;
+        }
+        if !satisfies_link_condition(&triangles, &alive, &adjacency, u, v)
+            || !can_collapse(positions, &triangles, &alive, &adjacency[u], u, v) {
+            continue;
+        }
+
+        // 折叠u到v：共享边的三角形退化后删除，其余三角形改为引用v
+        let faces = std::mem::take(&mut adjacency[u]);
+        for face in faces {
+            if !alive[face] {
+                continue;
+            }
+            let triangle = &mut triangles[face];
+            if triangle.contains(&(v as u32)) {
+                alive[face] = false;
+                live_count -= 1;
+            } else {
+                for i in triangle.iter_mut() {
+                    if *i as usize == u {
+                        *i = v as u32;
+                    }
+                }
+                adjacency[v].push(face);
+            }
+        }
+        let quadric = quadrics[u];
+        quadrics[v].add(&quadric);
+        removed[u] = true;
+        versions[v] += 1;
+
+        // v的二次误差改变，重新计算v所在所有边的折叠代价
+        adjacency[v].retain(|face| alive[*face]);
+        let mut neighbors: Vec<usize> = adjacency[v].iter()
+            .flat_map(|face| triangles[*face].iter().map(|i| *i as usize))
+            .filter(|i| *i != v)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        for w in neighbors {
+            push_candidate(&mut heap, positions, &quadrics, &locked, &versions, v, w);
+            push_candidate(&mut heap, positions, &quadrics, &locked, &versions, w, v);
+        }
+    }
+
+    triangles.iter().zip(alive.iter())
+        .filter(|(_, alive)| **alive)
+        .flat_map(|(triangle, _)| triangle.iter().copied())
+        .collect()
+}
+
+/// 边界顶点与接缝顶点
+fn locked_vertices(positions: &[[f32; 3]], triangles: &[[u32; 3]]) -> Vec<bool> {
+    let mut locked = vec![false; positions.len()];
+
+    // 只被一个三角形使用的边为边界
+    let mut edges: HashMap<(u32, u32), u32> = HashMap::new();
+    for triangle in triangles.iter() {
+        for k in 0..3 {
+            let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+            *edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+    }
+    for ((a, b), count) in edges {
+        if count == 1 {
+            locked[a as usize] = true;
+            locked[b as usize] = true;
+        }
+    }
+
+    // 位置相同的多个顶点（纹理坐标或法向量不同）为接缝
+    let mut by_position: HashMap<[u32; 3], usize> = HashMap::new();
+    for (i, position) in positions.iter().enumerate() {
+        if let Some(first) = by_position.insert(position.map(f32::to_bits), i) {
+            locked[first] = true;
+            locked[i] = true;
+        }
+    }
+    locked
+}
+
+/**
+ * 连接条件：u与v的公共相邻顶点只能是共享边uv的三角形的第三个顶点
+ * 否则折叠后u、v与该公共顶点组成的两条边会重合，被两个以上三角形共用
+ */
+fn satisfies_link_condition(triangles: &[[u32; 3]], alive: &[bool], adjacency: &[Vec<usize>], u: usize, v: usize) -> bool {
+    let ring = |center: usize| {
+        let mut ring: Vec<u32> = adjacency[center].iter()
+            .filter(|face| alive[**face])
+            .flat_map(|face| triangles[*face].iter().copied())
+            .filter(|i| *i as usize != u && *i as usize != v)
+            .collect();
+        ring.sort_unstable();
+        ring.dedup();
+        ring
+    };
+    let ring_v = ring(v);
+    let common: Vec<u32> = ring(u).into_iter().filter(|i| ring_v.binary_search(i).is_ok()).collect();
+
+    let mut opposite: Vec<u32> = adjacency[u].iter()
+        .filter(|face| alive[**face] && triangles[**face].contains(&(v as u32)))
+        .flat_map(|face| triangles[*face].iter().copied())
+        .filter(|i| *i as usize != u && *i as usize != v)
+        .collect();
+    opposite.sort_unstable();
+    opposite.dedup();
+    common == opposite
+}
+
+/// 检查折叠是否会使相邻三角形翻转或退化
+fn can_collapse(positions: &[[f32; 3]], triangles: &[[u32; 3]], alive: &[bool], faces: &[usize], u: usize, v: usize) -> bool {
+    for face in faces.iter() {
+        if !alive[*face] {
+            continue;
+        }
+        let triangle = triangles[*face];
+        if triangle.contains(&(v as u32)) {
+            continue;
+        }
+        let before = triangle_normal(triangle.map(|i| positions[i as usize]));
+        let after = triangle_normal(triangle.map(|i| positions[if i as usize == u { v } else { i as usize }]));
+        // 夹角超过约75°也视为翻转，避免多次折叠逐步转过90°或生成狭长的竖直三角形
+        if after.magnitude2() == 0.0 || before.dot(after) <= 0.25 * before.magnitude() * after.magnitude() {
+            return false;
+        }
+    }
+    true
+}
+
+fn triangle_normal(triangle: [[f32; 3]; 3]) -> Vector3<f32> {
+    let a = Vector3::from(triangle[0]);
+    let b = Vector3::from(triangle[1]);
+    let c = Vector3::from(triangle[2]);
+    (b - a).cross(c - a)
+}
+
+fn push_candidate(heap: &mut BinaryHeap<Collapse>, positions: &[[f32; 3]], quadrics: &[Quadric], locked: &[bool], versions: &[u32], from: usize, to: usize) {
+    if locked[from] {
+        return;
+    }
+    let mut quadric = quadrics[from];
+    quadric.add(&quadrics[to]);
+    heap.push(Collapse {
+        cost: quadric.error(positions[to]),
+        from,
+        to,
+        from_version: versions[from],
+        to_version: versions[to],
+    });
+}
+
+/// 候选折叠，版本号与顶点当前版本不一致时说明代价已过期
+struct Collapse {
+    cost: f64,
+    from: usize,
+    to: usize,
+    from_version: u32,
+    to_version: u32,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    /// BinaryHeap为最大堆，代价越小优先级越高
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// 对称4x4误差矩阵的上三角10个分量
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+
+    /// 三角形所在平面的误差矩阵，按面积加权
+    fn from_triangle(triangle: [[f32; 3]; 3]) -> Self {
+        let normal = triangle_normal(triangle);
+        let double_area = normal.magnitude();
+        if double_area == 0.0 {
+            return Quadric::default();
+        }
+        let n = normal / double_area;
+        let (a, b, c) = (n.x as f64, n.y as f64, n.z as f64);
+        let d = -(a * triangle[0][0] as f64 + b * triangle[0][1] as f64 + c * triangle[0][2] as f64);
+        let weight = double_area as f64 / 2.0;
+        Quadric([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d].map(|q| q * weight))
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (q, o) in self.0.iter_mut().zip(other.0.iter()) {
+            *q += *o;
+        }
+    }
+
+    /// 点到所有平面距离平方的加权和
+    fn error(&self, point: [f32; 3]) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (point[0] as f64, point[1] as f64, point[2] as f64);
+        q[0] * x * x + 2.0 * q[1] * x * y + 2.0 * q[2] * x * z + 2.0 * q[3] * x
+            + q[4] * y * y + 2.0 * q[5] * y * z + 2.0 * q[6] * y
+            + q[7] * z * z + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// n×n个格子的高度场网格，三角形逆时针朝向+z
+    fn grid(n: u32, height: impl Fn(f32, f32) -> f32) -> (Vec<[f32; 3]>, Vec<u32>) {
+        let mut positions = Vec::new();
+        for y in 0..=n {
+            for x in 0..=n {
+                let (fx, fy) = (x as f32 / n as f32, y as f32 / n as f32);
+                positions.push([fx, fy, height(fx, fy)]);
+            }
+        }
+        let mut indices = Vec::new();
+        let index = |x: u32, y: u32| y * (n + 1) + x;
+        for y in 0..n {
+            for x in 0..n {
+                indices.extend([index(x, y), index(x + 1, y), index(x + 1, y + 1)]);
+                indices.extend([index(x, y), index(x + 1, y + 1), index(x, y + 1)]);
+            }
+        }
+        (positions, indices)
+    }
+
+    fn normal(positions: &[[f32; 3]], triangle: &[u32]) -> Vector3<f32> {
+        triangle_normal([0, 1, 2].map(|k| positions[triangle[k] as usize]))
+    }
+
+    fn bumps(x: f32, y: f32) -> f32 {
+        0.05 * (x * 6.0).sin() * (y * 4.0).cos()
+    }
+
+    #[test]
+    fn reaches_target_on_grid() {
+        let (positions, indices) = grid(16, bumps);
+        let target = indices.len() / 4;
+        let simplified = simplify(&positions, &indices, target);
+        assert_eq!(simplified.len() % 3, 0);
+        // 每次折叠删除两个三角形
+        assert!(simplified.len() <= target && simplified.len() + 6 > target, "{} -> {}", indices.len(), simplified.len());
+    }
+
+    #[test]
+    fn keeps_border_vertices_and_orientation() {
+        let n = 12;
+        let (positions, indices) = grid(n, bumps);
+        let simplified = simplify(&positions, &indices, 60);
+        for (i, position) in positions.iter().enumerate() {
+            let on_border = position[0] == 0.0 || position[0] == 1.0 || position[1] == 0.0 || position[1] == 1.0;
+            if on_border {
+                assert!(simplified.contains(&(i as u32)), "边界顶点{}被折叠", i);
+            }
+        }
+        for triangle in simplified.chunks_exact(3) {
+            assert!(normal(&positions, triangle).z > 0.0, "{:?} 翻转或退化", triangle);
+        }
+    }
+
+    #[test]
+    fn keeps_seam_vertices() {
+        // 第4列的顶点复制一份供右半边使用，模拟纹理接缝
+        let n = 8;
+        let (mut positions, mut indices) = grid(n, |_, _| 0.0);
+        let seam_x = 4;
+        let mut duplicates = HashMap::new();
+        for y in 0..=n {
+            let original = y * (n + 1) + seam_x;
+            duplicates.insert(original, positions.len() as u32);
+            positions.push(positions[original as usize]);
+        }
+        for triangle in indices.chunks_exact_mut(3) {
+            let right = triangle.iter().any(|i| i % (n + 1) > seam_x);
+            if right {
+                for i in triangle.iter_mut() {
+                    if let Some(duplicate) = duplicates.get(i) {
+                        *i = *duplicate;
+                    }
+                }
+            }
+        }
+
+        let simplified = simplify(&positions, &indices, 24);
+        assert!(simplified.len() < indices.len());
+        for (original, duplicate) in duplicates {
+            assert!(simplified.contains(&original), "接缝顶点{}被折叠", original);
+            assert!(simplified.contains(&duplicate), "接缝顶点{}被折叠", duplicate);
+        }
+        for triangle in simplified.chunks_exact(3) {
+            assert!(normal(&positions, triangle).z > 0.0);
+        }
+    }
+
+    fn adjacency(triangles: &[[u32; 3]], vertex_count: usize) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); vertex_count];
+        for (face, triangle) in triangles.iter().enumerate() {
+            for i in triangle {
+                adjacency[*i as usize].push(face);
+            }
+        }
+        adjacency
+    }
+
+    #[test]
+    fn link_condition() {
+        // 边0-1两侧的三角形之外，0和1还通过4、5相连，折叠后0-4-5会与1-5-4重合
+        let triangles = [[0, 1, 2], [1, 0, 3], [0, 4, 5], [1, 5, 4]];
+        let alive = [true; 4];
+        assert!(!satisfies_link_condition(&triangles, &alive, &adjacency(&triangles, 6), 0, 1));
+
+        // 八面体上的任意边都满足连接条件
+        let (_, indices) = sphere(0);
+        let triangles: Vec<[u32; 3]> = indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+        let alive = vec![true; triangles.len()];
+        assert!(satisfies_link_condition(&triangles, &alive, &adjacency(&triangles, 6), 0, 2));
+    }
+
+    /// 细分八面体得到的封闭流形
+    fn sphere(subdivisions: u32) -> (Vec<[f32; 3]>, Vec<u32>) {
+        let mut positions = vec![[1.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, -1.0]];
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 2, 4], [2, 1, 4], [1, 3, 4], [3, 0, 4],
+            [2, 0, 5], [1, 2, 5], [3, 1, 5], [0, 3, 5],
+        ];
+        for _ in 0..subdivisions {
+            let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+            let mut midpoint = |a: u32, b: u32, positions: &mut Vec<[f32; 3]>| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let m = (Vector3::from(positions[a as usize]) + Vector3::from(positions[b as usize])).normalize();
+                    positions.push(m.into());
+                    positions.len() as u32 - 1
+                })
+            };
+            let mut next = Vec::with_capacity(triangles.len() * 4);
+            for [a, b, c] in triangles {
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+                next.extend([[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]);
+            }
+            triangles = next;
+        }
+        (positions, triangles.into_iter().flatten().collect())
+    }
+
+    #[test]
+    fn closed_mesh_stays_manifold() {
+        let (positions, indices) = sphere(3);
+        for target in [indices.len() / 2, indices.len() / 8, 12] {
+            let simplified = simplify(&positions, &indices, target);
+            let mut edges: HashMap<(u32, u32), u32> = HashMap::new();
+            for triangle in simplified.chunks_exact(3) {
+                assert!(triangle[0] != triangle[1] && triangle[1] != triangle[2] && triangle[2] != triangle[0]);
+                for k in 0..3 {
+                    let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+                    *edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+                }
+            }
+            // 封闭流形上每条边恰好被两个三角形共用
+            assert!(edges.values().all(|count| *count == 2), "目标{}产生了非流形边", target);
+        }
+    }
+}
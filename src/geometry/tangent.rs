@@ -0,0 +1,145 @@
+use cgmath::{Vector3, Vector2, InnerSpace, Zero};
+
+use crate::{Vertex, objectsv2::RawVertexPNTTB};
+
+use super::safe_normalize;
+
+/**
+ * 为三角形列表计算逐顶点的切线与副切线，生成可用于法线贴图的顶点
+ * 约定与MikkTSpace一致：
+ * 1. 按三角形在该顶点处的夹角加权累加各面的切线与副切线
+ * 2. 切线对法向量做Gram-Schmidt正交化
+ * 3. 副切线 = sign * cross(normal, tangent)，sign为贴图坐标系的手性
+ *
+ * 共享位置但贴图坐标不同的顶点（UV接缝）在去重时已被拆开，因此可以直接按索引累加
+ */
+pub fn generate_tangents(vertices: &[Vertex], indices: &[u32]) -> Vec<RawVertexPNTTB> {
+    let mut tangents = vec![Vector3::<f32>::zero(); vertices.len()];
+    let mut bitangents = vec![Vector3::<f32>::zero(); vertices.len()];
+
+    for triangle in indices.chunks_exact(3) {
+        let corners = [triangle[0] as usize, triangle[1] as usize, triangle[2] as usize];
+        let p = corners.map(|i| Vector3::from(vertices[i].position));
+        let uv = corners.map(|i| Vector2::from(vertices[i].texture));
+
+        let edge1 = p[1] - p[0];
+        let edge2 = p[2] - p[0];
+        let delta_uv1 = uv[1] - uv[0];
+        let delta_uv2 = uv[2] - uv[0];
+
+        let det = delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y;
+        if det.abs() < f32::EPSILON {
+            // 贴图坐标退化，该面不贡献切线
+            continue;
+        }
+        let f = 1.0 / det;
+        let tangent = (edge1 * delta_uv2.y - edge2 * delta_uv1.y) * f;
+        let bitangent = (edge2 * delta_uv1.x - edge1 * delta_uv2.x) * f;
+
+        for corner in 0..3 {
+            let weight = corner_angle(p[corner], p[(corner + 1) % 3], p[(corner + 2) % 3]);
+            tangents[corners[corner]] += tangent * weight;
+            bitangents[corners[corner]] += bitangent * weight;
+        }
+    }
+
+    vertices.iter().enumerate().map(|(i, vertex)| {
+        let (tangent, bitangent) = orthogonalize(Vector3::from(vertex.normal), tangents[i], bitangents[i]);
+        RawVertexPNTTB {
+            position: vertex.position,
+            normal: vertex.normal,
+            tex_coords: vertex.texture,
+            tangent: tangent.into(),
+            bitangent: bitangent.into(),
+        }
+    }).collect()
+}
+
+/// 顶点处两条边的夹角
+fn corner_angle(corner: Vector3<f32>, a: Vector3<f32>, b: Vector3<f32>) -> f32 {
+    let e1 = a - corner;
+    let e2 = b - corner;
+    if e1.magnitude2() == 0.0 || e2.magnitude2() == 0.0 {
+        return 0.0;
+    }
+    e1.normalize().dot(e2.normalize()).clamp(-1.0, 1.0).acos()
+}
+
+/// 切线对法向量正交化，并按手性重建副切线
+fn orthogonalize(normal: Vector3<f32>, tangent: Vector3<f32>, bitangent: Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+    if normal.magnitude2() == 0.0 {
+        // 没有法向量时无法正交化，仅做归一化
+        return (safe_normalize(tangent, Vector3::unit_x()), safe_normalize(bitangent, Vector3::unit_y()));
+    }
+    let normal = normal.normalize();
+    let mut t = tangent - normal * normal.dot(tangent);
+    if t.magnitude2() < f32::EPSILON {
+        // 没有有效切线（贴图坐标缺失或退化），任选一个与法向量垂直的方向
+        t = any_perpendicular(normal);
+    }
+    let t = t.normalize();
+    let sign = if normal.cross(t).dot(bitangent) < 0.0 { -1.0 } else { 1.0 };
+    (t, normal.cross(t) * sign)
+}
+
+fn any_perpendicular(normal: Vector3<f32>) -> Vector3<f32> {
+    let axis = if normal.x.abs() < 0.9 { Vector3::unit_x() } else { Vector3::unit_y() };
+    normal.cross(axis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// z=0平面上的单位四边形，法向量+z，uv由位置计算
+    fn quad(uv: impl Fn(f32, f32) -> [f32; 2]) -> Vec<Vertex> {
+        [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]].iter()
+            .map(|[x, y]| Vertex { position: [*x, *y, 0.0], normal: [0.0, 0.0, 1.0], texture: uv(*x, *y) })
+            .collect()
+    }
+
+    const INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        assert!((Vector3::from(a) - Vector3::from(b)).magnitude() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn tangents_are_orthonormal_to_normals() {
+        let vertices = generate_tangents(&quad(|x, y| [x, y]), &INDICES);
+        for vertex in vertices.iter() {
+            let normal = Vector3::from(vertex.normal);
+            let tangent = Vector3::from(vertex.tangent);
+            let bitangent = Vector3::from(vertex.bitangent);
+            assert!(normal.dot(tangent).abs() < 1e-5);
+            assert!(normal.dot(bitangent).abs() < 1e-5);
+            assert!((tangent.magnitude() - 1.0).abs() < 1e-5);
+            // 右手系：T×B与N同向
+            assert!(tangent.cross(bitangent).dot(normal) > 0.99);
+            assert_close(vertex.tangent, [1.0, 0.0, 0.0]);
+            assert_close(vertex.bitangent, [0.0, 1.0, 0.0]);
+        }
+    }
+
+    #[test]
+    fn mirrored_uv_flips_handedness() {
+        // u方向镜像后切线反向，副切线仍沿+v方向，T×B与N反向
+        let vertices = generate_tangents(&quad(|x, y| [1.0 - x, y]), &INDICES);
+        for vertex in vertices.iter() {
+            assert_close(vertex.tangent, [-1.0, 0.0, 0.0]);
+            assert_close(vertex.bitangent, [0.0, 1.0, 0.0]);
+            let tangent = Vector3::from(vertex.tangent);
+            assert!(tangent.cross(Vector3::from(vertex.bitangent)).dot(Vector3::from(vertex.normal)) < -0.99);
+        }
+    }
+
+    #[test]
+    fn degenerate_uv_still_gives_perpendicular_tangent() {
+        let vertices = generate_tangents(&quad(|_, _| [0.5, 0.5]), &INDICES);
+        for vertex in vertices.iter() {
+            let tangent = Vector3::from(vertex.tangent);
+            assert!((tangent.magnitude() - 1.0).abs() < 1e-5);
+            assert!(tangent.dot(Vector3::from(vertex.normal)).abs() < 1e-5);
+        }
+    }
+}
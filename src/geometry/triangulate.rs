@@ -0,0 +1,208 @@
+use cgmath::{Vector3, Vector2, InnerSpace};
+
+/**
+ * 将一个多边形三角化
+ * 输入为多边形顶点位置（按绕序排列），返回多边形内的局部索引三元组，三角形绕序与原多边形一致
+ * 凸多边形使用扇形三角化，凹多边形使用耳切法
+ */
+pub fn triangulate(positions: &[[f32; 3]]) -> Vec<[usize; 3]> {
+    let count = positions.len();
+    if count < 3 {
+        return Vec::new();
+    }
+    if count == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    let normal = polygon_normal(positions);
+    if is_convex(positions, normal) {
+        triangulate_fan(count)
+    } else {
+        triangulate_ear_clipping(positions, normal)
+    }
+}
+
+/// 扇形三角化，以第一个顶点为中心
+pub fn triangulate_fan(count: usize) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::with_capacity(count.saturating_sub(2));
+    for i in 1..count.saturating_sub(1) {
+        triangles.push([0, i, i + 1]);
+    }
+    triangles
+}
+
+/// 使用Newell方法计算多边形法向量（未归一化），对非平面多边形也较稳定
+pub fn polygon_normal(positions: &[[f32; 3]]) -> Vector3<f32> {
+    let mut normal = Vector3::new(0.0, 0.0, 0.0);
+    for (i, current) in positions.iter().enumerate() {
+        let next = positions[(i + 1) % positions.len()];
+        normal.x += (current[1] - next[1]) * (current[2] + next[2]);
+        normal.y += (current[2] - next[2]) * (current[0] + next[0]);
+        normal.z += (current[0] - next[0]) * (current[1] + next[1]);
+    }
+    normal
+}
+
+/// 判断多边形相对于给定法向量是否为凸多边形
+fn is_convex(positions: &[[f32; 3]], normal: Vector3<f32>) -> bool {
+    let count = positions.len();
+    for i in 0..count {
+        let prev = Vector3::from(positions[(i + count - 1) % count]);
+        let current = Vector3::from(positions[i]);
+        let next = Vector3::from(positions[(i + 1) % count]);
+        if (current - prev).cross(next - current).dot(normal) < 0.0 {
+            return false;
+        }
+    }
+    true
+}
+
+/// 耳切法三角化，先将多边形投影到法向量主轴对应的平面上
+fn triangulate_ear_clipping(positions: &[[f32; 3]], normal: Vector3<f32>) -> Vec<[usize; 3]> {
+    let points = project_to_plane(positions, normal);
+
+    let mut remaining: Vec<usize> = (0..positions.len()).collect();
+    let mut triangles = Vec::with_capacity(positions.len() - 2);
+
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let mut ear = None;
+        for i in 0..count {
+            let prev = remaining[(i + count - 1) % count];
+            let current = remaining[i];
+            let next = remaining[(i + 1) % count];
+            if is_ear(&points, &remaining, prev, current, next) {
+                ear = Some(i);
+                break;
+            }
+        }
+
+        // 找不到耳朵说明多边形退化（自相交或共线），直接切掉第一个顶点保证能结束
+        let i = ear.unwrap_or(0);
+        let prev = remaining[(i + count - 1) % count];
+        let next = remaining[(i + 1) % count];
+        triangles.push([prev, remaining[i], next]);
+        remaining.remove(i);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+
+    triangles
+}
+
+/// 投影到二维平面，并保证投影后的多边形为逆时针方向
+fn project_to_plane(positions: &[[f32; 3]], normal: Vector3<f32>) -> Vec<Vector2<f32>> {
+    let (abs_x, abs_y, abs_z) = (normal.x.abs(), normal.y.abs(), normal.z.abs());
+    // 丢弃法向量分量最大的坐标轴，按右手系顺序取剩余两轴
+    let (u, v, sign) = if abs_x >= abs_y && abs_x >= abs_z {
+        (1, 2, normal.x)
+    } else if abs_y >= abs_z {
+        (2, 0, normal.y)
+    } else {
+        (0, 1, normal.z)
+    };
+    positions.iter().map(|p| {
+        if sign >= 0.0 {
+            Vector2::new(p[u], p[v])
+        } else {
+            Vector2::new(p[v], p[u])
+        }
+    }).collect()
+}
+
+fn cross_2d(o: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+fn is_ear(points: &[Vector2<f32>], remaining: &[usize], prev: usize, current: usize, next: usize) -> bool {
+    let (a, b, c) = (points[prev], points[current], points[next]);
+    // 凹顶点（或共线）不能作为耳朵
+    if cross_2d(a, b, c) <= 0.0 {
+        return false;
+    }
+    // 三角形内不能包含其他剩余顶点
+    for &other in remaining.iter() {
+        if other == prev || other == current || other == next {
+            continue;
+        }
+        let p = points[other];
+        if cross_2d(a, b, p) >= 0.0 && cross_2d(b, c, p) >= 0.0 && cross_2d(c, a, p) >= 0.0 {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xy(points: &[[f32; 2]]) -> Vec<[f32; 3]> {
+        points.iter().map(|p| [p[0], p[1], 0.0]).collect()
+    }
+
+    /// XY平面上三角形的有向面积，逆时针为正
+    fn signed_area(positions: &[[f32; 3]], triangle: &[usize; 3]) -> f32 {
+        let [a, b, c] = triangle.map(|i| Vector2::new(positions[i][0], positions[i][1]));
+        cross_2d(a, b, c) * 0.5
+    }
+
+    #[test]
+    fn quad_gives_two_triangles_covering_its_area() {
+        let positions = xy(&[[0.0, 0.0], [2.0, 0.0], [2.0, 1.0], [0.0, 1.0]]);
+        let triangles = triangulate(&positions);
+
+        assert_eq!(triangles.len(), 2);
+        for triangle in triangles.iter() {
+            assert!(signed_area(&positions, triangle) > 0.0);
+        }
+        let area: f32 = triangles.iter().map(|t| signed_area(&positions, t)).sum();
+        assert!((area - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn concave_pentagon_does_not_span_reflex_vertex() {
+        // 上边向内凹的五边形，顶点3为凹顶点，面积为 4 - 1 = 3
+        let positions = xy(&[[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [1.0, 1.0], [0.0, 2.0]]);
+        let triangles = triangulate(&positions);
+
+        assert_eq!(triangles.len(), 3);
+        let reflex = Vector2::new(1.0, 1.0);
+        for triangle in triangles.iter() {
+            assert!(signed_area(&positions, triangle) > 0.0, "{:?} 绕序翻转", triangle);
+            // 跨过凹口的对角线2-4在多边形外
+            assert!(!(triangle.contains(&2) && triangle.contains(&4) && !triangle.contains(&3)), "{:?} 跨过了凹顶点", triangle);
+            if !triangle.contains(&3) {
+                let [a, b, c] = triangle.map(|i| Vector2::new(positions[i][0], positions[i][1]));
+                let inside = cross_2d(a, b, reflex) > 0.0 && cross_2d(b, c, reflex) > 0.0 && cross_2d(c, a, reflex) > 0.0;
+                assert!(!inside, "{:?} 包含凹顶点", triangle);
+            }
+        }
+        let area: f32 = triangles.iter().map(|t| signed_area(&positions, t)).sum();
+        assert!((area - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn collinear_polygon_terminates() {
+        // 所有顶点共线时找不到耳朵，每轮都走兜底分支
+        let positions = xy(&[[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [3.0, 0.0], [4.0, 0.0]]);
+        let triangles = triangulate_ear_clipping(&positions, Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(triangles.len(), 3);
+
+        assert_eq!(triangulate(&positions).len(), 3);
+    }
+
+    #[test]
+    fn degenerate_concave_polygon_terminates() {
+        // 凹顶点重复、含共线边的多边形
+        let positions = xy(&[[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [2.0, 2.0], [1.0, 1.0], [1.0, 1.0], [0.0, 2.0]]);
+        let triangles = triangulate(&positions);
+        assert_eq!(triangles.len(), positions.len() - 2);
+        for triangle in triangles.iter() {
+            assert!(triangle.iter().all(|&i| i < positions.len()));
+        }
+
+        // 所有顶点重合
+        let positions = vec![[1.0, 1.0, 1.0]; 6];
+        assert_eq!(triangulate(&positions).len(), 4);
+    }
+}
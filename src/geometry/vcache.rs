@@ -0,0 +1,232 @@
+use std::collections::VecDeque;
+
+/// 优化与统计时假设的顶点后变换缓存大小
+pub const DEFAULT_CACHE_SIZE: usize = 16;
+
+/**
+ * 使用Tipsify算法（Sander等，2007）重排三角形顺序，提高顶点后变换缓存命中率
+ * indices为三角形列表索引，cache_size为目标缓存大小，返回重排后的索引
+ */
+pub fn optimize_vertex_cache(indices: &[u32], vertex_count: usize, cache_size: usize) -> Vec<u32> {
+    let triangle_count = indices.len() / 3;
+    if triangle_count == 0 {
+        return indices.to_vec();
+    }
+
+    // 顶点 -> 相邻三角形，使用偏移数组紧凑存储
+    let mut live = vec![0u32; vertex_count];
+    for index in indices[..triangle_count * 3].iter() {
+        live[*index as usize] += 1;
+    }
+    let mut offsets = vec![0usize; vertex_count + 1];
+    for v in 0..vertex_count {
+        offsets[v + 1] = offsets[v] + live[v] as usize;
+    }
+    let mut adjacency = vec![0usize; offsets[vertex_count]];
+    let mut fill = offsets.clone();
+    for (triangle, corners) in indices.chunks_exact(3).enumerate() {
+        for v in corners.iter() {
+            adjacency[fill[*v as usize]] = triangle;
+            fill[*v as usize] += 1;
+        }
+    }
+
+    let mut cache_time = vec![0usize; vertex_count];
+    let mut emitted = vec![false; triangle_count];
+    let mut dead_end: Vec<u32> = Vec::new();
+    let mut output = Vec::with_capacity(triangle_count * 3);
+    let mut time = cache_size + 1;
+    let mut cursor = 0;
+
+    let mut fanning = skip_dead_end(&live, &mut dead_end, &mut cursor);
+    while let Some(f) = fanning {
+        let mut candidates: Vec<u32> = Vec::new();
+        for triangle in adjacency[offsets[f]..offsets[f + 1]].iter() {
+            if emitted[*triangle] {
+                continue;
+            }
+            for v in indices[triangle * 3..triangle * 3 + 3].iter() {
+                output.push(*v);
+                dead_end.push(*v);
+                candidates.push(*v);
+                let v = *v as usize;
+                live[v] -= 1;
+                if time - cache_time[v] > cache_size {
+                    cache_time[v] = time;
+                    time += 1;
+                }
+            }
+            emitted[*triangle] = true;
+        }
+
+        // 优先选择仍在缓存中且剩余三角形能在被挤出前处理完的顶点
+        let mut best = None;
+        let mut best_priority = -1i64;
+        for v in candidates {
+            let v = v as usize;
+            if live[v] == 0 {
+                continue;
+            }
+            let mut priority = 0i64;
+            if time - cache_time[v] + 2 * live[v] as usize <= cache_size {
+                priority = (time - cache_time[v]) as i64;
+            }
+            if priority > best_priority {
+                best_priority = priority;
+                best = Some(v);
+            }
+        }
+        fanning = match best {
+            Some(v) => Some(v),
+            None => skip_dead_end(&live, &mut dead_end, &mut cursor),
+        };
+    }
+    output
+}
+
+/// 从最近输出的顶点中找仍有剩余三角形的顶点，否则按输入顺序查找
+fn skip_dead_end(live: &[u32], dead_end: &mut Vec<u32>, cursor: &mut usize) -> Option<usize> {
+    while let Some(v) = dead_end.pop() {
+        if live[v as usize] > 0 {
+            return Some(v as usize);
+        }
+    }
+    while *cursor < live.len() {
+        if live[*cursor] > 0 {
+            return Some(*cursor);
+        }
+        *cursor += 1;
+    }
+    None
+}
+
+/**
+ * 按索引中首次出现的顺序重排顶点，提高顶点读取的内存局部性
+ * 返回旧顶点索引 -> 新顶点索引的映射，未被引用的顶点排在最后
+ */
+pub fn vertex_fetch_remap(indices: &[u32], vertex_count: usize) -> Vec<u32> {
+    let mut remap = vec![u32::MAX; vertex_count];
+    let mut next = 0u32;
+    for index in indices.iter() {
+        let slot = &mut remap[*index as usize];
+        if *slot == u32::MAX {
+            *slot = next;
+            next += 1;
+        }
+    }
+    for slot in remap.iter_mut() {
+        if *slot == u32::MAX {
+            *slot = next;
+            next += 1;
+        }
+    }
+    remap
+}
+
+/**
+ * 平均缓存未命中率（ACMR）：模拟FIFO顶点缓存，每个三角形平均需要变换的顶点数
+ * 取值在0.5（理想）到3.0（完全无复用）之间
+ */
+pub fn acmr(indices: &[u32], cache_size: usize) -> f32 {
+    let triangle_count = indices.len() / 3;
+    if triangle_count == 0 {
+        return 0.0;
+    }
+    let mut cache: VecDeque<u32> = VecDeque::with_capacity(cache_size + 1);
+    let mut misses = 0;
+    for index in indices[..triangle_count * 3].iter() {
+        if !cache.contains(index) {
+            misses += 1;
+            cache.push_back(*index);
+            if cache.len() > cache_size {
+                cache.pop_front();
+            }
+        }
+    }
+    misses as f32 / triangle_count as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// n×n个格子的网格，按行输出三角形
+    fn grid(n: u32) -> Vec<u32> {
+        let index = |x: u32, y: u32| y * (n + 1) + x;
+        let mut indices = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                indices.extend([index(x, y), index(x + 1, y), index(x + 1, y + 1)]);
+                indices.extend([index(x, y), index(x + 1, y + 1), index(x, y + 1)]);
+            }
+        }
+        indices
+    }
+
+    /// 以最小索引开头旋转三角形（保持绕序），排序后便于比较
+    fn canonical(indices: &[u32]) -> Vec<[u32; 3]> {
+        let mut triangles: Vec<[u32; 3]> = indices.chunks_exact(3)
+            .map(|t| {
+                let first = (0..3).min_by_key(|k| t[*k]).unwrap();
+                [t[first], t[(first + 1) % 3], t[(first + 2) % 3]]
+            })
+            .collect();
+        triangles.sort_unstable();
+        triangles
+    }
+
+    /// 固定种子的洗牌，打乱三角形顺序
+    fn shuffled(indices: &[u32]) -> Vec<u32> {
+        let mut triangles: Vec<&[u32]> = indices.chunks_exact(3).collect();
+        let mut seed = 0x2545_f491u32;
+        for i in (1..triangles.len()).rev() {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            triangles.swap(i, seed as usize % (i + 1));
+        }
+        triangles.concat()
+    }
+
+    #[test]
+    fn output_is_permutation_of_triangles() {
+        let indices = shuffled(&grid(12));
+        let optimized = optimize_vertex_cache(&indices, 13 * 13, DEFAULT_CACHE_SIZE);
+        assert_eq!(optimized.len(), indices.len());
+        assert_eq!(canonical(&optimized), canonical(&indices));
+    }
+
+    #[test]
+    fn acmr_does_not_get_worse() {
+        let ordered = grid(20);
+        let optimized = optimize_vertex_cache(&ordered, 21 * 21, DEFAULT_CACHE_SIZE);
+        assert!(acmr(&optimized, DEFAULT_CACHE_SIZE) <= acmr(&ordered, DEFAULT_CACHE_SIZE));
+
+        let random = shuffled(&ordered);
+        let optimized = optimize_vertex_cache(&random, 21 * 21, DEFAULT_CACHE_SIZE);
+        assert!(acmr(&optimized, DEFAULT_CACHE_SIZE) < acmr(&random, DEFAULT_CACHE_SIZE));
+        assert!(acmr(&optimized, DEFAULT_CACHE_SIZE) < 1.0);
+    }
+
+    #[test]
+    fn acmr_bounds() {
+        // 互不共享顶点的三角形每个需要变换3个顶点
+        assert_eq!(acmr(&[0, 1, 2, 3, 4, 5], DEFAULT_CACHE_SIZE), 3.0);
+        assert_eq!(acmr(&[0, 1, 2, 0, 2, 1], DEFAULT_CACHE_SIZE), 1.5);
+        assert_eq!(acmr(&[], DEFAULT_CACHE_SIZE), 0.0);
+    }
+
+    #[test]
+    fn remap_is_bijection() {
+        // 顶点1、4、6未被引用
+        let indices = [5, 2, 0, 0, 2, 3];
+        let remap = vertex_fetch_remap(&indices, 7);
+        let mut sorted = remap.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..7).collect::<Vec<u32>>());
+
+        // 被引用的顶点按首次出现顺序排在前面
+        assert_eq!(&[remap[5], remap[2], remap[0], remap[3]], &[0, 1, 2, 3]);
+        assert!([1, 4, 6].iter().all(|v| remap[*v] >= 4));
+    }
+}
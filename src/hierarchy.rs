@@ -0,0 +1,109 @@
+//! 保留OBJ对象与组名称的模型层级
+
+use cgmath::{Matrix4, SquareMatrix};
+use glium::Display;
+
+use crate::{Model, ObjLoadOptions, load_wavefront_obj_as_meshes, upload_obj_meshes, error::LoadError};
+
+/**
+ * 模型层级中的一个节点
+ * OBJ加载结果为三层：根节点（文件名）-> 对象（`o`）-> 组（`g`），绘制单位挂在组节点上
+ */
+pub struct ModelNode {
+    pub name: String,
+    /// 相对父节点的变换
+    pub transform: Matrix4<f32>,
+    /// 隐藏后该节点及其子节点都不绘制
+    pub visible: bool,
+    pub models: Vec<Model>,
+    pub children: Vec<ModelNode>,
+}
+
+impl ModelNode {
+
+    pub fn new(name: &str) -> Self {
+        ModelNode { name: name.to_string(), transform: Matrix4::identity(), visible: true, models: Vec::new(), children: Vec::new() }
+    }
+
+    pub fn child(&self, name: &str) -> Option<&ModelNode> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut ModelNode> {
+        self.children.iter_mut().find(|child| child.name == name)
+    }
+
+    /// 深度优先查找第一个指定名称的节点（包括自身）
+    pub fn find(&self, name: &str) -> Option<&ModelNode> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(name))
+    }
+
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut ModelNode> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|child| child.find_mut(name))
+    }
+
+    /// 按`/`分隔的路径查找子孙节点，路径相对于当前节点，例如`"nanosuit/helmet"`
+    pub fn get(&self, path: &str) -> Option<&ModelNode> {
+        path.split('/').filter(|name| !name.is_empty()).try_fold(self, |node, name| node.child(name))
+    }
+
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut ModelNode> {
+        path.split('/').filter(|name| !name.is_empty()).try_fold(self, |node, name| node.child_mut(name))
+    }
+
+    /**
+     * 遍历所有可见的绘制单位，parent为当前节点父级的世界变换
+     * 回调参数为绘制单位及其世界变换
+     */
+    pub fn for_each_visible<F: FnMut(&Model, Matrix4<f32>)>(&self, parent: Matrix4<f32>, f: &mut F) {
+        if !self.visible {
+            return;
+        }
+        let transform = parent * self.transform;
+        for model in self.models.iter() {
+            f(model, transform);
+        }
+        for child in self.children.iter() {
+            child.for_each_visible(transform, f);
+        }
+    }
+}
+
+/**
+ * 将OBJ模型加载为保留对象与组名称的层级结构
+ * 加载失败时直接panic，需要处理错误时使用`try_load_wavefront_obj_as_hierarchy`
+ */
+pub fn load_wavefront_obj_as_hierarchy(display: &Display, basepath: &str, obj_file: &str) -> ModelNode {
+    try_load_wavefront_obj_as_hierarchy(display, basepath, obj_file, &ObjLoadOptions::default()).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/**
+ * 按指定选项加载OBJ层级，按材质合并会丢失对象与组的划分，因此该选项在这里被忽略
+ */
+pub fn try_load_wavefront_obj_as_hierarchy(display: &Display, basepath: &str, obj_file: &str, options: &ObjLoadOptions) -> Result<ModelNode, LoadError> {
+    let options = ObjLoadOptions { merge_by_material: false, ..options.clone() };
+    let obj_meshes = load_wavefront_obj_as_meshes(basepath, obj_file, &options)?;
+    let models = upload_obj_meshes(display, basepath, &obj_meshes, &options)?;
+
+    let mut root = ModelNode::new(obj_file);
+    for (mesh, model) in obj_meshes.meshes.iter().zip(models) {
+        let object_name = mesh.name.as_deref().unwrap_or_default();
+        let group_name = mesh.group.as_deref().unwrap_or_default();
+        // 同名对象或组（例如同一组内切换材质）合并到同一个节点
+        if root.child(object_name).is_none() {
+            root.children.push(ModelNode::new(object_name));
+        }
+        let object = root.child_mut(object_name).unwrap();
+        if object.child(group_name).is_none() {
+            object.children.push(ModelNode::new(group_name));
+        }
+        object.child_mut(group_name).unwrap().models.push(model);
+    }
+    Ok(root)
+}
@@ -0,0 +1,14 @@
+#version 330 core
+in vec3 position;
+
+out vec3 LocalPos;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+// 从立方体中心渲染立方体贴图的一个面，局部坐标即采样方向
+void main()
+{
+    LocalPos = position;
+    gl_Position = projection * view * vec4(position, 1.0);
+}
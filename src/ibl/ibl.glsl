@@ -0,0 +1,34 @@
+// 基于图像的环境光照，与`rust_opengl_learn::ibl::Ibl::add_to_uniforms`绑定的uniform对应
+// 不依赖包含它的着色器中的其他函数，可以直接替换原来的常量环境光
+
+struct Ibl {
+    samplerCube irradiance_map;
+    samplerCube prefilter_map;
+    sampler2D brdf_lut;
+    float max_reflection_lod;
+};
+uniform Ibl ibl;
+
+vec3 fresnelSchlickRoughness(float cosTheta, vec3 F0, float roughness)
+{
+    return F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+// 环境光的漫反射与镜面反射部分，N、V为单位向量
+vec3 AmbientIBL(vec3 N, vec3 V, vec3 albedo, float metallic, float roughness, float ao)
+{
+    vec3 F0 = mix(vec3(0.04), albedo, metallic);
+    float NdotV = max(dot(N, V), 0.0);
+    vec3 F = fresnelSchlickRoughness(NdotV, F0, roughness);
+
+    vec3 kS = F;
+    vec3 kD = (1.0 - kS) * (1.0 - metallic);
+    vec3 diffuse = texture(ibl.irradiance_map, N).rgb * albedo;
+
+    vec3 R = reflect(-V, N);
+    vec3 prefilteredColor = textureLod(ibl.prefilter_map, R, roughness * ibl.max_reflection_lod).rgb;
+    vec2 brdf = texture(ibl.brdf_lut, vec2(NdotV, roughness)).rg;
+    vec3 specular = prefilteredColor * (F * brdf.x + brdf.y);
+
+    return (kD * diffuse + specular) * ao;
+}
@@ -0,0 +1,38 @@
+#version 330 core
+out vec4 FragColor;
+in vec3 LocalPos;
+
+uniform samplerCube environmentMap;
+
+const float PI = 3.14159265359;
+
+// 对法线方向所在半球做余弦加权卷积，得到漫反射辐照度
+void main()
+{
+    vec3 N = normalize(LocalPos);
+
+    vec3 irradiance = vec3(0.0);
+
+    // 以法线为z轴的切线空间
+    vec3 up    = vec3(0.0, 1.0, 0.0);
+    vec3 right = normalize(cross(up, N));
+    up         = normalize(cross(N, right));
+
+    float sampleDelta = 0.025;
+    float nrSamples = 0.0;
+    for(float phi = 0.0; phi < 2.0 * PI; phi += sampleDelta)
+    {
+        for(float theta = 0.0; theta < 0.5 * PI; theta += sampleDelta)
+        {
+            // 球坐标 -> 切线空间 -> 世界空间
+            vec3 tangentSample = vec3(sin(theta) * cos(phi),  sin(theta) * sin(phi), cos(theta));
+            vec3 sampleVec = tangentSample.x * right + tangentSample.y * up + tangentSample.z * N;
+
+            irradiance += texture(environmentMap, sampleVec).rgb * cos(theta) * sin(theta);
+            nrSamples++;
+        }
+    }
+    irradiance = PI * irradiance * (1.0 / float(nrSamples));
+
+    FragColor = vec4(irradiance, 1.0);
+}
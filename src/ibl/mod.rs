@@ -0,0 +1,226 @@
+use std::time::Instant;
+
+use cgmath::{Deg, Point3};
+use glium::{Display, Program, Surface, Texture2d, framebuffer::SimpleFrameBuffer, texture::{Cubemap, MipmapsOption, UncompressedFloatFormat}, uniforms::{AsUniformValue, SamplerWrapFunction, UniformValue}};
+
+use crate::{try_create_program, error::LoadError, objects::{Cube, Plane}, material::{self, TextureRole, cube_face_views}, uniforms::{DynamicUniforms, TextureSampler, add_value_to_uniforms, with_sampler}};
+
+/// 从立方体中心渲染立方体贴图各个面的顶点着色器
+pub const CUBEMAP_VERTEX_SHADER_PATH: &str = "src/ibl/cubemap.vert";
+const EQUIRECT_TO_CUBEMAP_SHADER_PATH: &str = "src/ibl/equirect_to_cubemap.frag";
+const IRRADIANCE_SHADER_PATH: &str = "src/ibl/irradiance.frag";
+const PREFILTER_SHADER_PATH: &str = "src/ibl/prefilter.frag";
+const BRDF_VERTEX_SHADER_PATH: &str = "src/ibl/brdf.vert";
+const BRDF_FRAGMENT_SHADER_PATH: &str = "src/ibl/brdf.frag";
+
+/// 预计算结果的纹理格式，环境光是HDR数据，需要浮点格式
+const CUBEMAP_FORMAT: UncompressedFloatFormat = UncompressedFloatFormat::F16F16F16F16;
+
+/**
+ * IBL预计算参数
+ */
+#[derive(Clone, Debug)]
+pub struct IblOptions {
+    /// 环境立方体贴图的边长
+    pub environment_size: u32,
+    /// 漫反射辐照度立方体贴图的边长，辐照度变化平缓，很小的尺寸就足够
+    pub irradiance_size: u32,
+    /// 镜面反射预滤波立方体贴图第0级的边长
+    pub prefilter_size: u32,
+    /// 预滤波的mip层级数，粗糙度0-1均匀分布在各层级上
+    pub prefilter_levels: u32,
+    /// BRDF查找表的边长
+    pub brdf_lut_size: u32,
+    /// 预滤波与BRDF积分的重要性采样数
+    pub sample_count: u32,
+}
+
+impl Default for IblOptions {
+    fn default() -> Self {
+        IblOptions {
+            environment_size: 512,
+            irradiance_size: 32,
+            prefilter_size: 128,
+            prefilter_levels: 5,
+            brdf_lut_size: 512,
+            sample_count: 1024,
+        }
+    }
+}
+
+/**
+ * 基于图像的光照（IBL）预计算结果
+ * 对应`src/ibl/ibl.glsl`中的`Ibl`结构体，PBR着色器包含该文件后用`AmbientIBL`代替常量环境光
+ */
+pub struct Ibl {
+    /// 环境立方体贴图（带mipmap），可以直接作为天空盒渲染
+    pub environment: Cubemap,
+    /// 漫反射辐照度立方体贴图
+    pub irradiance: Cubemap,
+    /// 按粗糙度预滤波的镜面反射立方体贴图，第i级对应粗糙度 i / (层级数 - 1)
+    pub prefilter: Cubemap,
+    /// 分割求和近似的BRDF查找表，x为NdotV，y为粗糙度，RG为F0的缩放与偏移
+    pub brdf_lut: Texture2d,
+}
+
+impl Ibl {
+
+    /**
+     * 由环境立方体贴图计算辐照度、预滤波贴图与BRDF查找表
+     * 环境贴图的mipmap在这里生成，预滤波时按采样密度读取较低分辨率的层级以减少噪点
+     */
+    pub fn from_environment(display: &Display, environment: Cubemap, options: &IblOptions) -> Result<Ibl, LoadError> {
+        // 没有分配mip存储的纹理不会生成额外层级，只影响采样时是否使用mipmap
+        unsafe { environment.generate_mipmaps(); }
+        let cube = Cube::new_skybox("ibl_cube", 2.0, display);
+        let irradiance = compute_irradiance(display, &cube, &environment, options.irradiance_size)?;
+        let prefilter = prefilter_specular(display, &cube, &environment, options.prefilter_size, options.prefilter_levels, options.sample_count)?;
+        let brdf_lut = compute_brdf_lut(display, options.brdf_lut_size, options.sample_count)?;
+        Ok(Ibl { environment, irradiance, prefilter, brdf_lut })
+    }
+
+    /**
+     * 各方向亮度相同的环境光，没有环境贴图时使用
+     * color为vec3(0.03)时与原来`ambient = vec3(0.03) * albedo * ao`的效果相当，但同时有菲涅尔镜面反射
+     */
+    pub fn from_color(display: &Display, color: [f32; 3], options: &IblOptions) -> Result<Ibl, LoadError> {
+        let environment = Cubemap::empty_with_format(display, CUBEMAP_FORMAT, MipmapsOption::NoMipmap, 1)?;
+        for (layer, _) in cube_face_views(Point3::new(0.0, 0.0, 0.0)).iter() {
+            let mut framebuffer = SimpleFrameBuffer::new(display, environment.main_level().image(*layer)).map_err(|err| LoadError::gpu("帧缓冲", err))?;
+            framebuffer.clear_color(color[0], color[1], color[2], 1.0);
+        }
+        Ibl::from_environment(display, environment, options)
+    }
+
+    /// 预滤波贴图最高的mip层级，对应粗糙度1
+    pub fn max_reflection_lod(&self) -> f32 {
+        (self.prefilter.get_mipmap_levels() - 1) as f32
+    }
+
+    /// 绑定辐照度、预滤波贴图与BRDF查找表，key通常为`ibl`
+    pub fn add_to_uniforms<'a: 'b, 'b>(&'a self, key: &str, uniforms: &'b mut DynamicUniforms<'a>) {
+        let sampler = TextureSampler::default().with_wrap(SamplerWrapFunction::Clamp, SamplerWrapFunction::Clamp);
+        add_value_to_uniforms(key, ".irradiance_map", with_sampler(self.irradiance.as_uniform_value(), sampler.with_mipmaps(false).behavior()), uniforms);
+        let prefilter_sampler = sampler.with_mipmaps(self.prefilter.get_mipmap_levels() > 1);
+        add_value_to_uniforms(key, ".prefilter_map", with_sampler(self.prefilter.as_uniform_value(), prefilter_sampler.behavior()), uniforms);
+        add_value_to_uniforms(key, ".brdf_lut", with_sampler(self.brdf_lut.as_uniform_value(), sampler.with_mipmaps(false).behavior()), uniforms);
+        add_value_to_uniforms(key, ".max_reflection_lod", UniformValue::Float(self.max_reflection_lod()), uniforms);
+    }
+}
+
+/**
+ * 加载等距柱状投影（equirectangular）的环境贴图并完成IBL预计算
+ * 加载失败时直接panic，需要处理错误时使用`try_load_ibl`
+ */
+pub fn load_ibl(display: &Display, path: &str, options: &IblOptions) -> Ibl {
+    try_load_ibl(display, path, options).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/**
+ * 加载等距柱状投影的环境贴图并完成IBL预计算
+ * HDR、EXR按浮点纹理上传；普通图片按sRGB颜色上传，亮度不会超过1
+ */
+pub fn try_load_ibl(display: &Display, path: &str, options: &IblOptions) -> Result<Ibl, LoadError> {
+    let start = Instant::now();
+    let equirect = material::upload_decoded_image(display, material::decode_image(path, TextureRole::Color)?, MipmapsOption::NoMipmap)?;
+    let environment = equirect_to_cubemap(display, &equirect, options.environment_size)?;
+    let ibl = Ibl::from_environment(display, environment, options)?;
+    println!("IBL预计算完成: {}，耗时{:?}", path, start.elapsed());
+    Ok(ibl)
+}
+
+/**
+ * 将等距柱状投影的环境贴图渲染为立方体贴图，分配了完整的mip存储但没有生成mipmap
+ */
+pub fn equirect_to_cubemap(display: &Display, equirect: &dyn AsUniformValue, size: u32) -> Result<Cubemap, LoadError> {
+    let program = try_create_program(CUBEMAP_VERTEX_SHADER_PATH, EQUIRECT_TO_CUBEMAP_SHADER_PATH, display)?;
+    let environment = Cubemap::empty_with_format(display, CUBEMAP_FORMAT, MipmapsOption::EmptyMipmaps, size)?;
+    let cube = Cube::new_skybox("ibl_cube", 2.0, display);
+
+    // 水平方向首尾相接，竖直方向在两极截断
+    let sampler = TextureSampler::default().with_wrap(SamplerWrapFunction::Repeat, SamplerWrapFunction::Clamp).with_mipmaps(false);
+    let mut uniforms = DynamicUniforms::new();
+    uniforms.add_str_key_value("equirectangularMap", with_sampler(equirect.as_uniform_value(), sampler.behavior()));
+    render_cube_faces(display, &cube, &environment, 0, &program, &uniforms)?;
+    Ok(environment)
+}
+
+/**
+ * 对环境贴图做余弦加权的半球卷积，得到漫反射辐照度立方体贴图
+ */
+pub fn compute_irradiance(display: &Display, cube: &Cube, environment: &Cubemap, size: u32) -> Result<Cubemap, LoadError> {
+    let program = try_create_program(CUBEMAP_VERTEX_SHADER_PATH, IRRADIANCE_SHADER_PATH, display)?;
+    let irradiance = Cubemap::empty_with_format(display, CUBEMAP_FORMAT, MipmapsOption::NoMipmap, size)?;
+
+    let mut uniforms = DynamicUniforms::new();
+    uniforms.add_str_key_value("environmentMap", with_sampler(environment.as_uniform_value(), environment_sampler(environment).behavior()));
+    render_cube_faces(display, cube, &irradiance, 0, &program, &uniforms)?;
+    Ok(irradiance)
+}
+
+/**
+ * 按粗糙度对环境贴图做GGX重要性采样卷积，每个粗糙度渲染到预滤波贴图的一个mip层级
+ * levels超过尺寸允许的层级数时会被截断
+ */
+pub fn prefilter_specular(display: &Display, cube: &Cube, environment: &Cubemap, size: u32, levels: u32, sample_count: u32) -> Result<Cubemap, LoadError> {
+    let program = try_create_program(CUBEMAP_VERTEX_SHADER_PATH, PREFILTER_SHADER_PATH, display)?;
+    // 边长为size时最多有 floor(log2(size)) + 1 个层级
+    let levels = levels.clamp(1, 32 - size.max(1).leading_zeros());
+    let prefilter = Cubemap::empty_with_format(display, CUBEMAP_FORMAT, MipmapsOption::EmptyMipmapsMax(levels - 1), size)?;
+
+    let mut uniforms = DynamicUniforms::new();
+    uniforms.add_str_key_value("environmentMap", with_sampler(environment.as_uniform_value(), environment_sampler(environment).behavior()));
+    uniforms.add_str_key_value("resolution", UniformValue::Float(environment.get_width() as f32));
+    uniforms.add_str_key_value("sampleCount", UniformValue::UnsignedInt(sample_count));
+    for level in 0..levels {
+        let roughness = if levels > 1 { level as f32 / (levels - 1) as f32 } else { 0.0 };
+        uniforms.add_str_key_value("roughness", UniformValue::Float(roughness));
+        render_cube_faces(display, cube, &prefilter, level, &program, &uniforms)?;
+    }
+    Ok(prefilter)
+}
+
+/**
+ * 计算分割求和近似中与环境无关的BRDF积分，结果只取决于采样数，可以在多个环境间共用
+ */
+pub fn compute_brdf_lut(display: &Display, size: u32, sample_count: u32) -> Result<Texture2d, LoadError> {
+    let program = try_create_program(BRDF_VERTEX_SHADER_PATH, BRDF_FRAGMENT_SHADER_PATH, display)?;
+    let brdf_lut = Texture2d::empty_with_format(display, UncompressedFloatFormat::F16F16, MipmapsOption::NoMipmap, size, size)?;
+    // 铺满标准化设备坐标的平面
+    let quad = Plane::new_2d_plane("brdf_quad", 2.0, 2.0, display);
+
+    let mut uniforms = DynamicUniforms::new();
+    uniforms.add_str_key_value("sampleCount", UniformValue::UnsignedInt(sample_count));
+    let mut framebuffer = SimpleFrameBuffer::new(display, &brdf_lut).map_err(|err| LoadError::gpu("帧缓冲", err))?;
+    framebuffer.clear_color(0.0, 0.0, 0.0, 1.0);
+    framebuffer.draw(&quad.vertex_buffer, &quad.index_buffer, &program, &uniforms, &Default::default())
+        .map_err(|err| LoadError::gpu("BRDF查找表", err))?;
+    Ok(brdf_lut)
+}
+
+/// 采样环境贴图时在边缘截断，有mipmap时使用三线性过滤
+fn environment_sampler(environment: &Cubemap) -> TextureSampler {
+    TextureSampler::default()
+        .with_wrap(SamplerWrapFunction::Clamp, SamplerWrapFunction::Clamp)
+        .with_mipmaps(environment.get_mipmap_levels() > 1)
+}
+
+/**
+ * 从立方体中心依次渲染目标立方体贴图指定mip层级的六个面
+ * uniforms中不需要包含`projection`与`view`
+ */
+fn render_cube_faces(display: &Display, cube: &Cube, target: &Cubemap, level: u32, program: &Program, uniforms: &DynamicUniforms) -> Result<(), LoadError> {
+    let mipmap = target.mipmap(level).ok_or_else(|| LoadError::gpu("立方体贴图", format!("不存在第{}级mipmap", level)))?;
+    let projection = cgmath::perspective(Deg(90.0), 1.0, 0.1, 10.0);
+    for (layer, view) in cube_face_views(Point3::new(0.0, 0.0, 0.0)).iter() {
+        let mut uniforms = uniforms.clone();
+        uniforms.add_str_key_value("projection", UniformValue::Mat4(projection.into()));
+        uniforms.add_str_key_value("view", UniformValue::Mat4((*view).into()));
+
+        let mut framebuffer = SimpleFrameBuffer::new(display, mipmap.image(*layer)).map_err(|err| LoadError::gpu("帧缓冲", err))?;
+        framebuffer.clear_color(0.0, 0.0, 0.0, 1.0);
+        framebuffer.draw(&cube.vertex_buffer, &cube.index_buffer, program, &uniforms, &Default::default())
+            .map_err(|err| LoadError::gpu("IBL预计算", err))?;
+    }
+    Ok(())
+}
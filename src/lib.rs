@@ -1,11 +1,16 @@
-use std::{collections::{HashMap}, rc::Rc, fs::{self}, time::{Instant, Duration}};
+use std::{collections::{HashMap}, rc::Rc, fs::{self}, io, path::{Path, PathBuf}, time::{Instant, Duration}};
 
-use cgmath::{Vector3, Zero, Vector2};
+use cgmath::{Zero, Vector2};
 use context::{LoopContext};
+use error::LoadError;
+use geometry::{triangulate::triangulate, normals::{generate_normals, NormalMode}, bounds::{Aabb, BoundingSphere}};
+use mesh::{MeshData, merge_by_material};
+use animation::morph::{MorphTarget, MorphTargets, MorphMode};
+use uniforms::TextureSampler;
 
-use glium::{implement_vertex, vertex::VertexBufferAny, index::{IndexBufferAny, self}, Display, IndexBuffer, program::{SourceCode, ProgramCreationInput}, Program, glutin::{event_loop::{EventLoop, ControlFlow}, event::{Event, WindowEvent, StartCause, KeyboardInput, VirtualKeyCode, ElementState}}};
-use material::{Material, MaterialLoader};
-use obj::{ObjMaterial};
+use glium::{implement_vertex, vertex::{VertexBufferAny, VerticesSource}, index::{IndexBufferAny, IndicesSource, self}, Display, IndexBuffer, program::{SourceCode, ProgramCreationInput}, Program, glutin::{event_loop::{EventLoop, ControlFlow}, event::{Event, WindowEvent, StartCause, KeyboardInput, VirtualKeyCode, ElementState}}};
+use material::{Material, MaterialLoader, PbrMaterial};
+use obj::{ObjMaterial, IndexTuple, Mtl};
 
 pub mod utils;
 pub mod camera;
@@ -18,8 +23,20 @@ pub mod objectsv2;
 pub mod event;
 pub mod mouse;
 pub mod context;
+pub mod error;
+pub mod geometry;
+pub mod loader;
+pub mod mesh;
+pub mod mesh_cache;
+pub mod export;
+pub mod hierarchy;
+pub mod animation;
+pub mod ibl;
+pub mod shadow;
 
+/// 内存布局固定为repr(C)，网格缓存直接按此布局读写
 #[derive(Copy, Clone)]
+#[repr(C)]
 pub struct Vertex {
     pub position: [f32; 3],
     pub normal: [f32; 3],
@@ -39,56 +56,359 @@ implement_vertex!(ColorVertex, position, color);
 
 pub struct Model {
     pub vertex_buffer: VertexBufferAny,
-    pub index_buffer: IndexBufferAny,
+    pub index_buffer: ModelIndexBuffer,
     pub material: Option<Rc<Material>>,
+    /// PBR材质，glTF等使用金属度-粗糙度工作流的模型格式加载
+    pub pbr_material: Option<Rc<PbrMaterial>>,
+    /// 按材质合并后各对象的子范围，可以单独绘制或隐藏，未记录时为空
+    pub ranges: Vec<ModelRange>,
+    /// 局部坐标系下的包围盒
+    pub bounds: Aabb,
+    /// 局部坐标系下的包围球
+    pub sphere: BoundingSphere,
+    /// 简化后的各级LOD索引缓冲，与模型共享顶点缓冲，第0级为`index_buffer`
+    pub lods: Vec<IndexBufferAny>,
+    /// 形变目标，没有时为None
+    pub morph: Option<MorphTargets>,
+}
+
+/**
+ * 合并模型中一个对象的子范围，与模型共享顶点缓冲与索引缓冲
+ */
+pub struct ModelRange {
+    pub name: String,
+    /// 该对象在模型索引缓冲中的第一个索引
+    pub start: usize,
+    /// 该对象的索引数量
+    pub count: usize,
+    pub visible: bool,
+}
+
+impl Model {
+
+    /// 将CPU端网格数据上传为绘制单位
+    pub fn from_mesh<V: glium::Vertex + Send + 'static>(display: &Display, mesh: &MeshData<V>, material: Option<Rc<Material>>) -> Result<Model, LoadError> {
+        let vertex_buffer = mesh.vertex_buffer(display)?.into();
+        let index_buffer = ModelIndexBuffer::new(display, mesh.primitive_type, mesh.vertices.len(), &mesh.indices)?;
+        let ranges = mesh.ranges.iter().map(|range| ModelRange {
+            name: range.name.clone(),
+            start: range.start,
+            count: range.count,
+            visible: true,
+        }).collect();
+        let mut lods = Vec::with_capacity(mesh.lods.len());
+        for indices in mesh.lods.iter() {
+            lods.push(create_index_buffer(display, mesh.primitive_type, mesh.vertices.len(), indices)?);
+        }
+        Ok(Model {
+            vertex_buffer,
+            index_buffer,
+            material,
+            pbr_material: None,
+            ranges,
+            bounds: mesh.bounds,
+            sphere: mesh.sphere,
+            lods,
+            morph: None,
+        })
+    }
+
+    /**
+     * 为模型附加形变目标，mesh为创建该模型时使用的网格
+     * CPU模式下形变结果写入形变目标自己的顶点缓冲，绘制时通过`Model::vertices`取得
+     * 顶点着色器模式下绘制时需要通过`MorphTargets::add_to_uniforms`传入偏移与权重
+     */
+    pub fn with_morph_targets(mut self, display: &Display, mesh: &MeshData<Vertex>, targets: Vec<MorphTarget>, mode: MorphMode) -> Result<Model, LoadError> {
+        if self.vertex_buffer.len() != mesh.vertices.len() {
+            return Err(LoadError::invalid("形变目标", "网格与模型的顶点缓冲不一致"));
+        }
+        self.morph = Some(MorphTargets::new(display, &mesh.vertices, targets, mode)?);
+        Ok(self)
+    }
+
+    /**
+     * 按当前形变权重更新顶点，每帧修改权重后调用
+     * 只有CPU模式且权重发生变化时才会重新写入顶点缓冲
+     */
+    pub fn update_morph(&mut self) {
+        if let Some(morph) = self.morph.as_mut() {
+            morph.update();
+        }
+    }
+
+    /// 绘制时使用的顶点，CPU模式的形变结果优先于原始顶点缓冲
+    pub fn vertices(&self) -> VerticesSource<'_> {
+        match self.morph.as_ref().and_then(MorphTargets::vertex_buffer) {
+            Some(vertex_buffer) => vertex_buffer.into(),
+            None => (&self.vertex_buffer).into(),
+        }
+    }
+
+    /// 指定LOD级别的索引缓冲，0为原始网格，超出已生成级别时使用最粗糙的一级
+    pub fn lod_index_buffer(&self, level: usize) -> IndicesSource<'_> {
+        match level {
+            0 => (&self.index_buffer).into(),
+            _ => self.lods.get(level - 1).or(self.lods.last()).map(Into::into).unwrap_or_else(|| (&self.index_buffer).into()),
+        }
+    }
+
+    /// 一个模型文件加载出的所有绘制单位的整体包围盒
+    pub fn bounds_of(models: &[Model]) -> Aabb {
+        models.iter().fold(Aabb::empty(), |bounds, model| bounds.merge(&model.bounds))
+    }
+
+    /// 一个模型文件加载出的所有绘制单位的整体包围球
+    pub fn sphere_of(models: &[Model]) -> BoundingSphere {
+        models.iter().fold(BoundingSphere::empty(), |sphere, model| sphere.merge(&model.sphere))
+    }
+
+    /// 设置指定名称对象的可见性，返回是否找到该对象
+    pub fn set_visible(&mut self, name: &str, visible: bool) -> bool {
+        let mut found = false;
+        for range in self.ranges.iter_mut().filter(|range| range.name == name) {
+            range.visible = visible;
+            found = true;
+        }
+        found
+    }
+
+    /**
+     * 绘制时使用的索引
+     * 所有对象都可见时只返回整个模型的索引缓冲（一次绘制调用），否则返回各可见对象在索引缓冲中的切片
+     */
+    pub fn visible_indices(&self) -> Vec<IndicesSource<'_>> {
+        if self.ranges.iter().all(|range| range.visible) {
+            vec![(&self.index_buffer).into()]
+        } else {
+            self.ranges.iter()
+                .filter(|range| range.visible)
+                .filter_map(|range| self.index_buffer.slice(range.start, range.count))
+                .collect()
+        }
+    }
 }
 
 /**
  * 将一个模型数据加载为多个绘制单位
+ * 加载失败时直接panic，需要处理错误时使用`try_load_wavefront_obj_as_models`
  */
 pub fn load_wavefront_obj_as_models(display: &Display, basepath: &str, obj_file: &str) -> Vec<Model> {
+    try_load_wavefront_obj_as_models(display, basepath, obj_file).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/**
+ * 将一个模型数据加载为多个绘制单位
+ * 颜色贴图缺失时使用占位纹理，数据贴图缺失时不绑定，打印警告后不会中断加载
+ */
+pub fn try_load_wavefront_obj_as_models(display: &Display, basepath: &str, obj_file: &str) -> Result<Vec<Model>, LoadError> {
+    try_load_wavefront_obj_as_models_with(display, basepath, obj_file, &ObjLoadOptions::default())
+}
+
+/**
+ * OBJ模型加载选项
+ */
+#[derive(Clone, Debug)]
+pub struct ObjLoadOptions {
+    /// 计算切线与副切线，顶点类型为`RawVertexPNTTB`，用于法线贴图/视差贴图；否则为`Vertex`
+    pub tangents: bool,
+    /// 顶点缺少法向量时的生成方式，默认按面积加权平滑
+    pub normals: NormalMode,
+    /// 使用二进制网格缓存（OBJ旁的`.meshcache`文件），默认开启，源文件变化后自动重新解析
+    pub cache: bool,
+    /// 跨对象、组合并材质相同的几何数据，每种材质只产生一个绘制单位
+    pub merge_by_material: bool,
+    /// 合并时记录每个对象的子范围，使对象仍可单独绘制或隐藏
+    pub object_ranges: bool,
+    /// 加载时生成的LOD，每级相对原始三角形数量的比例，按从大到小排列，为空时不生成
+    pub lods: Vec<f32>,
+    /// 重排三角形与顶点以提高顶点缓存命中率，并打印优化前后的ACMR
+    pub optimize_vertex_cache: bool,
+    /// 材质贴图的采样描述（环绕、过滤、mipmap、各向异性）
+    pub sampler: TextureSampler,
+}
+
+impl Default for ObjLoadOptions {
+    fn default() -> Self {
+        ObjLoadOptions {
+            tangents: false,
+            normals: NormalMode::default(),
+            cache: true,
+            merge_by_material: false,
+            object_ranges: false,
+            lods: Vec::new(),
+            optimize_vertex_cache: false,
+            sampler: TextureSampler::default(),
+        }
+    }
+}
+
+impl ObjLoadOptions {
+
+    /// 生成带切线的顶点
+    pub fn with_tangents() -> Self {
+        ObjLoadOptions { tangents: true, ..Default::default() }
+    }
+}
+
+/**
+ * 按指定选项将一个模型数据加载为多个绘制单位
+ */
+pub fn try_load_wavefront_obj_as_models_with(display: &Display, basepath: &str, obj_file: &str, options: &ObjLoadOptions) -> Result<Vec<Model>, LoadError> {
+    let obj_meshes = load_wavefront_obj_as_meshes(basepath, obj_file, options)?;
+    upload_obj_meshes(display, basepath, &obj_meshes, options)
+}
+
+/// 加载材质并上传所有网格，返回的绘制单位与`obj_meshes.meshes`一一对应
+pub(crate) fn upload_obj_meshes(display: &Display, basepath: &str, obj_meshes: &ObjMeshes, options: &ObjLoadOptions) -> Result<Vec<Model>, LoadError> {
+    // 加载材质
+    let mut material_loader = MaterialLoader::new().with_sampler(options.sampler);
+    material_loader.parse_and_load(&obj_meshes.material_libs, basepath, display)?;
+
+    let mut models = Vec::with_capacity(obj_meshes.meshes.len());
+    for mesh in obj_meshes.meshes.iter() {
+        let material = mesh.material.as_ref().and_then(|name| material_loader.find_in_cache(name.clone()));
+        let mut model = if options.tangents {
+            Model::from_mesh(display, &mesh.with_tangents(), material)?
+        } else {
+            Model::from_mesh(display, mesh, material)?
+        };
+        model.pbr_material = mesh.material.as_ref().and_then(|name| material_loader.find_pbr_in_cache(name));
+        models.push(model);
+    }
+    Ok(models)
+}
+
+/**
+ * OBJ模型的CPU端解析结果
+ */
+pub struct ObjMeshes {
+    /// 每个对象的每个组对应一个网格，开启按材质合并时每种材质对应一个网格
+    pub meshes: Vec<MeshData<Vertex>>,
+    /// 模型引用的材质库，需要通过`MaterialLoader`加载到GPU
+    pub material_libs: Vec<Mtl>,
+}
+
+/**
+ * 解析OBJ模型为CPU端网格数据，不需要OpenGL上下文
+ * 多边形会被三角化，缺少法向量的顶点按选项生成法向量
+ * 开启缓存时优先读取二进制网格缓存，缓存失效时重新解析并写入缓存
+ */
+pub fn load_wavefront_obj_as_meshes(basepath: &str, obj_file: &str, options: &ObjLoadOptions) -> Result<ObjMeshes, LoadError> {
     let mut obj_path = String::from(basepath);
     obj_path.push_str(obj_file);
-    let mut obj = obj::Obj::load(obj_path).unwrap();
-    // 需要手动加载材质
-    obj.load_mtls().unwrap();
-    let data = obj.data;
+    let path = Path::new(&obj_path);
 
-    // 加载材质
-    let mut material_loader = MaterialLoader::new();
-    material_loader.parse_and_load(&data.material_libs, basepath, display);
+    let cached = if options.cache { mesh_cache::read(path, options.normals) } else { None };
+    let mut obj_meshes = match cached {
+        Some(cached) => {
+            let material_libs = load_mtl_files(path, &cached.mtl_files)?;
+            ObjMeshes { meshes: cached.meshes, material_libs }
+        },
+        None => {
+            let obj_meshes = parse_wavefront_obj(&obj_path, options)?;
+            if options.cache {
+                let mtl_files: Vec<String> = obj_meshes.material_libs.iter().map(|mtl| mtl.filename.clone()).collect();
+                // 缓存写入失败不影响本次加载
+                if let Err(err) = mesh_cache::write(path, &mtl_files, options.normals, &obj_meshes.meshes) {
+                    println!("警告: 写入网格缓存{}失败: {}", mesh_cache::cache_path(path).display(), err);
+                }
+            }
+            obj_meshes
+        },
+    };
 
-    let mut models = Vec::new();
+    if options.merge_by_material {
+        obj_meshes.meshes = merge_by_material(obj_meshes.meshes, options.object_ranges);
+    }
+    if !options.lods.is_empty() {
+        for mesh in obj_meshes.meshes.iter_mut() {
+            mesh.build_lods(&options.lods);
+        }
+    }
+    if options.optimize_vertex_cache {
+        for mesh in obj_meshes.meshes.iter_mut() {
+            let report = mesh.optimize_vertex_cache();
+            println!("顶点缓存优化 {}/{}: ACMR {:.3} -> {:.3}",
+                mesh.name.as_deref().unwrap_or("-"), mesh.material.as_deref().unwrap_or("-"), report.acmr_before, report.acmr_after);
+        }
+    }
+    Ok(obj_meshes)
+}
+
+/// 加载OBJ引用的MTL材质库，文件名相对于OBJ所在目录
+fn load_mtl_files(obj_path: &Path, mtl_files: &[String]) -> Result<Vec<Mtl>, LoadError> {
+    let dir = obj_path.parent().unwrap_or_else(|| Path::new(""));
+    let mut material_libs = Vec::with_capacity(mtl_files.len());
+    for filename in mtl_files {
+        let path = dir.join(filename);
+        let source = fs::read_to_string(&path).map_err(|err| LoadError::io(&path.to_string_lossy(), err))?;
+        let mut mtl = Mtl::new(filename.clone());
+        if let Err(err) = mtl.reload(material::strip_mtl_extensions(&source).as_bytes()) {
+            return Err(obj::MtlLibsLoadError(vec![(filename.clone(), err)]).into());
+        }
+        material_libs.push(mtl);
+    }
+    Ok(material_libs)
+}
+
+/// 解析OBJ文本，三角化并去重顶点
+fn parse_wavefront_obj(obj_path: &str, options: &ObjLoadOptions) -> Result<ObjMeshes, LoadError> {
+    let mut obj = obj::Obj::load(obj_path)?;
+    // 需要手动加载材质，obj库无法解析的扩展指令先过滤掉
+    obj.load_mtls_fn(|dir, mtllib| {
+        let source = fs::read_to_string(dir.join(mtllib))?;
+        Ok(io::Cursor::new(material::strip_mtl_extensions(&source).into_bytes()))
+    })?;
+    let data = obj.data;
+
+    let mut meshes = Vec::new();
     for obj in data.objects.iter() {
         for group in obj.groups.iter() {
-            // 目前认为相同材质的所有面（具有法向量和材质坐标的顶点）可以一起绘制，放入一个model
+            // 目前认为相同材质的所有面（具有法向量和材质坐标的顶点）可以一起绘制，放入一个mesh
             let mut cache = HashMap::new();
             let mut vertex_data = Vec::new();
             let mut index_data = Vec::new();
 
-            // 创建材质
-            let material = match &group.material {
-                Some(material) => {
-                    let name = match material {
-                        ObjMaterial::Ref(name) => name,
-                        ObjMaterial::Mtl(meterial) => &meterial.name,
-                    };
-                    material_loader.find_in_cache(name.clone())
-                },
-                None => None,
-            };
+            // 材质名称
+            let material = group.material.as_ref().map(|material| match material {
+                ObjMaterial::Ref(name) => name.clone(),
+                ObjMaterial::Mtl(meterial) => meterial.name.clone(),
+            });
+
+            // 按面进行绘制，四边形及多边形需要先三角化，每个角为OBJ中的顶点索引
+            let mut triangles: Vec<[&IndexTuple; 3]> = Vec::new();
             for poly in group.polys.iter() {
-                // 按面进行绘制
-                // 创建顶点
-                for index in poly.0.iter() {
-                    let i = match cache.get(index) {
+                let poly_positions: Vec<[f32; 3]> = poly.0.iter().map(|index| data.position[index.0]).collect();
+                for triangle in triangulate(&poly_positions) {
+                    triangles.push(triangle.map(|i| &poly.0[i]));
+                }
+            }
+
+            // 存在缺少法向量的顶点时自动生成，已有的法向量保持不变
+            let generated_normals = if triangles.iter().any(|triangle| triangle.iter().any(|index| index.2.is_none())) {
+                let position_triangles: Vec<[usize; 3]> = triangles.iter().map(|triangle| triangle.map(|index| index.0)).collect();
+                generate_normals(&data.position, &position_triangles, options.normals)
+            } else {
+                Vec::new()
+            };
+
+            // 创建顶点
+            for (face, triangle) in triangles.iter().enumerate() {
+                for (corner, index) in triangle.iter().enumerate() {
+                    let generated_normal = match index.2 {
+                        Some(_) => None,
+                        None => Some(generated_normals[face][corner]),
+                    };
+                    // 生成的法向量也参与去重，平面着色或硬边处同一位置会拆分为多个顶点
+                    let key = (*index, generated_normal.map(|normal| normal.map(f32::to_bits)));
+                    let i = match cache.get(&key) {
                         Some(vertex_index) => *vertex_index,
                         None => {
                             let vertex = Vertex {
                                 position: data.position[index.0],
                                 normal: match index.2 {
                                     Some(i) => data.normal[i],
-                                    None => Into::<[f32; 3]>::into(Vector3::zero()),
+                                    None => generated_normals[face][corner],
                                 },
                                 texture: match index.1 {
                                     Some(i) => data.texture[i],
@@ -97,23 +417,84 @@ pub fn load_wavefront_obj_as_models(display: &Display, basepath: &str, obj_file:
                             };
                             let vertex_index = vertex_data.len();
                             vertex_data.push(vertex);
-                            cache.insert(index, vertex_index);
+                            cache.insert(key, vertex_index);
                             vertex_index
                         }
                     };
-                    index_data.push(i as u16);
+                    index_data.push(i as u32);
                 }
             }
-            let vertex_buffer = glium::vertex::VertexBuffer::new(display, &vertex_data).unwrap().into();
-            let index_buffer = IndexBuffer::new(display, index::PrimitiveType::TrianglesList, &index_data).unwrap().into();
-            models.push(Model {
-                vertex_buffer: vertex_buffer,
-                index_buffer: index_buffer,
-                material: material,
-            });
+            meshes.push(MeshData::new(vertex_data, index_data, index::PrimitiveType::TrianglesList).with_material(material).with_name(Some(obj.name.clone())).with_group(Some(group.name.clone())));
+        }
+    }
+    Ok(ObjMeshes { meshes, material_libs: data.material_libs })
+}
+
+/**
+ * 根据顶点数量选择索引类型创建索引缓冲
+ * 顶点数不超过u16可表示范围时使用u16，否则使用u32，避免索引溢出回绕
+ */
+pub fn create_index_buffer(display: &Display, primitive_type: index::PrimitiveType, vertex_count: usize, indices: &[u32]) -> Result<IndexBufferAny, LoadError> {
+    Ok(ModelIndexBuffer::new(display, primitive_type, vertex_count, indices)?.into())
+}
+
+/**
+ * 保留索引类型的索引缓冲，可以切片绘制其中一段索引
+ * 索引类型的选择与`create_index_buffer`相同
+ */
+pub enum ModelIndexBuffer {
+    U16(IndexBuffer<u16>),
+    U32(IndexBuffer<u32>),
+}
+
+impl ModelIndexBuffer {
+
+    pub fn new(display: &Display, primitive_type: index::PrimitiveType, vertex_count: usize, indices: &[u32]) -> Result<Self, LoadError> {
+        if vertex_count <= u16::MAX as usize {
+            let indices: Vec<u16> = indices.iter().map(|i| *i as u16).collect();
+            Ok(ModelIndexBuffer::U16(IndexBuffer::new(display, primitive_type, &indices)?))
+        } else {
+            Ok(ModelIndexBuffer::U32(IndexBuffer::new(display, primitive_type, indices)?))
+        }
+    }
+
+    /// 索引数量
+    pub fn len(&self) -> usize {
+        match self {
+            ModelIndexBuffer::U16(buffer) => buffer.len(),
+            ModelIndexBuffer::U32(buffer) => buffer.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 从start开始的count个索引，超出范围时返回None
+    pub fn slice(&self, start: usize, count: usize) -> Option<IndicesSource<'_>> {
+        match self {
+            ModelIndexBuffer::U16(buffer) => buffer.slice(start..start + count).map(Into::into),
+            ModelIndexBuffer::U32(buffer) => buffer.slice(start..start + count).map(Into::into),
+        }
+    }
+}
+
+impl<'a> From<&'a ModelIndexBuffer> for IndicesSource<'a> {
+    fn from(buffer: &'a ModelIndexBuffer) -> Self {
+        match buffer {
+            ModelIndexBuffer::U16(buffer) => buffer.into(),
+            ModelIndexBuffer::U32(buffer) => buffer.into(),
+        }
+    }
+}
+
+impl From<ModelIndexBuffer> for IndexBufferAny {
+    fn from(buffer: ModelIndexBuffer) -> Self {
+        match buffer {
+            ModelIndexBuffer::U16(buffer) => buffer.into(),
+            ModelIndexBuffer::U32(buffer) => buffer.into(),
         }
     }
-    models
 }
 
 /// 渲染结果的动作
@@ -203,8 +584,51 @@ fn handle_keyboard_input(input: KeyboardInput) -> Option<ControlFlow> {
 
 
 pub fn create_program(vert_source_path: &str, frag_source_path: &str, display: &Display) -> Program {
-    let obj_vert_source = fs::read_to_string(vert_source_path).unwrap();
-    let obj_frag_source = fs::read_to_string(frag_source_path).unwrap();
+    try_create_program(vert_source_path, frag_source_path, display).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn create_program_vgf(vert_source_path: &str, geometry_source_path: &str, frag_source_path: &str, display: &Display) -> Program {
+    try_create_program_vgf(vert_source_path, geometry_source_path, frag_source_path, display).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/**
+ * 读取着色器源文件，并展开其中的`#include "文件"`指令
+ * 被引入的文件路径相对于引入它的文件，同一个文件只会被引入一次
+ */
+fn read_shader_source(path: &str) -> Result<String, LoadError> {
+    let mut included = Vec::new();
+    expand_shader_includes(Path::new(path), &mut included)
+}
+
+fn expand_shader_includes(path: &Path, included: &mut Vec<PathBuf>) -> Result<String, LoadError> {
+    let display_path = path.to_string_lossy();
+    let source = fs::read_to_string(path).map_err(|err| LoadError::io(&display_path, err))?;
+    included.push(fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()));
+
+    let mut result = String::with_capacity(source.len());
+    for line in source.lines() {
+        let include = line.trim().strip_prefix("#include").map(|rest| rest.trim().trim_matches('"'));
+        match include {
+            Some(file) if !file.is_empty() => {
+                let include_path = path.parent().unwrap_or_else(|| Path::new("")).join(file);
+                let canonical = fs::canonicalize(&include_path).unwrap_or_else(|_| include_path.clone());
+                if !included.contains(&canonical) {
+                    result.push_str(&expand_shader_includes(&include_path, included)?);
+                    result.push('\n');
+                }
+            },
+            _ => {
+                result.push_str(line);
+                result.push('\n');
+            },
+        }
+    }
+    Ok(result)
+}
+
+pub fn try_create_program(vert_source_path: &str, frag_source_path: &str, display: &Display) -> Result<Program, LoadError> {
+    let obj_vert_source = read_shader_source(vert_source_path)?;
+    let obj_frag_source = read_shader_source(frag_source_path)?;
     let obj_shader_source = SourceCode {
         vertex_shader: obj_vert_source.as_str(),
         tessellation_control_shader: None,
@@ -216,13 +640,13 @@ pub fn create_program(vert_source_path: &str, frag_source_path: &str, display: &
     glium::Program::new(
         display,
         ProgramCreationInput::from(obj_shader_source)
-    ).unwrap()
+    ).map_err(|err| LoadError::shader(err, vert_source_path, None, frag_source_path))
 }
 
-pub fn create_program_vgf(vert_source_path: &str, geometry_source_path: &str, frag_source_path: &str, display: &Display) -> Program {
-    let obj_vert_source = fs::read_to_string(vert_source_path).unwrap();
-    let obj_frag_source = fs::read_to_string(frag_source_path).unwrap();
-    let geometry_source = fs::read_to_string(geometry_source_path).unwrap();
+pub fn try_create_program_vgf(vert_source_path: &str, geometry_source_path: &str, frag_source_path: &str, display: &Display) -> Result<Program, LoadError> {
+    let obj_vert_source = read_shader_source(vert_source_path)?;
+    let obj_frag_source = read_shader_source(frag_source_path)?;
+    let geometry_source = read_shader_source(geometry_source_path)?;
     let obj_shader_source = SourceCode {
         vertex_shader: obj_vert_source.as_str(),
         tessellation_control_shader: None,
@@ -234,5 +658,5 @@ pub fn create_program_vgf(vert_source_path: &str, geometry_source_path: &str, fr
     glium::Program::new(
         display,
         ProgramCreationInput::from(obj_shader_source)
-    ).unwrap()
-}
\ No newline at end of file
+    ).map_err(|err| LoadError::shader(err, vert_source_path, Some(geometry_source_path), frag_source_path))
+}
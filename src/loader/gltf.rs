@@ -0,0 +1,559 @@
+use std::{rc::Rc, collections::HashMap};
+
+use cgmath::{Matrix4, Matrix3, Vector3, Vector4, Quaternion, SquareMatrix, InnerSpace, Matrix};
+use glium::{Display, texture::{RawImage2d, SrgbTexture2d}, Texture2d, index::PrimitiveType, uniforms::{SamplerWrapFunction, MinifySamplerFilter, MagnifySamplerFilter}};
+
+use crate::{Vertex, Model, mesh::MeshData, error::LoadError, material::{PbrMaterial, MaterialTexture}, geometry::normals::smooth_vertex_normals, objectsv2::RawVertexPNTJW, uniforms::TextureSampler};
+use crate::animation::{morph::{MorphTarget, MorphMode}, skeleton::{Joint, Skeleton, Transform, normalize_weights}, clip::{AnimationClip, Interpolation, JointChannel, Keyframe, Keyframes}, skinning::{AnimatedModel, Rig, SkinnedModel, MAX_JOINTS}};
+
+/**
+ * 加载glTF 2.0模型（.gltf或.glb，缓冲可以内嵌或外置），每个图元作为一个绘制单位
+ * 节点变换已经应用到顶点上，材质映射为金属度-粗糙度PBR材质
+ * 加载失败时直接panic，需要处理错误时使用`try_load_gltf_as_models`
+ */
+pub fn load_gltf_as_models(display: &Display, path: &str) -> Vec<Model> {
+    try_load_gltf_as_models(display, path).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_load_gltf_as_models(display: &Display, path: &str) -> Result<Vec<Model>, LoadError> {
+    println!("加载glTF模型: {}", path);
+    let (document, buffers, images) = ::gltf::import(path)?;
+
+    let mut loader = GltfLoader::new(display, &buffers, &images);
+
+    let scene = document.default_scene().or_else(|| document.scenes().next());
+    match scene {
+        Some(scene) => {
+            for node in scene.nodes() {
+                loader.load_node(&node, Matrix4::identity())?;
+            }
+        },
+        None => {
+            // 没有场景时直接加载所有网格
+            for mesh in document.meshes() {
+                loader.load_mesh(&mesh, Matrix4::identity())?;
+            }
+        },
+    }
+
+    Ok(loader.models)
+}
+
+/**
+ * 加载带骨骼动画的glTF模型，每个蒙皮（skin）对应一个骨架，每个动画在每个骨架中对应一个同索引的片段
+ * 蒙皮网格保持绑定姿态，按规范忽略网格节点自身的变换，放置由关节的全局变换决定
+ * 加载失败时直接panic，需要处理错误时使用`try_load_gltf_animated`
+ */
+pub fn load_gltf_animated(display: &Display, path: &str) -> AnimatedModel {
+    try_load_gltf_animated(display, path).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_load_gltf_animated(display: &Display, path: &str) -> Result<AnimatedModel, LoadError> {
+    println!("加载glTF骨骼动画模型: {}", path);
+    let (document, buffers, images) = ::gltf::import(path)?;
+
+    // 节点索引 -> 父节点索引，glTF只记录子节点
+    let mut parents: Vec<Option<usize>> = vec![None; document.nodes().len()];
+    for node in document.nodes() {
+        for child in node.children() {
+            parents[child.index()] = Some(node.index());
+        }
+    }
+
+    let mut rigs = Vec::new();
+    // 每个蒙皮：glTF关节顺序 -> 骨架关节索引
+    let mut joint_remaps = Vec::new();
+    for skin in document.skins() {
+        let (skeleton, remap) = load_skeleton(&skin, &document, &buffers, &parents, path)?;
+        rigs.push(Rig { skeleton, clips: Vec::new() });
+        joint_remaps.push(remap);
+    }
+    for animation in document.animations() {
+        for (rig, (skin, remap)) in rigs.iter_mut().zip(document.skins().zip(joint_remaps.iter())) {
+            rig.clips.push(load_clip(&animation, &skin, remap, &buffers, path)?);
+        }
+    }
+
+    let mut loader = GltfLoader::new(display, &buffers, &images);
+    let mut models = Vec::new();
+    for node in document.nodes() {
+        let (mesh, skin) = match (node.mesh(), node.skin()) {
+            (Some(mesh), Some(skin)) => (mesh, skin),
+            _ => continue,
+        };
+        let remap = &joint_remaps[skin.index()];
+        for primitive in mesh.primitives() {
+            let mesh = match loader.read_primitive(&primitive, Matrix4::identity()) {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            let reader = primitive.reader(|buffer| Some(&*buffers[buffer.index()]));
+            let joints: Vec<[u16; 4]> = match reader.read_joints(0) {
+                Some(joints) => joints.into_u16().collect(),
+                None => vec![[0; 4]; mesh.vertices.len()],
+            };
+            let weights: Vec<[f32; 4]> = match reader.read_weights(0) {
+                Some(weights) => weights.into_f32().collect(),
+                None => vec![[1.0, 0.0, 0.0, 0.0]; mesh.vertices.len()],
+            };
+            if joints.len() != mesh.vertices.len() || weights.len() != mesh.vertices.len() {
+                return Err(LoadError::parse(path, "关节或权重数量与顶点数量不一致"));
+            }
+
+            let mut vertices = Vec::with_capacity(mesh.vertices.len());
+            for ((vertex, joints), weights) in mesh.vertices.iter().zip(joints.iter()).zip(weights.iter()) {
+                let mut mapped = [0u32; 4];
+                for (target, joint) in mapped.iter_mut().zip(joints.iter()) {
+                    *target = *remap.get(*joint as usize).ok_or_else(|| LoadError::parse(path, format!("顶点引用了不存在的关节{}", joint)))?;
+                }
+                vertices.push(RawVertexPNTJW {
+                    position: vertex.position,
+                    normal: vertex.normal,
+                    texture: vertex.texture,
+                    joints: mapped,
+                    weights: normalize_weights(*weights),
+                });
+            }
+            let skinned_mesh = MeshData::new(vertices, mesh.indices, mesh.primitive_type);
+            let mut model = SkinnedModel::from_mesh(display, &skinned_mesh, skin.index())?;
+            model.pbr_material = Some(loader.load_material(&primitive.material())?);
+            models.push(model);
+        }
+    }
+
+    Ok(AnimatedModel { models, rigs })
+}
+
+/**
+ * 将蒙皮的关节重排为父关节在前的骨架，返回骨架与glTF关节顺序到骨架索引的映射
+ * 关节之间夹着的非关节节点的变换会被忽略
+ */
+fn load_skeleton(skin: &::gltf::Skin, document: &::gltf::Document, buffers: &[::gltf::buffer::Data], parents: &[Option<usize>], path: &str) -> Result<(Skeleton, Vec<u32>), LoadError> {
+    let joint_nodes: Vec<usize> = skin.joints().map(|node| node.index()).collect();
+    if joint_nodes.len() > MAX_JOINTS {
+        return Err(LoadError::parse(path, format!("蒙皮{}有{}个关节，超过上限{}", skin.index(), joint_nodes.len(), MAX_JOINTS)));
+    }
+    let reader = skin.reader(|buffer| Some(&*buffers[buffer.index()]));
+    let inverse_binds: Vec<Matrix4<f32>> = match reader.read_inverse_bind_matrices() {
+        Some(matrices) => matrices.map(Matrix4::from).collect(),
+        None => vec![Matrix4::identity(); joint_nodes.len()],
+    };
+
+    let depth = |mut node: usize| {
+        let mut depth = 0;
+        while let Some(parent) = parents[node] {
+            depth += 1;
+            node = parent;
+        }
+        depth
+    };
+    // 按节点深度稳定排序，保证父关节排在子关节前面
+    let mut order: Vec<usize> = (0..joint_nodes.len()).collect();
+    order.sort_by_key(|i| depth(joint_nodes[*i]));
+    let mut remap = vec![0u32; joint_nodes.len()];
+    for (skeleton_index, skin_index) in order.iter().enumerate() {
+        remap[*skin_index] = skeleton_index as u32;
+    }
+
+    let nodes: Vec<::gltf::Node> = document.nodes().collect();
+    // 最近的作为关节的祖先节点
+    let parent_joint = |node: usize| {
+        let mut current = parents[node];
+        while let Some(parent) = current {
+            if let Some(i) = joint_nodes.iter().position(|joint| *joint == parent) {
+                return Some(remap[i] as usize);
+            }
+            current = parents[parent];
+        }
+        None
+    };
+    let joints: Vec<Joint> = order.iter().map(|skin_index| {
+        let node = &nodes[joint_nodes[*skin_index]];
+        let (translation, rotation, scale) = node.transform().decomposed();
+        Joint {
+            name: node.name().map(str::to_string).unwrap_or_else(|| format!("joint{}", node.index())),
+            parent: parent_joint(node.index()),
+            inverse_bind: inverse_binds.get(*skin_index).copied().unwrap_or_else(Matrix4::identity),
+            rest: Transform {
+                translation: Vector3::from(translation),
+                rotation: Quaternion::new(rotation[3], rotation[0], rotation[1], rotation[2]),
+                scale: Vector3::from(scale),
+            },
+        }
+    }).collect();
+    let mut skeleton = Skeleton::new(joints).map_err(|message| LoadError::parse(path, message))?;
+
+    // 根关节之上的节点（例如Blender导出的Armature节点）的全局变换
+    if let Some(first) = order.first() {
+        let mut root_transform = Matrix4::identity();
+        let mut current = parents[joint_nodes[*first]];
+        while let Some(parent) = current {
+            root_transform = Matrix4::from(nodes[parent].transform().matrix()) * root_transform;
+            current = parents[parent];
+        }
+        skeleton.root_transform = root_transform;
+    }
+    Ok((skeleton, remap))
+}
+
+/**
+ * 读取动画中作用于该蒙皮关节的通道，三次样条插值只取关键帧的值并按线性插值处理
+ */
+fn load_clip(animation: &::gltf::Animation, skin: &::gltf::Skin, remap: &[u32], buffers: &[::gltf::buffer::Data], path: &str) -> Result<AnimationClip, LoadError> {
+    use ::gltf::animation::util::ReadOutputs;
+
+    // 节点索引 -> 骨架关节索引
+    let node_joints: HashMap<usize, usize> = skin.joints().zip(remap.iter())
+        .map(|(node, joint)| (node.index(), *joint as usize))
+        .collect();
+
+    let mut channels: HashMap<usize, JointChannel> = HashMap::new();
+    for channel in animation.channels() {
+        let joint = match node_joints.get(&channel.target().node().index()) {
+            Some(joint) => *joint,
+            None => continue,
+        };
+        let reader = channel.reader(|buffer| Some(&*buffers[buffer.index()]));
+        let times: Vec<f32> = match reader.read_inputs() {
+            Some(inputs) => inputs.collect(),
+            None => continue,
+        };
+        let interpolation = match channel.sampler().interpolation() {
+            ::gltf::animation::Interpolation::Step => Some(Interpolation::Step),
+            ::gltf::animation::Interpolation::Linear => Some(Interpolation::Linear),
+            // 三次样条退化为线性插值
+            ::gltf::animation::Interpolation::CubicSpline => None,
+        };
+        let target = channels.entry(joint).or_insert_with(|| JointChannel::new(joint));
+        let parse_error = |message| LoadError::parse(path, message);
+        match reader.read_outputs() {
+            Some(ReadOutputs::Translations(values)) => {
+                let values = values.map(Vector3::from).collect();
+                target.translation = Some(keyframes(times, values, interpolation).map_err(parse_error)?);
+            },
+            Some(ReadOutputs::Rotations(values)) => {
+                let values = values.into_f32().map(|q| Quaternion::new(q[3], q[0], q[1], q[2])).collect();
+                target.rotation = Some(keyframes(times, values, interpolation).map_err(parse_error)?);
+            },
+            Some(ReadOutputs::Scales(values)) => {
+                let values = values.map(Vector3::from).collect();
+                target.scale = Some(keyframes(times, values, interpolation).map_err(parse_error)?);
+            },
+            _ => {},
+        }
+    }
+
+    let mut channels: Vec<JointChannel> = channels.into_values().collect();
+    channels.sort_by_key(|channel| channel.joint);
+    let name = animation.name().map(str::to_string).unwrap_or_else(|| format!("animation{}", animation.index()));
+    Ok(AnimationClip::new(&name, channels))
+}
+
+/// interpolation为None时按三次样条读取
+fn keyframes<T: Keyframe>(times: Vec<f32>, values: Vec<T>, interpolation: Option<Interpolation>) -> Result<Keyframes<T>, String> {
+    match interpolation {
+        Some(interpolation) => Keyframes::new(times, values, interpolation),
+        None => Keyframes::from_cubic_spline(times, values),
+    }
+}
+
+struct GltfLoader<'a> {
+    display: &'a Display,
+    buffers: &'a [::gltf::buffer::Data],
+    images: &'a [::gltf::image::Data],
+    /// 材质索引 -> 材质，None为glTF默认材质
+    materials: HashMap<Option<usize>, Rc<PbrMaterial>>,
+    /// 图片索引 -> 颜色贴图
+    srgb_textures: HashMap<usize, MaterialTexture>,
+    /// 图片索引 -> 数据贴图（金属度、粗糙度、法线等不做gamma解码）
+    linear_textures: HashMap<usize, MaterialTexture>,
+    models: Vec<Model>,
+}
+
+impl<'a> GltfLoader<'a> {
+
+    fn new(display: &'a Display, buffers: &'a [::gltf::buffer::Data], images: &'a [::gltf::image::Data]) -> Self {
+        GltfLoader {
+            display,
+            buffers,
+            images,
+            materials: HashMap::new(),
+            srgb_textures: HashMap::new(),
+            linear_textures: HashMap::new(),
+            models: Vec::new(),
+        }
+    }
+
+    fn load_node(&mut self, node: &::gltf::Node, parent: Matrix4<f32>) -> Result<(), LoadError> {
+        let transform = parent * Matrix4::from(node.transform().matrix());
+        if let Some(mesh) = node.mesh() {
+            self.load_mesh(&mesh, transform)?;
+        }
+        for child in node.children() {
+            self.load_node(&child, transform)?;
+        }
+        Ok(())
+    }
+
+    fn load_mesh(&mut self, gltf_mesh: &::gltf::Mesh, transform: Matrix4<f32>) -> Result<(), LoadError> {
+        for primitive in gltf_mesh.primitives() {
+            let mesh = match self.read_primitive(&primitive, transform) {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            let mut model = Model::from_mesh(self.display, &mesh, None)?;
+            let targets = self.read_morph_targets(&primitive, transform, mesh.vertices.len());
+            if !targets.is_empty() {
+                model = model.with_morph_targets(self.display, &mesh, targets, MorphMode::Cpu)?;
+                if let (Some(morph), Some(weights)) = (model.morph.as_mut(), gltf_mesh.weights()) {
+                    for (target, weight) in morph.weights.iter_mut().zip(weights.iter()) {
+                        *target = *weight;
+                    }
+                }
+                model.update_morph();
+            }
+            model.pbr_material = Some(self.load_material(&primitive.material())?);
+            self.models.push(model);
+        }
+        Ok(())
+    }
+
+    /// 读取图元的形变目标，偏移与顶点一样应用节点变换（不含平移）
+    fn read_morph_targets(&self, primitive: &::gltf::Primitive, transform: Matrix4<f32>, vertex_count: usize) -> Vec<MorphTarget> {
+        let linear = Matrix3::from_cols(transform.x.truncate(), transform.y.truncate(), transform.z.truncate());
+        let normal_matrix = linear.invert().map(|m| m.transpose()).unwrap_or(linear);
+
+        let buffers = self.buffers;
+        let reader = primitive.reader(move |buffer| Some(&*buffers[buffer.index()]));
+        reader.read_morph_targets().enumerate().map(|(i, (positions, normals, _))| MorphTarget {
+            name: format!("target{}", i),
+            positions: match positions {
+                Some(positions) => positions.map(|p| (linear * Vector3::from(p)).into()).collect(),
+                None => vec![[0.0; 3]; vertex_count],
+            },
+            normals: match normals {
+                Some(normals) => normals.map(|n| (normal_matrix * Vector3::from(n)).into()).collect(),
+                None => Vec::new(),
+            },
+        }).collect()
+    }
+
+    /// 读取图元的位置、法向量、贴图坐标与索引，没有位置属性时返回None
+    fn read_primitive(&self, primitive: &::gltf::Primitive, transform: Matrix4<f32>) -> Option<MeshData<Vertex>> {
+        // 法向量需要使用模型矩阵左上角3x3的逆转置矩阵变换
+        let linear = Matrix3::from_cols(transform.x.truncate(), transform.y.truncate(), transform.z.truncate());
+        let normal_matrix = linear.invert().map(|m| m.transpose()).unwrap_or(linear);
+        // 镜像变换会翻转三角形绕序
+        let flip_winding = linear.determinant() < 0.0;
+
+        let buffers = self.buffers;
+        let reader = primitive.reader(move |buffer| Some(&*buffers[buffer.index()]));
+
+        let positions: Vec<[f32; 3]> = reader.read_positions()?.map(|p| {
+            let p = transform * Vector4::new(p[0], p[1], p[2], 1.0);
+            [p.x, p.y, p.z]
+        }).collect();
+        let tex_coords: Vec<[f32; 2]> = match reader.read_tex_coords(0) {
+            Some(tex_coords) => tex_coords.into_f32().collect(),
+            None => vec![[0.0, 0.0]; positions.len()],
+        };
+        let mut indices: Vec<u32> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+
+        let primitive_type = match primitive.mode() {
+            ::gltf::mesh::Mode::Points => PrimitiveType::Points,
+            ::gltf::mesh::Mode::Lines => PrimitiveType::LinesList,
+            ::gltf::mesh::Mode::LineLoop => PrimitiveType::LineLoop,
+            ::gltf::mesh::Mode::LineStrip => PrimitiveType::LineStrip,
+            ::gltf::mesh::Mode::Triangles => PrimitiveType::TrianglesList,
+            ::gltf::mesh::Mode::TriangleStrip => PrimitiveType::TriangleStrip,
+            ::gltf::mesh::Mode::TriangleFan => PrimitiveType::TriangleFan,
+        };
+        if flip_winding && primitive_type == PrimitiveType::TrianglesList {
+            for triangle in indices.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+
+        let normals: Vec<[f32; 3]> = match reader.read_normals() {
+            Some(normals) => normals.map(|n| {
+                let n = normal_matrix * Vector3::from(n);
+                if n.magnitude2() > 0.0 { n.normalize().into() } else { n.into() }
+            }).collect(),
+            None if primitive_type == PrimitiveType::TrianglesList => smooth_vertex_normals(&positions, &indices),
+            None => vec![[0.0, 0.0, 0.0]; positions.len()],
+        };
+
+        let vertex_data: Vec<Vertex> = positions.iter().zip(normals.iter()).zip(tex_coords.iter())
+            .map(|((position, normal), texture)| Vertex { position: *position, normal: *normal, texture: *texture })
+            .collect();
+
+        Some(MeshData::new(vertex_data, indices, primitive_type))
+    }
+
+    fn load_material(&mut self, material: &::gltf::Material) -> Result<Rc<PbrMaterial>, LoadError> {
+        if let Some(cached) = self.materials.get(&material.index()) {
+            return Ok(Rc::clone(cached));
+        }
+
+        let pbr = material.pbr_metallic_roughness();
+        let metallic_roughness_map = match pbr.metallic_roughness_texture() {
+            Some(info) => Some(self.linear_texture(info.texture().source().index())?),
+            None => None,
+        };
+        let result = Rc::new(PbrMaterial {
+            name: material.name().unwrap_or_default().to_string(),
+            albedo: pbr.base_color_factor(),
+            metallic: pbr.metallic_factor(),
+            roughness: pbr.roughness_factor(),
+            ao: material.occlusion_texture().map(|t| t.strength()).unwrap_or(1.0),
+            emissive: material.emissive_factor(),
+            albedo_map: match pbr.base_color_texture() {
+                Some(info) => Some(self.srgb_texture(info.texture().source().index())?),
+                None => None,
+            },
+            // glTF的金属度和粗糙度在同一张贴图的B、G通道
+            metallic_map: metallic_roughness_map.clone(),
+            roughness_map: metallic_roughness_map,
+            ao_map: match material.occlusion_texture() {
+                Some(occlusion) => Some(self.linear_texture(occlusion.texture().source().index())?),
+                None => None,
+            },
+            emissive_map: match material.emissive_texture() {
+                Some(info) => Some(self.srgb_texture(info.texture().source().index())?),
+                None => None,
+            },
+            normal_map: match material.normal_texture() {
+                Some(normal) => Some(self.linear_texture(normal.texture().source().index())?),
+                None => None,
+            },
+            sampler: material_sampler(material),
+        });
+        self.materials.insert(material.index(), Rc::clone(&result));
+        Ok(result)
+    }
+
+    fn srgb_texture(&mut self, image: usize) -> Result<MaterialTexture, LoadError> {
+        if let Some(texture) = self.srgb_textures.get(&image) {
+            return Ok(texture.clone());
+        }
+        let texture = MaterialTexture::Srgb(Rc::new(SrgbTexture2d::new(self.display, to_raw_image(&self.images[image]))?));
+        self.srgb_textures.insert(image, texture.clone());
+        Ok(texture)
+    }
+
+    fn linear_texture(&mut self, image: usize) -> Result<MaterialTexture, LoadError> {
+        if let Some(texture) = self.linear_textures.get(&image) {
+            return Ok(texture.clone());
+        }
+        let texture = MaterialTexture::Linear(Rc::new(Texture2d::new(self.display, to_raw_image(&self.images[image]))?));
+        self.linear_textures.insert(image, texture.clone());
+        Ok(texture)
+    }
+}
+
+/**
+ * PbrMaterial的所有贴图共用一个采样描述，取基础色贴图的采样器，
+ * 没有基础色贴图时依次取金属度-粗糙度、法线、遮蔽、自发光贴图的采样器
+ */
+fn material_sampler(material: &::gltf::Material) -> TextureSampler {
+    let pbr = material.pbr_metallic_roughness();
+    let texture = pbr.base_color_texture().map(|info| info.texture())
+        .or_else(|| pbr.metallic_roughness_texture().map(|info| info.texture()))
+        .or_else(|| material.normal_texture().map(|normal| normal.texture()))
+        .or_else(|| material.occlusion_texture().map(|occlusion| occlusion.texture()))
+        .or_else(|| material.emissive_texture().map(|info| info.texture()));
+    match texture {
+        Some(texture) => texture_sampler(&texture.sampler()),
+        None => TextureSampler::default(),
+    }
+}
+
+/// 将glTF采样器转换为TextureSampler，未指定的过滤方式使用默认值
+fn texture_sampler(sampler: &::gltf::texture::Sampler) -> TextureSampler {
+    use ::gltf::texture::{WrappingMode, MinFilter, MagFilter};
+
+    let wrap = |mode: WrappingMode| match mode {
+        WrappingMode::ClampToEdge => SamplerWrapFunction::Clamp,
+        WrappingMode::MirroredRepeat => SamplerWrapFunction::Mirror,
+        WrappingMode::Repeat => SamplerWrapFunction::Repeat,
+    };
+    let default = TextureSampler::default();
+    let min_filter = match sampler.min_filter() {
+        Some(MinFilter::Nearest) => MinifySamplerFilter::Nearest,
+        Some(MinFilter::Linear) => MinifySamplerFilter::Linear,
+        Some(MinFilter::NearestMipmapNearest) => MinifySamplerFilter::NearestMipmapNearest,
+        Some(MinFilter::LinearMipmapNearest) => MinifySamplerFilter::LinearMipmapNearest,
+        Some(MinFilter::NearestMipmapLinear) => MinifySamplerFilter::NearestMipmapLinear,
+        Some(MinFilter::LinearMipmapLinear) => MinifySamplerFilter::LinearMipmapLinear,
+        None => default.min_filter,
+    };
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => MagnifySamplerFilter::Nearest,
+        Some(MagFilter::Linear) => MagnifySamplerFilter::Linear,
+        None => default.mag_filter,
+    };
+    default.with_wrap(wrap(sampler.wrap_s()), wrap(sampler.wrap_t())).with_filters(min_filter, mag_filter)
+}
+
+/// 将glTF解码后的图片统一转换为RGBA8
+fn to_raw_image(data: &::gltf::image::Data) -> RawImage2d<'static, u8> {
+    use ::gltf::image::Format;
+
+    let pixels = &data.pixels;
+    let rgba: Vec<u8> = match data.format {
+        Format::R8 => pixels.iter().flat_map(|r| [*r, *r, *r, 255]).collect(),
+        Format::R8G8 => pixels.chunks_exact(2).flat_map(|p| [p[0], p[1], 0, 255]).collect(),
+        Format::R8G8B8 => pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        Format::R8G8B8A8 => pixels.clone(),
+        Format::B8G8R8 => pixels.chunks_exact(3).flat_map(|p| [p[2], p[1], p[0], 255]).collect(),
+        Format::B8G8R8A8 => pixels.chunks_exact(4).flat_map(|p| [p[2], p[1], p[0], p[3]]).collect(),
+        // 16位通道为小端序，取高字节
+        Format::R16 => pixels.chunks_exact(2).flat_map(|p| [p[1], p[1], p[1], 255]).collect(),
+        Format::R16G16 => pixels.chunks_exact(4).flat_map(|p| [p[1], p[3], 0, 255]).collect(),
+        Format::R16G16B16 => pixels.chunks_exact(6).flat_map(|p| [p[1], p[3], p[5], 255]).collect(),
+        Format::R16G16B16A16 => pixels.chunks_exact(8).flat_map(|p| [p[1], p[3], p[5], p[7]]).collect(),
+    };
+    RawImage2d::from_raw_rgba(rgba, (data.width, data.height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = r#"{
+        "asset": { "version": "2.0" },
+        "samplers": [ { "wrapS": 33071, "wrapT": 33648, "minFilter": 9728, "magFilter": 9728 } ],
+        "images": [ { "uri": "albedo.png" }, { "uri": "normal.png" } ],
+        "textures": [ { "source": 0, "sampler": 0 }, { "source": 1 } ],
+        "materials": [
+            { "pbrMetallicRoughness": { "baseColorTexture": { "index": 0 } }, "normalTexture": { "index": 1 } },
+            { "normalTexture": { "index": 1 } },
+            { }
+        ]
+    }"#;
+
+    #[test]
+    fn maps_texture_sampler() {
+        let gltf = ::gltf::Gltf::from_slice(DOCUMENT.as_bytes()).unwrap();
+        let materials: Vec<::gltf::Material> = gltf.materials().collect();
+
+        let sampler = material_sampler(&materials[0]);
+        assert_eq!(sampler.wrap_s, SamplerWrapFunction::Clamp);
+        assert_eq!(sampler.wrap_t, SamplerWrapFunction::Mirror);
+        assert_eq!(sampler.min_filter, MinifySamplerFilter::Nearest);
+        assert_eq!(sampler.mag_filter, MagnifySamplerFilter::Nearest);
+
+        // 没有指定采样器的贴图按glTF默认值重复采样，过滤方式使用TextureSampler默认值
+        let default = TextureSampler::default();
+        for material in &materials[1..] {
+            let sampler = material_sampler(material);
+            assert_eq!(sampler.wrap_s, SamplerWrapFunction::Repeat);
+            assert_eq!(sampler.wrap_t, SamplerWrapFunction::Repeat);
+            assert_eq!(sampler.min_filter, default.min_filter);
+            assert_eq!(sampler.mag_filter, default.mag_filter);
+        }
+    }
+}
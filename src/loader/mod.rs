@@ -0,0 +1,5 @@
+//! 除Wavefront OBJ以外的模型格式加载
+
+pub mod gltf;
+pub mod ply;
+pub mod stl;
@@ -0,0 +1,387 @@
+use std::{fs, str::SplitAsciiWhitespace};
+
+use glium::{Display, index::PrimitiveType};
+
+use crate::{Vertex, Model, mesh::MeshData, error::LoadError, objectsv2::RawVertexPNC, geometry::{triangulate::triangulate, normals::smooth_vertex_normals}};
+
+/**
+ * 加载PLY模型（ASCII、二进制小端或大端），返回一个绘制单位
+ * 带顶点颜色时顶点类型为`RawVertexPNC`，否则为`Vertex`；缺少法向量时生成平滑法向量
+ * 加载失败时直接panic，需要处理错误时使用`try_load_ply_as_models`
+ */
+pub fn load_ply_as_models(display: &Display, path: &str) -> Vec<Model> {
+    try_load_ply_as_models(display, path).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_load_ply_as_models(display: &Display, path: &str) -> Result<Vec<Model>, LoadError> {
+    let model = match load_ply_as_mesh(path)? {
+        PlyMesh::Plain(mesh) => Model::from_mesh(display, &mesh, None)?,
+        PlyMesh::Colored(mesh) => Model::from_mesh(display, &mesh, None)?,
+    };
+    Ok(vec![model])
+}
+
+/**
+ * PLY模型的CPU端解析结果
+ */
+pub enum PlyMesh {
+    /// 没有顶点颜色
+    Plain(MeshData<Vertex>),
+    /// 带顶点颜色，颜色已归一化到0-1
+    Colored(MeshData<RawVertexPNC>),
+}
+
+/**
+ * 解析PLY模型为CPU端网格数据，多边形面会被三角化
+ */
+pub fn load_ply_as_mesh(path: &str) -> Result<PlyMesh, LoadError> {
+    println!("加载PLY模型: {}", path);
+    let bytes = fs::read(path).map_err(|err| LoadError::io(path, err))?;
+    parse_ply(&bytes).map_err(|message| LoadError::parse(path, message))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+
+    fn parse(name: &str) -> Result<Self, String> {
+        Ok(match name {
+            "char" | "int8" => ScalarType::I8,
+            "uchar" | "uint8" => ScalarType::U8,
+            "short" | "int16" => ScalarType::I16,
+            "ushort" | "uint16" => ScalarType::U16,
+            "int" | "int32" => ScalarType::I32,
+            "uint" | "uint32" => ScalarType::U32,
+            "float" | "float32" => ScalarType::F32,
+            "double" | "float64" => ScalarType::F64,
+            _ => return Err(format!("未知的属性类型{}", name)),
+        })
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+
+    /// 整数颜色分量的最大值，浮点颜色已经在0-1范围内
+    fn color_scale(&self) -> f64 {
+        match self {
+            ScalarType::U8 | ScalarType::I8 => 255.0,
+            ScalarType::U16 | ScalarType::I16 => 65535.0,
+            ScalarType::U32 | ScalarType::I32 => u32::MAX as f64,
+            ScalarType::F32 | ScalarType::F64 => 1.0,
+        }
+    }
+}
+
+enum Property {
+    Scalar { name: String, ty: ScalarType },
+    List { name: String, count_ty: ScalarType, item_ty: ScalarType },
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// 按属性类型依次读取数据体中的值
+enum BodyReader<'a> {
+    Ascii(SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], offset: usize, big_endian: bool },
+}
+
+impl<'a> BodyReader<'a> {
+
+    fn read(&mut self, ty: ScalarType) -> Result<f64, String> {
+        match self {
+            BodyReader::Ascii(tokens) => {
+                let token = tokens.next().ok_or("数据不完整")?;
+                token.parse::<f64>().map_err(|_| format!("无法解析数值{}", token))
+            },
+            BodyReader::Binary { data, offset, big_endian } => {
+                let size = ty.size();
+                let bytes = data.get(*offset..*offset + size).ok_or("数据不完整")?;
+                *offset += size;
+                let mut buf = [0u8; 8];
+                buf[..size].copy_from_slice(bytes);
+                if *big_endian {
+                    buf[..size].reverse();
+                }
+                Ok(match ty {
+                    ScalarType::I8 => buf[0] as i8 as f64,
+                    ScalarType::U8 => buf[0] as f64,
+                    ScalarType::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    ScalarType::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    ScalarType::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    ScalarType::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    ScalarType::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    ScalarType::F64 => f64::from_le_bytes(buf),
+                })
+            },
+        }
+    }
+}
+
+fn parse_header(bytes: &[u8]) -> Result<(Format, Vec<Element>, usize), String> {
+    let end = find_subslice(bytes, b"end_header").ok_or("缺少end_header")?;
+    // 数据体从end_header所在行的换行符之后开始
+    let body_start = bytes[end..].iter().position(|b| *b == b'\n').map(|i| end + i + 1).ok_or("缺少end_header")?;
+    let header = std::str::from_utf8(&bytes[..end]).map_err(|_| "文件头不是有效的文本")?;
+
+    let mut lines = header.lines();
+    if lines.next().map(str::trim) != Some("ply") {
+        return Err("不是PLY文件".to_string());
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    for line in lines {
+        let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+        match tokens.as_slice() {
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(format!("不支持的格式{}", name)),
+                });
+            },
+            ["element", name, count] => {
+                let count = count.parse().map_err(|_| format!("元素{}的数量无效", name))?;
+                elements.push(Element { name: name.to_string(), count, properties: Vec::new() });
+            },
+            ["property", "list", count_ty, item_ty, name] => {
+                let element = elements.last_mut().ok_or("属性必须位于元素之后")?;
+                element.properties.push(Property::List {
+                    name: name.to_string(),
+                    count_ty: ScalarType::parse(count_ty)?,
+                    item_ty: ScalarType::parse(item_ty)?,
+                });
+            },
+            ["property", ty, name] => {
+                let element = elements.last_mut().ok_or("属性必须位于元素之后")?;
+                element.properties.push(Property::Scalar { name: name.to_string(), ty: ScalarType::parse(ty)? });
+            },
+            ["comment", ..] | ["obj_info", ..] | [] => {},
+            _ => return Err(format!("无法识别的文件头: {}", line)),
+        }
+    }
+    Ok((format.ok_or("缺少format声明")?, elements, body_start))
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn parse_ply(bytes: &[u8]) -> Result<PlyMesh, String> {
+    let (format, elements, body_start) = parse_header(bytes)?;
+    let body = &bytes[body_start..];
+    let mut reader = match format {
+        Format::Ascii => BodyReader::Ascii(std::str::from_utf8(body).map_err(|_| "数据体不是有效的文本")?.split_ascii_whitespace()),
+        Format::BinaryLittleEndian => BodyReader::Binary { data: body, offset: 0, big_endian: false },
+        Format::BinaryBigEndian => BodyReader::Binary { data: body, offset: 0, big_endian: true },
+    };
+
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut colors: Vec<[f32; 3]> = Vec::new();
+    let mut tex_coords: Vec<[f32; 2]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+
+    for element in elements.iter() {
+        // 属性名称 -> 在一行标量值中的位置
+        let scalar = |names: &[&str]| element.properties.iter()
+            .filter(|p| matches!(p, Property::Scalar { .. }))
+            .position(|p| matches!(p, Property::Scalar { name, .. } if names.contains(&name.as_str())));
+        let position_slots = [scalar(&["x"]), scalar(&["y"]), scalar(&["z"])];
+        let normal_slots = [scalar(&["nx"]), scalar(&["ny"]), scalar(&["nz"])];
+        let color_slots = [scalar(&["red", "diffuse_red", "r"]), scalar(&["green", "diffuse_green", "g"]), scalar(&["blue", "diffuse_blue", "b"])];
+        let tex_slots = [scalar(&["u", "s", "texture_u", "texture_s"]), scalar(&["v", "t", "texture_v", "texture_t"])];
+        let scalar_types: Vec<ScalarType> = element.properties.iter().filter_map(|p| match p {
+            Property::Scalar { ty, .. } => Some(*ty),
+            _ => None,
+        }).collect();
+
+        let is_vertex = element.name == "vertex";
+        let is_face = element.name == "face";
+        let mut values = Vec::with_capacity(scalar_types.len());
+        let mut face = Vec::new();
+        for _ in 0..element.count {
+            values.clear();
+            face.clear();
+            for property in element.properties.iter() {
+                match property {
+                    Property::Scalar { ty, .. } => values.push(reader.read(*ty)?),
+                    Property::List { name, count_ty, item_ty } => {
+                        let count = reader.read(*count_ty)? as usize;
+                        let is_indices = is_face && (name == "vertex_indices" || name == "vertex_index");
+                        for _ in 0..count {
+                            let value = reader.read(*item_ty)?;
+                            if is_indices {
+                                face.push(value as u32);
+                            }
+                        }
+                    },
+                }
+            }
+
+            if is_vertex {
+                let get = |slot: Option<usize>| slot.map(|i| values[i]);
+                match position_slots.map(get) {
+                    [Some(x), Some(y), Some(z)] => positions.push([x as f32, y as f32, z as f32]),
+                    _ => return Err("顶点缺少x、y、z属性".to_string()),
+                }
+                if let [Some(x), Some(y), Some(z)] = normal_slots.map(get) {
+                    normals.push([x as f32, y as f32, z as f32]);
+                }
+                if let [Some(r), Some(g), Some(b)] = color_slots.map(get) {
+                    let scale = color_slots[0].map(|i| scalar_types[i].color_scale()).unwrap_or(1.0);
+                    colors.push([(r / scale) as f32, (g / scale) as f32, (b / scale) as f32]);
+                }
+                if let [Some(u), Some(v)] = tex_slots.map(get) {
+                    tex_coords.push([u as f32, v as f32]);
+                }
+            } else if is_face && face.len() >= 3 {
+                if let Some(index) = face.iter().find(|index| **index as usize >= positions.len()) {
+                    return Err(format!("面引用了不存在的顶点{}", index));
+                }
+                let polygon: Vec<[f32; 3]> = face.iter().map(|index| positions[*index as usize]).collect();
+                for triangle in triangulate(&polygon) {
+                    indices.extend(triangle.map(|i| face[i]));
+                }
+            }
+        }
+    }
+
+    if positions.is_empty() {
+        return Err("没有顶点数据".to_string());
+    }
+    let primitive_type = if indices.is_empty() {
+        // 只有顶点的点云
+        indices = (0..positions.len() as u32).collect();
+        PrimitiveType::Points
+    } else {
+        PrimitiveType::TrianglesList
+    };
+    if normals.len() != positions.len() {
+        normals = if primitive_type == PrimitiveType::TrianglesList {
+            smooth_vertex_normals(&positions, &indices)
+        } else {
+            vec![[0.0, 0.0, 0.0]; positions.len()]
+        };
+    }
+
+    if colors.len() == positions.len() {
+        let vertices = positions.iter().zip(normals.iter()).zip(colors.iter())
+            .map(|((position, normal), color)| RawVertexPNC { position: *position, normal: *normal, color: *color })
+            .collect();
+        Ok(PlyMesh::Colored(MeshData::new(vertices, indices, primitive_type)))
+    } else {
+        let vertices = positions.iter().zip(normals.iter()).enumerate()
+            .map(|(i, (position, normal))| Vertex {
+                position: *position,
+                normal: *normal,
+                texture: tex_coords.get(i).copied().unwrap_or([0.0, 0.0]),
+            })
+            .collect();
+        Ok(PlyMesh::Plain(MeshData::new(vertices, indices, primitive_type)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINARY_HEADER: &str = "ply\nformat {} 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n";
+
+    fn binary_triangle(format: &str, big_endian: bool) -> Vec<u8> {
+        let mut bytes = BINARY_HEADER.replace("{}", format).into_bytes();
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            bytes.extend(if big_endian { value.to_be_bytes() } else { value.to_le_bytes() });
+        }
+        bytes.push(3);
+        for index in [0i32, 1, 2] {
+            bytes.extend(if big_endian { index.to_be_bytes() } else { index.to_le_bytes() });
+        }
+        bytes
+    }
+
+    fn plain(mesh: PlyMesh) -> MeshData<Vertex> {
+        match mesh {
+            PlyMesh::Plain(mesh) => mesh,
+            PlyMesh::Colored(_) => panic!("不应带顶点颜色"),
+        }
+    }
+
+    #[test]
+    fn ascii_with_vertex_colors() {
+        let source = "ply\nformat ascii 1.0\ncomment 测试\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+            property uchar red\nproperty uchar green\nproperty uchar blue\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n\
+            0 0 0 255 0 0\n1 0 0 0 255 0\n0 1 0 0 0 255\n3 0 1 2\n";
+        let mesh = match parse_ply(source.as_bytes()).unwrap() {
+            PlyMesh::Colored(mesh) => mesh,
+            PlyMesh::Plain(_) => panic!("应带顶点颜色"),
+        };
+        assert_eq!(mesh.primitive_type, PrimitiveType::TrianglesList);
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        assert_eq!(mesh.vertices[0].color, [1.0, 0.0, 0.0]);
+        assert_eq!(mesh.vertices[2].color, [0.0, 0.0, 1.0]);
+        assert_eq!(mesh.vertices[1].position, [1.0, 0.0, 0.0]);
+        // 缺少法向量时生成平滑法向量
+        assert_eq!(mesh.vertices[0].normal, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn binary_little_and_big_endian() {
+        for (format, big_endian) in [("binary_little_endian", false), ("binary_big_endian", true)] {
+            let mesh = plain(parse_ply(&binary_triangle(format, big_endian)).unwrap());
+            assert_eq!(mesh.indices, vec![0, 1, 2], "{}", format);
+            assert_eq!(mesh.vertices[1].position, [1.0, 0.0, 0.0], "{}", format);
+            assert_eq!(mesh.vertices[2].position, [0.0, 1.0, 0.0], "{}", format);
+        }
+    }
+
+    #[test]
+    fn truncated_binary_is_an_error() {
+        let bytes = binary_triangle("binary_little_endian", false);
+        assert!(parse_ply(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn polygon_faces_are_fan_triangulated() {
+        let source = "ply\nformat ascii 1.0\nelement vertex 5\nproperty float x\nproperty float y\nproperty float z\n\
+            element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+            0 0 0\n2 0 0\n3 1 0\n1 2 0\n-1 1 0\n5 0 1 2 3 4\n";
+        let mesh = plain(parse_ply(source.as_bytes()).unwrap());
+        assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3, 0, 3, 4]);
+    }
+
+    #[test]
+    fn face_with_missing_vertex_is_an_error() {
+        let source = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+            element face 1\nproperty list uchar int vertex_indices\nend_header\n0 0 0\n1 0 0\n0 1 0\n3 0 1 7\n";
+        assert!(parse_ply(source.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use std::{fs, collections::HashMap, str::SplitAsciiWhitespace};
+
+use cgmath::{Vector3, InnerSpace};
+use glium::{Display, index::PrimitiveType};
+
+use crate::{Vertex, Model, mesh::MeshData, error::LoadError};
+
+/**
+ * 加载STL模型（ASCII或二进制），返回一个绘制单位
+ * STL没有纹理坐标，每个面使用文件中的面法向量，法向量为零时根据三角形重新计算
+ * 加载失败时直接panic，需要处理错误时使用`try_load_stl_as_models`
+ */
+pub fn load_stl_as_models(display: &Display, path: &str) -> Vec<Model> {
+    try_load_stl_as_models(display, path).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_load_stl_as_models(display: &Display, path: &str) -> Result<Vec<Model>, LoadError> {
+    let mesh = load_stl_as_mesh(path)?;
+    Ok(vec![Model::from_mesh(display, &mesh, None)?])
+}
+
+/**
+ * 解析STL模型为CPU端网格数据，位置与法向量都相同的顶点会被合并
+ */
+pub fn load_stl_as_mesh(path: &str) -> Result<MeshData<Vertex>, LoadError> {
+    println!("加载STL模型: {}", path);
+    let bytes = fs::read(path).map_err(|err| LoadError::io(path, err))?;
+    let facets = if is_binary(&bytes) {
+        parse_binary(&bytes)
+    } else {
+        parse_ascii(&bytes)
+    }.map_err(|message| LoadError::parse(path, message))?;
+
+    let mut cache: HashMap<[u32; 6], u32> = HashMap::new();
+    let mut vertices = Vec::new();
+    let mut indices = Vec::with_capacity(facets.len() * 3);
+    for facet in facets.iter() {
+        let normal = facet_normal(facet);
+        for position in facet.vertices.iter() {
+            let key = [position[0], position[1], position[2], normal[0], normal[1], normal[2]].map(f32::to_bits);
+            let index = *cache.entry(key).or_insert_with(|| {
+                vertices.push(Vertex { position: *position, normal, texture: [0.0, 0.0] });
+                vertices.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+    }
+    Ok(MeshData::new(vertices, indices, PrimitiveType::TrianglesList))
+}
+
+struct Facet {
+    normal: [f32; 3],
+    vertices: [[f32; 3]; 3],
+}
+
+/// 文件中的法向量为零或无效时根据顶点绕序重新计算
+fn facet_normal(facet: &Facet) -> [f32; 3] {
+    let normal = Vector3::from(facet.normal);
+    if normal.magnitude2() > 0.0 && normal.magnitude2().is_finite() {
+        return normal.normalize().into();
+    }
+    let a = Vector3::from(facet.vertices[0]);
+    let b = Vector3::from(facet.vertices[1]);
+    let c = Vector3::from(facet.vertices[2]);
+    let normal = (b - a).cross(c - a);
+    if normal.magnitude2() > 0.0 { normal.normalize().into() } else { normal.into() }
+}
+
+/**
+ * 二进制STL的文件头也可能以"solid"开头，因此按文件长度判断：
+ * 80字节文件头 + 4字节三角形数量 + 每个三角形50字节
+ */
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() < 84 {
+        return false;
+    }
+    let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+    if count.checked_mul(50).and_then(|size| size.checked_add(84)) == Some(bytes.len()) {
+        return true;
+    }
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(bytes.len());
+    !bytes[start..].starts_with(b"solid")
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<Vec<Facet>, String> {
+    let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+    let data = &bytes[84..];
+    if data.len() < count.saturating_mul(50) {
+        return Err(format!("声明了{}个三角形，但数据不完整", count));
+    }
+    let read = |offset: usize| -> [f32; 3] {
+        let f = |i: usize| f32::from_le_bytes([data[offset + i * 4], data[offset + i * 4 + 1], data[offset + i * 4 + 2], data[offset + i * 4 + 3]]);
+        [f(0), f(1), f(2)]
+    };
+    Ok((0..count).map(|i| {
+        let offset = i * 50;
+        Facet {
+            normal: read(offset),
+            vertices: [read(offset + 12), read(offset + 24), read(offset + 36)],
+        }
+    }).collect())
+}
+
+fn parse_ascii(bytes: &[u8]) -> Result<Vec<Facet>, String> {
+    let text = std::str::from_utf8(bytes).map_err(|_| "不是有效的ASCII STL文件")?;
+    let mut tokens = text.split_ascii_whitespace();
+
+    // solid名称、outer loop等关键字在查找facet与vertex时被跳过
+    let mut facets = Vec::new();
+    while let Some(token) = tokens.next() {
+        if token != "facet" {
+            continue;
+        }
+        // facet normal nx ny nz
+        tokens.next();
+        let normal = read_vector(&mut tokens)?;
+        let mut vertices = Vec::with_capacity(3);
+        loop {
+            match tokens.next() {
+                Some("vertex") => vertices.push(read_vector(&mut tokens)?),
+                Some("endfacet") => break,
+                Some(_) => {},
+                None => return Err("facet没有结束".to_string()),
+            }
+        }
+        // 超过3个顶点的面按扇形拆分
+        for i in 1..vertices.len().saturating_sub(1) {
+            facets.push(Facet { normal, vertices: [vertices[0], vertices[i], vertices[i + 1]] });
+        }
+    }
+    if facets.is_empty() {
+        return Err("没有三角形数据".to_string());
+    }
+    Ok(facets)
+}
+
+fn read_vector(tokens: &mut SplitAsciiWhitespace) -> Result<[f32; 3], String> {
+    let mut vector = [0.0; 3];
+    for value in vector.iter_mut() {
+        let token = tokens.next().ok_or("数据不完整")?;
+        *value = token.parse().map_err(|_| format!("无法解析数值{}", token))?;
+    }
+    Ok(vector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary_stl(header: &[u8], facets: &[([f32; 3], [[f32; 3]; 3])]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 80];
+        bytes[..header.len()].copy_from_slice(header);
+        bytes.extend((facets.len() as u32).to_le_bytes());
+        for (normal, vertices) in facets {
+            for value in normal.iter().chain(vertices.iter().flatten()) {
+                bytes.extend(value.to_le_bytes());
+            }
+            bytes.extend([0u8; 2]);
+        }
+        bytes
+    }
+
+    const TRIANGLE: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+
+    #[test]
+    fn binary_with_solid_header() {
+        let bytes = binary_stl(b"solid exported by some cad tool", &[([0.0, 0.0, 1.0], TRIANGLE), ([0.0, 0.0, 1.0], TRIANGLE)]);
+        assert!(is_binary(&bytes));
+        let facets = parse_binary(&bytes).unwrap();
+        assert_eq!(facets.len(), 2);
+        assert_eq!(facets[1].vertices, TRIANGLE);
+        assert_eq!(facets[0].normal, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn truncated_binary_is_an_error() {
+        let bytes = binary_stl(b"", &[([0.0, 0.0, 1.0], TRIANGLE)]);
+        assert!(parse_binary(&bytes[..bytes.len() - 10]).is_err());
+    }
+
+    #[test]
+    fn ascii_facets() {
+        let source = "solid test\n\
+            facet normal 0 0 1\n outer loop\n  vertex 0 0 0\n  vertex 1 0 0\n  vertex 0 1 0\n endloop\nendfacet\n\
+            facet normal 0 0 0\n outer loop\n  vertex 0 0 0\n  vertex 0 1 0\n  vertex 1 0 0\n endloop\nendfacet\n\
+            endsolid test\n";
+        assert!(!is_binary(source.as_bytes()));
+        let facets = parse_ascii(source.as_bytes()).unwrap();
+        assert_eq!(facets.len(), 2);
+        assert_eq!(facets[0].vertices, TRIANGLE);
+        assert_eq!(facets[1].normal, [0.0, 0.0, 0.0]);
+
+        assert!(parse_ascii(b"solid empty\nendsolid empty\n").is_err());
+    }
+
+    #[test]
+    fn zero_normal_is_recomputed() {
+        let facet = Facet { normal: [0.0, 0.0, 0.0], vertices: TRIANGLE };
+        assert_eq!(facet_normal(&facet), [0.0, 0.0, 1.0]);
+        let facet = Facet { normal: [0.0, 0.0, 0.0], vertices: [TRIANGLE[0], TRIANGLE[2], TRIANGLE[1]] };
+        assert_eq!(facet_normal(&facet), [0.0, 0.0, -1.0]);
+        // 文件中的法向量不为零时只做归一化
+        let facet = Facet { normal: [0.0, 2.0, 0.0], vertices: TRIANGLE };
+        assert_eq!(facet_normal(&facet), [0.0, 1.0, 0.0]);
+    }
+}
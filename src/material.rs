@@ -1,10 +1,51 @@
-use std::{rc::Rc, collections::HashMap, sync::Arc, io::Cursor, fs};
+use std::{rc::Rc, collections::HashMap, sync::Arc, io::Cursor, fs, time::Instant};
 
-use futures::{executor::{block_on, ThreadPool, ThreadPoolBuilder}};
-use glium::{texture::{CompressedSrgbTexture2d, DepthCubemap, DepthTexture2d, SrgbCubemap, CubeLayer, RawImage2d}, Display, framebuffer::{SimpleFrameBuffer}, Texture2d, Surface, BlitTarget, uniforms::MagnifySamplerFilter};
+use cgmath::{Matrix4, Point3, Vector3};
+use futures::{executor::{block_on, ThreadPool, ThreadPoolBuilder}, task::SpawnExt};
+use glium::{texture::{CompressedSrgbTexture2d, SrgbTexture2d, DepthCubemap, SrgbCubemap, CubeLayer, RawImage2d, UncompressedFloatFormat, MipmapsOption}, Display, framebuffer::{SimpleFrameBuffer}, Texture2d, Surface, BlitTarget, uniforms::{MagnifySamplerFilter, SamplerBehavior, UniformValue, AsUniformValue}};
+use image::{DynamicImage, ImageFormat};
 use obj::Mtl;
 
-use crate::{uniforms::{DynamicUniforms, add_to_uniforms}, utils};
+use crate::{uniforms::{DynamicUniforms, add_to_uniforms, add_value_to_uniforms, with_sampler, TextureSampler, SampledTexture}, utils, error::LoadError};
+
+/**
+ * 贴图在材质中的用途，决定上传时的颜色空间
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextureRole {
+    /// 颜色贴图（漫反射、环境光、自发光），按sRGB上传，采样时解码为线性颜色
+    Color,
+    /// 数据贴图（凹凸/法线、位移、镜面强度、高光指数、不透明度、反射强度），按线性上传，采样时不做gamma解码
+    Data,
+}
+
+/**
+ * 材质贴图，颜色贴图与数据贴图使用不同的纹理类型
+ */
+#[derive(Clone)]
+pub enum MaterialTexture {
+    Srgb(Rc<SrgbTexture2d>),
+    Linear(Rc<Texture2d>),
+}
+
+impl MaterialTexture {
+
+    pub fn role(&self) -> TextureRole {
+        match self {
+            MaterialTexture::Srgb(_) => TextureRole::Color,
+            MaterialTexture::Linear(_) => TextureRole::Data,
+        }
+    }
+}
+
+impl AsUniformValue for MaterialTexture {
+    fn as_uniform_value(&self) -> UniformValue<'_> {
+        match self {
+            MaterialTexture::Srgb(texture) => texture.as_ref().as_uniform_value(),
+            MaterialTexture::Linear(texture) => texture.as_ref().as_uniform_value(),
+        }
+    }
+}
 
 /**
  * 材质
@@ -32,21 +73,23 @@ pub struct Material {
     /// The optical density, i.e. index of refraction, specified by `Ni`
     pub optical_density: Option<f32>,
     /// The ambient color map, specified by `map_Ka`
-    pub ambient_map: Option<Rc<CompressedSrgbTexture2d>>,
+    pub ambient_map: Option<MaterialTexture>,
     /// The diffuse color map, specified by `map_Kd`
-    pub diffuse_map: Option<Rc<CompressedSrgbTexture2d>>,
+    pub diffuse_map: Option<MaterialTexture>,
     /// The specular color map, specified by `map_Ks`
-    pub specular_map: Option<Rc<CompressedSrgbTexture2d>>,
+    pub specular_map: Option<MaterialTexture>,
     /// The emissive color map, specified by `map_Ke`
-    pub emissive_map: Option<Rc<CompressedSrgbTexture2d>>,
+    pub emissive_map: Option<MaterialTexture>,
     /// The specular highlight component
-    pub specular_hightlight_map: Option<Rc<CompressedSrgbTexture2d>>,
+    pub specular_hightlight_map: Option<MaterialTexture>,
     /// The dissolve map, specified by `map_d`
-    pub dissolve_map: Option<Rc<CompressedSrgbTexture2d>>,
+    pub dissolve_map: Option<MaterialTexture>,
     /// The bump map (normal map), specified by `bump`
-    pub bump_map: Option<Rc<CompressedSrgbTexture2d>>,
+    pub bump_map: Option<MaterialTexture>,
     /// spherical reflection map
-    pub reflect_map: Option<Rc<CompressedSrgbTexture2d>>,
+    pub reflect_map: Option<MaterialTexture>,
+    /// 所有贴图共用的采样描述
+    pub sampler: TextureSampler,
 }
 
 impl Material {
@@ -58,66 +101,222 @@ impl Material {
     //     }
     // }
 
+    /**
+     * 绑定材质的全部参数，与`src/shaders/material.glsl`中的材质块对应
+     * 贴图是否存在通过`has_*`标记告知着色器，没有贴图时使用对应颜色；MTL中没有指定的颜色与数值使用`DEFAULT_*`
+     */
     pub fn add_to_uniforms<'a: 'b, 'b>(&'a self, key: &str, uniforms: &'b mut DynamicUniforms<'a>) {
-        if let Some(diffuse) = &self.diffuse_map {
-            add_to_uniforms(key, ".diffuse", diffuse.as_ref(), uniforms);
-        }
-        
-        if let Some(specular) = &self.specular_map {
-            add_to_uniforms(key, ".specular", specular.as_ref(), uniforms);
-        }
+        let diffuse = self.diffuse.unwrap_or(DEFAULT_DIFFUSE);
+        // 没有指定环境光颜色时与漫反射颜色相同
+        add_value_to_uniforms(key, ".ambient_color", UniformValue::Vec3(self.ambient.unwrap_or(diffuse)), uniforms);
+        add_value_to_uniforms(key, ".diffuse_color", UniformValue::Vec3(diffuse), uniforms);
+        add_value_to_uniforms(key, ".specular_color", UniformValue::Vec3(self.specular.unwrap_or(DEFAULT_SPECULAR)), uniforms);
+        add_value_to_uniforms(key, ".emissive_color", UniformValue::Vec3(self.emissive.unwrap_or([0.0, 0.0, 0.0])), uniforms);
+        add_value_to_uniforms(key, ".transmission_filter", UniformValue::Vec3(self.transmission_filter.unwrap_or([1.0, 1.0, 1.0])), uniforms);
+        let shininess = self.shininess.or(self.specular_exponent).unwrap_or(DEFAULT_SHININESS);
+        add_value_to_uniforms(key, ".shininess", UniformValue::Float(shininess), uniforms);
+        add_value_to_uniforms(key, ".dissolve", UniformValue::Float(self.dissolve.unwrap_or(1.0)), uniforms);
+        add_value_to_uniforms(key, ".optical_density", UniformValue::Float(self.optical_density.unwrap_or(1.0)), uniforms);
+        add_value_to_uniforms(key, ".illumination_model", UniformValue::SignedInt(self.illumination_model.unwrap_or(2)), uniforms);
+
+        // 漫反射、镜面反射、反射贴图沿用原有的uniform名称
+        let sampler = self.sampler.behavior();
+        add_map_to_uniforms(key, ".diffuse", ".has_diffuse", &self.diffuse_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".specular", ".has_specular", &self.specular_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".reflection", ".has_reflection", &self.reflect_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".ambient_map", ".has_ambient_map", &self.ambient_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".emissive_map", ".has_emissive_map", &self.emissive_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".specular_highlight_map", ".has_specular_highlight_map", &self.specular_hightlight_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".dissolve_map", ".has_dissolve_map", &self.dissolve_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".bump_map", ".has_bump_map", &self.bump_map, sampler, uniforms);
+    }
+}
 
-        if let Some(shininess) = &self.shininess {
-            add_to_uniforms(key, ".shininess", shininess, uniforms);
-        }
+/// MTL没有指定`Kd`时的漫反射颜色
+pub const DEFAULT_DIFFUSE: [f32; 3] = [0.8, 0.8, 0.8];
+/// MTL没有指定`Ks`时的镜面反射颜色
+pub const DEFAULT_SPECULAR: [f32; 3] = [0.5, 0.5, 0.5];
+/// MTL没有指定`Ns`时的反光度
+pub const DEFAULT_SHININESS: f32 = 32.0;
+
+/// 绑定一张可选贴图及其`has_*`标记，贴图通过给定的采样器采样
+fn add_map_to_uniforms<'a: 'b, 'b>(key: &str, map_suffix: &str, flag_suffix: &str, map: &'a Option<MaterialTexture>, sampler: SamplerBehavior, uniforms: &'b mut DynamicUniforms<'a>) {
+    add_value_to_uniforms(key, flag_suffix, UniformValue::Bool(map.is_some()), uniforms);
+    if let Some(map) = map {
+        add_value_to_uniforms(key, map_suffix, with_sampler(map.as_uniform_value(), sampler), uniforms);
+    }
+}
+
+
+/**
+ * PBR金属度-粗糙度材质
+ * 因子与贴图同时存在时，着色器中取两者的乘积
+ */
+pub struct PbrMaterial {
+    pub name: String,
+    /// 基础色（反照率）因子，RGBA
+    pub albedo: [f32; 4],
+    /// 金属度因子
+    pub metallic: f32,
+    /// 粗糙度因子
+    pub roughness: f32,
+    /// 环境光遮蔽强度
+    pub ao: f32,
+    /// 自发光颜色因子
+    pub emissive: [f32; 3],
+    /// 基础色贴图
+    pub albedo_map: Option<MaterialTexture>,
+    /// 金属度贴图，采样B通道（与glTF的metallicRoughness贴图一致，单通道灰度图同样适用）
+    pub metallic_map: Option<MaterialTexture>,
+    /// 粗糙度贴图，采样G通道
+    pub roughness_map: Option<MaterialTexture>,
+    /// 环境光遮蔽贴图，采样R通道
+    pub ao_map: Option<MaterialTexture>,
+    /// 自发光贴图
+    pub emissive_map: Option<MaterialTexture>,
+    /// 切线空间法线贴图
+    pub normal_map: Option<MaterialTexture>,
+    /// 所有贴图共用的采样描述
+    pub sampler: TextureSampler,
+}
 
-        if let Some(reflection) = &self.reflect_map {
-            // reflection.sampled()
-            add_to_uniforms(key, ".reflection", reflection.as_ref(), uniforms);
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        PbrMaterial {
+            name: String::new(),
+            albedo: [1.0, 1.0, 1.0, 1.0],
+            metallic: 0.0,
+            roughness: 1.0,
+            ao: 1.0,
+            emissive: [0.0, 0.0, 0.0],
+            albedo_map: None,
+            metallic_map: None,
+            roughness_map: None,
+            ao_map: None,
+            emissive_map: None,
+            normal_map: None,
+            sampler: TextureSampler::default(),
         }
     }
 }
 
+impl PbrMaterial {
+
+    /// 绑定材质参数，贴图是否存在通过`has_*`标记告知着色器
+    pub fn add_to_uniforms<'a: 'b, 'b>(&'a self, key: &str, uniforms: &'b mut DynamicUniforms<'a>) {
+        add_to_uniforms(key, ".albedo", &self.albedo, uniforms);
+        add_to_uniforms(key, ".metallic", &self.metallic, uniforms);
+        add_to_uniforms(key, ".roughness", &self.roughness, uniforms);
+        add_to_uniforms(key, ".ao", &self.ao, uniforms);
+        add_to_uniforms(key, ".emissive", &self.emissive, uniforms);
+
+        let sampler = self.sampler.behavior();
+        add_map_to_uniforms(key, ".albedo_map", ".has_albedo_map", &self.albedo_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".metallic_map", ".has_metallic_map", &self.metallic_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".roughness_map", ".has_roughness_map", &self.roughness_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".ao_map", ".has_ao_map", &self.ao_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".emissive_map", ".has_emissive_map", &self.emissive_map, sampler, uniforms);
+        add_map_to_uniforms(key, ".normal_map", ".has_normal_map", &self.normal_map, sampler, uniforms);
+    }
+}
+
 
 
 pub struct MaterialLoader {
     cache: HashMap<String, Rc<Material>>,
-    map_cache: HashMap<String, Rc<CompressedSrgbTexture2d>>,
+    /// 材质名 -> 由同一MTL材质转换的PBR材质
+    pbr_cache: HashMap<String, Rc<PbrMaterial>>,
+    /// 材质名 -> obj库不解析的PBR扩展参数
+    pbr_extensions: HashMap<String, MtlPbrExtension>,
+    /// (图片路径, 用途) -> 贴图，同一张图片以不同用途引用时分别上传
+    map_cache: HashMap<(String, TextureRole), MaterialTexture>,
     pool: ThreadPool,
+    /// 贴图上传与材质绑定使用的采样描述
+    sampler: TextureSampler,
 }
 
 impl MaterialLoader {
     
     pub fn new() -> MaterialLoader {
-        MaterialLoader { cache: HashMap::new(), map_cache: HashMap::new(), pool: ThreadPoolBuilder::new().name_prefix("material-loader").create().unwrap() }
+        MaterialLoader { cache: HashMap::new(), pbr_cache: HashMap::new(), pbr_extensions: HashMap::new(), map_cache: HashMap::new(), pool: ThreadPoolBuilder::new().name_prefix("material-loader").create().unwrap(), sampler: TextureSampler::default() }
+    }
+
+    /// 指定之后加载的材质使用的采样描述
+    pub fn with_sampler(mut self, sampler: TextureSampler) -> MaterialLoader {
+        self.sampler = sampler;
+        self
     }
 
-    pub fn parse_and_load(&mut self, mtls: &Vec<Mtl>, basepath: &str, display: &Display) {
+    /**
+     * 解析并加载材质库中的所有材质
+     * 颜色贴图加载失败时使用占位纹理，数据贴图加载失败时不绑定，并打印警告
+     */
+    pub fn parse_and_load(&mut self, mtls: &Vec<Mtl>, basepath: &str, display: &Display) -> Result<(), LoadError> {
         let mut texture_paths = HashMap::new();
         for mtl in mtls.iter() {
             println!("材质文件{}中有{}个材质需要加载...", mtl.filename, mtl.materials.len());
+            // obj库不认识PBR扩展指令（加载时已被`strip_mtl_extensions`过滤），需要重新读取MTL原文解析
+            let mtl_path = format!("{}{}", basepath, mtl.filename);
+            match fs::read_to_string(&mtl_path) {
+                Ok(source) => self.pbr_extensions.extend(parse_mtl_pbr_extensions(&source)),
+                Err(err) => println!("警告: 读取材质文件{}失败，忽略PBR扩展参数: {}", mtl_path, err),
+            }
             // let mut futures = Vec::with_capacity(mtl.materials.len());
             for material in mtl.materials.iter() {
                 let paths = Self::parse_valid_texture_paths(basepath, material);
                 for path in paths.into_iter() {
                     texture_paths.insert(path, 0);
                 }
+                if let Some(extension) = self.pbr_extensions.get(&material.name) {
+                    for path in extension.texture_paths(basepath).into_iter() {
+                        texture_paths.insert(path, 0);
+                    }
+                }
             }
         }
-        // 加载图片, 异步加载
-        let fts = {
-            let mut futures = Vec::with_capacity(texture_paths.len());
-            for path in texture_paths.keys().into_iter() {
-                futures.push(load_texture_async(path.clone(), display));
+        // 在线程池中读取并解码图片，OpenGL上传只能在持有上下文的当前线程进行
+        let decode_start = Instant::now();
+        let handles = {
+            let mut handles = Vec::with_capacity(texture_paths.len());
+            for (path, role) in texture_paths.into_keys() {
+                let handle = self.pool.spawn_with_handle(async move {
+                    let image = decode_image(&path, role);
+                    (path, role, image)
+                }).expect("材质加载线程池已关闭");
+                handles.push(handle);
             }
-            futures
+            handles
         };
         println!("开始加载材质图片");
-        let textures = block_on(futures::future::join_all(fts));
-        println!("材质图片加载完成");
-        for texture in textures.into_iter() {
-            self.map_cache.insert(texture.0, Rc::new(texture.1));
+        let images = block_on(futures::future::join_all(handles));
+        println!("材质图片解码完成: {}张，耗时{:?}", images.len(), decode_start.elapsed());
+
+        let upload_start = Instant::now();
+        let mipmaps = self.sampler.mipmaps_option();
+        let mut placeholder: Option<MaterialTexture> = None;
+        for (path, role, image) in images.into_iter() {
+            let texture = match image.and_then(|image| upload_decoded_image(display, image, mipmaps)) {
+                Ok(texture) => texture,
+                // 数据贴图（法线、高光等）没有通用的中性值，不绑定贴图，着色器按`has_*`标记退回到常量
+                Err(err) if role == TextureRole::Data => {
+                    println!("警告: {}，不绑定该贴图", err);
+                    continue;
+                },
+                Err(err) => {
+                    println!("警告: {}，使用占位纹理代替", err);
+                    match &placeholder {
+                        Some(placeholder) => placeholder.clone(),
+                        None => {
+                            let texture = placeholder_texture(display)?;
+                            placeholder = Some(texture.clone());
+                            texture
+                        },
+                    }
+                },
+            };
+            self.map_cache.insert((path, role), texture);
         }
+        println!("材质图片上传完成，耗时{:?}", upload_start.elapsed());
 
         for mtl in mtls.iter() {
             // let mut futures = Vec::with_capacity(mtl.materials.len());
@@ -125,6 +324,7 @@ impl MaterialLoader {
                 self.load(material, basepath);
             }
         }
+        Ok(())
     }
 
     fn load(&mut self, obj_material: &Arc<obj::Material>, basepath: &str) {
@@ -133,6 +333,9 @@ impl MaterialLoader {
             return;
         }
 
+        // obj库不解析`map_Ke`，由PBR扩展指令一并读取
+        let emissive_map = obj_material.map_ke.clone()
+            .or_else(|| self.pbr_extensions.get(&name).and_then(|extension| extension.emissive_map.clone()));
         let material = Material {
             ambient: obj_material.ka,
             diffuse: obj_material.kd,
@@ -144,30 +347,66 @@ impl MaterialLoader {
             dissolve: obj_material.d,
             specular_exponent: None,
             optical_density: obj_material.ni,
-            ambient_map: self.find_2d_texture(&obj_material.map_ka, basepath),
-            diffuse_map: self.find_2d_texture(&obj_material.map_kd, basepath),
-            specular_map: self.find_2d_texture(&obj_material.map_ks, basepath),
-            emissive_map: self.find_2d_texture(&obj_material.map_ke, basepath),
-            specular_hightlight_map: self.find_2d_texture(&obj_material.map_ns, basepath),
-            dissolve_map: self.find_2d_texture(&obj_material.map_d, basepath),
-            bump_map: self.find_2d_texture(&obj_material.map_bump, basepath),
-            reflect_map: self.find_2d_texture(&obj_material.map_refl, basepath),
+            ambient_map: self.find_2d_texture(&obj_material.map_ka, TextureRole::Color, basepath),
+            diffuse_map: self.find_2d_texture(&obj_material.map_kd, TextureRole::Color, basepath),
+            specular_map: self.find_2d_texture(&obj_material.map_ks, TextureRole::Data, basepath),
+            emissive_map: self.find_2d_texture(&emissive_map, TextureRole::Color, basepath),
+            specular_hightlight_map: self.find_2d_texture(&obj_material.map_ns, TextureRole::Data, basepath),
+            dissolve_map: self.find_2d_texture(&obj_material.map_d, TextureRole::Data, basepath),
+            bump_map: self.find_2d_texture(&obj_material.map_bump, TextureRole::Data, basepath),
+            reflect_map: self.find_2d_texture(&obj_material.map_refl, TextureRole::Data, basepath),
+            sampler: self.sampler,
         };
-        self.cache.insert(name, Rc::new(material));
+        let pbr_material = self.load_pbr(obj_material, &material, basepath);
+        self.cache.insert(name.clone(), Rc::new(material));
+        self.pbr_cache.insert(name, Rc::new(pbr_material));
     }
 
-    fn find_2d_texture(&self, file: &Option<String>, basepath: &str) -> Option<Rc<CompressedSrgbTexture2d>> {
+    /**
+     * 将MTL材质转换为金属度-粗糙度材质，使用`Pr`、`Pm`、`map_Pr`、`map_Pm`、`Ke`、`map_Ke`、`norm`
+     * 没有`Pr`时由`Ns`按Blinn-Phong与GGX的近似关系换算粗糙度；没有`norm`时使用`bump`作为法线贴图
+     */
+    fn load_pbr(&self, obj_material: &Arc<obj::Material>, material: &Material, basepath: &str) -> PbrMaterial {
+        let extension = self.pbr_extensions.get(&obj_material.name).cloned().unwrap_or_default();
+        let albedo_map = material.diffuse_map.clone();
+        // 与glTF一致，基础色因子与贴图相乘，有贴图而没有`Kd`时不改变贴图颜色
+        let diffuse = obj_material.kd.unwrap_or(if albedo_map.is_some() { [1.0, 1.0, 1.0] } else { DEFAULT_DIFFUSE });
+        let roughness = extension.roughness
+            .or_else(|| obj_material.ns.map(|ns| (2.0 / (ns.max(0.0) + 2.0)).sqrt()))
+            .unwrap_or(1.0);
+        PbrMaterial {
+            name: obj_material.name.clone(),
+            albedo: [diffuse[0], diffuse[1], diffuse[2], obj_material.d.unwrap_or(1.0)],
+            metallic: extension.metallic.unwrap_or(0.0),
+            roughness: roughness.clamp(0.0, 1.0),
+            ao: 1.0,
+            emissive: obj_material.ke.unwrap_or([0.0, 0.0, 0.0]),
+            albedo_map,
+            metallic_map: self.find_2d_texture(&extension.metallic_map, TextureRole::Data, basepath),
+            roughness_map: self.find_2d_texture(&extension.roughness_map, TextureRole::Data, basepath),
+            ao_map: None,
+            emissive_map: material.emissive_map.clone(),
+            normal_map: self.find_2d_texture(&extension.normal_map, TextureRole::Data, basepath).or_else(|| material.bump_map.clone()),
+            sampler: self.sampler,
+        }
+    }
+
+    fn find_2d_texture(&self, file: &Option<String>, role: TextureRole, basepath: &str) -> Option<MaterialTexture> {
         if let Some(file) = file {
             let mut path = basepath.to_string();
             path.push_str(file.as_str());
-            
-            if let Some(texture) = self.map_cache.get(&path) {
-                return Some(Rc::clone(texture));
+
+            if let Some(texture) = self.map_cache.get(&(path, role)) {
+                return Some(texture.clone());
             }
         }
         None
     }
 
+    pub fn find_pbr_in_cache(&self, name: &str) -> Option<Rc<PbrMaterial>> {
+        self.pbr_cache.get(name).cloned()
+    }
+
     pub fn find_in_cache(&self, name: String) -> Option<Rc<Material>> {
         match self.cache.get(&name) {
             Some(material) => {
@@ -177,52 +416,286 @@ impl MaterialLoader {
         }
     }
 
-    fn parse_valid_texture_paths(basepath: &str, material: &Arc<obj::Material>) -> Vec<String> {
+    /**
+     * 材质引用的所有贴图路径及其用途
+     * 镜面贴图在本项目中作为镜面强度使用，反射贴图作为反射强度遮罩使用，因此都按数据贴图处理
+     */
+    fn parse_valid_texture_paths(basepath: &str, material: &Arc<obj::Material>) -> Vec<(String, TextureRole)> {
         let mut result = Vec::new();
-        Self::add_to_vec(&mut result, &material.map_ka, basepath);
-        Self::add_to_vec(&mut result, &material.map_kd, basepath);
-        Self::add_to_vec(&mut result, &material.map_ks, basepath);
-        Self::add_to_vec(&mut result, &material.map_ke, basepath);
-        Self::add_to_vec(&mut result, &material.map_d, basepath);
-        Self::add_to_vec(&mut result, &material.map_bump, basepath);
+        Self::add_to_vec(&mut result, &material.map_ka, TextureRole::Color, basepath);
+        Self::add_to_vec(&mut result, &material.map_kd, TextureRole::Color, basepath);
+        Self::add_to_vec(&mut result, &material.map_ks, TextureRole::Data, basepath);
+        Self::add_to_vec(&mut result, &material.map_ke, TextureRole::Color, basepath);
+        Self::add_to_vec(&mut result, &material.map_ns, TextureRole::Data, basepath);
+        Self::add_to_vec(&mut result, &material.map_d, TextureRole::Data, basepath);
+        Self::add_to_vec(&mut result, &material.map_bump, TextureRole::Data, basepath);
+        Self::add_to_vec(&mut result, &material.map_refl, TextureRole::Data, basepath);
 
         result
     }
 
-    fn add_to_vec(list: &mut Vec<String>, value: &Option<String>, basepath: &str) {
+    fn add_to_vec(list: &mut Vec<(String, TextureRole)>, value: &Option<String>, role: TextureRole, basepath: &str) {
         if let Some(file) = value {
             let mut str = basepath.to_string();
             str.push_str(file.as_str());
-            list.push(str);
+            list.push((str, role));
         }
     }
     
 }
 
-pub async fn load_texture_async(path: String, display: &Display) -> (String, CompressedSrgbTexture2d) {
-    load_texture(path, display)
+/**
+ * MTL的PBR扩展指令（obj库不解析的部分），`Ke`由obj库解析
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MtlPbrExtension {
+    /// 粗糙度，specified by `Pr`
+    pub roughness: Option<f32>,
+    /// 金属度，specified by `Pm`
+    pub metallic: Option<f32>,
+    /// 粗糙度贴图，specified by `map_Pr`
+    pub roughness_map: Option<String>,
+    /// 金属度贴图，specified by `map_Pm`
+    pub metallic_map: Option<String>,
+    /// 切线空间法线贴图，specified by `norm`
+    pub normal_map: Option<String>,
+    /// 自发光贴图，specified by `map_Ke`
+    pub emissive_map: Option<String>,
 }
 
-pub fn load_texture(path: String, display: &Display) -> (String, CompressedSrgbTexture2d) {
-    let temp = path.clone();
-    let image = load_image(&temp);
-    (path, glium::texture::CompressedSrgbTexture2d::new(display, image).unwrap())
+impl MtlPbrExtension {
+
+    /// 扩展贴图的完整路径，都按数据贴图加载
+    fn texture_paths(&self, basepath: &str) -> Vec<(String, TextureRole)> {
+        let mut result = Vec::new();
+        MaterialLoader::add_to_vec(&mut result, &self.roughness_map, TextureRole::Data, basepath);
+        MaterialLoader::add_to_vec(&mut result, &self.metallic_map, TextureRole::Data, basepath);
+        MaterialLoader::add_to_vec(&mut result, &self.normal_map, TextureRole::Data, basepath);
+        MaterialLoader::add_to_vec(&mut result, &self.emissive_map, TextureRole::Color, basepath);
+        result
+    }
 }
 
-pub fn load_image(path: &str) -> RawImage2d<u8> {
-    println!("加载材质图片: {}", path);
-    let format = {
-        if path.ends_with(".png") {
-            image::ImageFormat::Png
-        } else if path.ends_with(".jpg") {
-            image::ImageFormat::Jpeg
-        } else {
-            panic!("不支持的图片格式");
+/**
+ * 从MTL原文中解析PBR扩展指令，返回 材质名 -> 扩展参数，只包含至少有一个扩展指令的材质
+ * 贴图指令中的选项（如`-bm 1.0`）被忽略，其余部分作为文件名，文件名可以包含空格；无法解析的数值被忽略
+ */
+pub fn parse_mtl_pbr_extensions(source: &str) -> HashMap<String, MtlPbrExtension> {
+    let mut result = HashMap::new();
+    let mut current: Option<(String, MtlPbrExtension)> = None;
+    for line in source.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        if keyword == "newmtl" {
+            if let Some((name, extension)) = current.take() {
+                if extension != MtlPbrExtension::default() {
+                    result.insert(name, extension);
+                }
+            }
+            current = Some((words.collect::<Vec<_>>().join(" "), MtlPbrExtension::default()));
+            continue;
+        }
+        let extension = match current.as_mut() {
+            Some((_, extension)) => extension,
+            None => continue,
+        };
+        match keyword {
+            "Pr" => extension.roughness = words.next().and_then(|value| value.parse().ok()),
+            "Pm" => extension.metallic = words.next().and_then(|value| value.parse().ok()),
+            "map_Pr" => extension.roughness_map = map_file_name(words),
+            "map_Pm" => extension.metallic_map = map_file_name(words),
+            "norm" | "map_norm" => extension.normal_map = map_file_name(words),
+            "map_Ke" => extension.emissive_map = map_file_name(words),
+            _ => {},
         }
+    }
+    if let Some((name, extension)) = current {
+        if extension != MtlPbrExtension::default() {
+            result.insert(name, extension);
+        }
+    }
+    result
+}
+
+/// obj库能够解析的MTL指令，其余指令会使obj库报错
+const OBJ_MTL_KEYWORDS: [&str; 22] = [
+    "newmtl", "Ka", "Kd", "Ks", "Ke", "Ns", "Ni", "Km", "d", "Tr", "Tf", "illum",
+    "map_Ka", "map_Kd", "map_Ks", "map_Ns", "map_d", "map_refl", "refl", "map_bump", "map_Bump", "bump",
+];
+
+/**
+ * 删除obj库无法解析的指令（PBR扩展、`map_Ke`等），得到可以交给`Mtl::reload`的MTL原文
+ * 被删除的PBR扩展指令由`parse_mtl_pbr_extensions`从原文中读取
+ */
+pub fn strip_mtl_extensions(source: &str) -> String {
+    source.lines()
+        .filter(|line| match line.split_whitespace().next() {
+            Some(keyword) => keyword.starts_with('#') || OBJ_MTL_KEYWORDS.contains(&keyword),
+            None => true,
+        })
+        .fold(String::with_capacity(source.len()), |mut result, line| {
+            result.push_str(line);
+            result.push('\n');
+            result
+        })
+}
+
+/**
+ * 跳过贴图指令开头的选项，剩余部分按单个空格拼接为文件名
+ * `-o`、`-s`、`-t`后跟1到3个数值，`-mm`后跟2个数值，其余选项后跟1个参数
+ */
+fn map_file_name<'a>(words: impl Iterator<Item = &'a str>) -> Option<String> {
+    let mut words = words.peekable();
+    while let Some(option) = words.next_if(|word| word.starts_with('-')) {
+        let (min, max) = match option {
+            "-o" | "-s" | "-t" => (1, 3),
+            "-mm" => (2, 2),
+            _ => (1, 1),
+        };
+        for count in 0..max {
+            if count >= min && words.peek().is_none_or(|word| word.parse::<f32>().is_err()) {
+                break;
+            }
+            words.next();
+        }
+    }
+    let name = words.collect::<Vec<_>>().join(" ");
+    if name.is_empty() { None } else { Some(name) }
+}
+
+pub fn load_texture(path: String, display: &Display) -> (String, CompressedSrgbTexture2d) {
+    let texture = try_load_texture(&path, display).unwrap_or_else(|err| panic!("{}", err));
+    (path, texture)
+}
+
+pub fn try_load_texture(path: &str, display: &Display) -> Result<CompressedSrgbTexture2d, LoadError> {
+    let image = try_load_image(path)?;
+    Ok(glium::texture::CompressedSrgbTexture2d::new(display, image)?)
+}
+
+/**
+ * 加载数据贴图（法线、位移、镜面强度等），按线性颜色空间上传，采样时不做gamma解码
+ */
+pub fn load_linear_texture(path: String, display: &Display) -> (String, Texture2d) {
+    let texture = try_load_linear_texture(&path, display).unwrap_or_else(|err| panic!("{}", err));
+    (path, texture)
+}
+
+pub fn try_load_linear_texture(path: &str, display: &Display) -> Result<Texture2d, LoadError> {
+    upload_linear_image(display, decode_image(path, TextureRole::Data)?, MipmapsOption::AutoGeneratedMipmaps)
+}
+
+/**
+ * 按采样描述加载颜色贴图，是否生成mipmap由采样描述决定，绑定时使用其中的环绕、过滤与各向异性设置
+ */
+pub fn load_texture_with(path: String, display: &Display, sampler: &TextureSampler) -> (String, SampledTexture<SrgbTexture2d>) {
+    let texture = try_load_texture_with(&path, display, sampler).unwrap_or_else(|err| panic!("{}", err));
+    (path, texture)
+}
+
+pub fn try_load_texture_with(path: &str, display: &Display, sampler: &TextureSampler) -> Result<SampledTexture<SrgbTexture2d>, LoadError> {
+    let image = try_load_image(path)?;
+    let texture = SrgbTexture2d::with_mipmaps(display, image, sampler.mipmaps_option())?;
+    Ok(SampledTexture { texture, sampler: *sampler })
+}
+
+/// 按贴图用途选择颜色空间加载
+pub fn try_load_material_texture(path: &str, role: TextureRole, display: &Display) -> Result<MaterialTexture, LoadError> {
+    upload_decoded_image(display, decode_image(path, role)?, MipmapsOption::AutoGeneratedMipmaps)
+}
+
+/**
+ * 解码后等待上传的像素数据，不依赖OpenGL上下文，可以在工作线程中生成后传回上下文线程
+ */
+pub enum DecodedImage {
+    /// 颜色贴图，按sRGB上传
+    Srgb(RawImage2d<'static, u8>),
+    /// 8位数据贴图
+    Linear8(RawImage2d<'static, u8>),
+    /// 16位数据贴图（16位PNG等）
+    Linear16(RawImage2d<'static, u16>),
+    /// 浮点图片（HDR、EXR），本身就是线性颜色，即使作为颜色贴图也按浮点纹理上传
+    Float(RawImage2d<'static, f32>),
+}
+
+/**
+ * 读取并解码图片，转换为上传需要的RGBA像素格式
+ * 颜色贴图只支持8位sRGB格式，因此16位颜色贴图会被转换为8位
+ */
+pub fn decode_image(path: &str, role: TextureRole) -> Result<DecodedImage, LoadError> {
+    let image = try_load_dynamic_image(path)?;
+    let dimensions = (image.width(), image.height());
+    Ok(match image {
+        DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+            DecodedImage::Float(RawImage2d::from_raw_rgba(image.into_rgba32f().into_raw(), dimensions))
+        },
+        DynamicImage::ImageLuma16(_) | DynamicImage::ImageLumaA16(_) | DynamicImage::ImageRgb16(_) | DynamicImage::ImageRgba16(_) if role == TextureRole::Data => {
+            DecodedImage::Linear16(RawImage2d::from_raw_rgba(image.into_rgba16().into_raw(), dimensions))
+        },
+        _ => {
+            let raw = RawImage2d::from_raw_rgba(image.into_rgba8().into_raw(), dimensions);
+            match role {
+                TextureRole::Color => DecodedImage::Srgb(raw),
+                TextureRole::Data => DecodedImage::Linear8(raw),
+            }
+        },
+    })
+}
+
+/// 上传解码后的图片，必须在持有OpenGL上下文的线程调用
+pub fn upload_decoded_image(display: &Display, image: DecodedImage, mipmaps: MipmapsOption) -> Result<MaterialTexture, LoadError> {
+    match image {
+        DecodedImage::Srgb(raw) => Ok(MaterialTexture::Srgb(Rc::new(SrgbTexture2d::with_mipmaps(display, raw, mipmaps)?))),
+        linear => Ok(MaterialTexture::Linear(Rc::new(upload_linear_image(display, linear, mipmaps)?))),
+    }
+}
+
+/**
+ * 按线性颜色空间上传图片，纹理格式跟随图片位深：
+ * 8位为RGBA8，16位为RGBA16，浮点为RGBA32F
+ */
+pub fn upload_linear_image(display: &Display, image: DecodedImage, mipmaps: MipmapsOption) -> Result<Texture2d, LoadError> {
+    let texture = match image {
+        DecodedImage::Srgb(raw) | DecodedImage::Linear8(raw) => Texture2d::with_mipmaps(display, raw, mipmaps)?,
+        DecodedImage::Linear16(raw) => Texture2d::with_format(display, raw, UncompressedFloatFormat::U16U16U16U16, mipmaps)?,
+        DecodedImage::Float(raw) => Texture2d::with_format(display, raw, UncompressedFloatFormat::F32F32F32F32, mipmaps)?,
     };
-    let image = image::load(Cursor::new(fs::read(path).unwrap()), format).unwrap().to_rgba8();
+    Ok(texture)
+}
+
+/// 贴图缺失时使用的1x1白色占位纹理
+pub fn placeholder_texture(display: &Display) -> Result<MaterialTexture, LoadError> {
+    let image = RawImage2d::from_raw_rgba(vec![255u8, 255, 255, 255], (1, 1));
+    Ok(MaterialTexture::Srgb(Rc::new(SrgbTexture2d::new(display, image)?)))
+}
+
+pub fn load_image(path: &str) -> RawImage2d<u8> {
+    try_load_image(path).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_load_image(path: &str) -> Result<RawImage2d<'static, u8>, LoadError> {
+    let image = try_load_dynamic_image(path)?.to_rgba8();
     let image_dimensions = image.dimensions();
-    RawImage2d::from_raw_rgba(image.into_raw(), image_dimensions)
+    Ok(RawImage2d::from_raw_rgba(image.into_raw(), image_dimensions))
+}
+
+/**
+ * 解码图片，格式根据文件内容判断（PNG、JPEG、BMP、HDR、EXR等），
+ * TGA等没有文件签名的格式再根据扩展名（不区分大小写）判断
+ * 返回的图片保留原始位深，16位与浮点图片不会被截断为8位
+ */
+pub fn try_load_dynamic_image(path: &str) -> Result<DynamicImage, LoadError> {
+    println!("加载材质图片: {}", path);
+    let bytes = fs::read(path).map_err(|err| LoadError::io(path, err))?;
+    let format = image::guess_format(&bytes)
+        .or_else(|_| ImageFormat::from_path(path))
+        .map_err(|_| LoadError::UnsupportedImageFormat(path.to_string()))?;
+    image::load(Cursor::new(bytes), format)
+        .map_err(|err| LoadError::Image { path: path.to_string(), source: err })
 }
 
 static CUBEMAP_FILES: [(&str, CubeLayer); 6] = [
@@ -234,6 +707,21 @@ static CUBEMAP_FILES: [(&str, CubeLayer); 6] = [
     ("back", CubeLayer::NegativeZ)
 ];
 
+/**
+ * 从eye位置向立方体贴图六个面观察时，各面对应的层与观察矩阵
+ * 顺序为+X、-X、+Y、-Y、+Z、-Z，配合90°视野、宽高比为1的透视投影逐个面渲染
+ */
+pub fn cube_face_views(eye: Point3<f32>) -> [(CubeLayer, Matrix4<f32>); 6] {
+    [
+        (CubeLayer::PositiveX, Matrix4::look_at_rh(eye, eye + Vector3::unit_x(), -Vector3::unit_y())),
+        (CubeLayer::NegativeX, Matrix4::look_at_rh(eye, eye - Vector3::unit_x(), -Vector3::unit_y())),
+        (CubeLayer::PositiveY, Matrix4::look_at_rh(eye, eye + Vector3::unit_y(), Vector3::unit_z())),
+        (CubeLayer::NegativeY, Matrix4::look_at_rh(eye, eye - Vector3::unit_y(), -Vector3::unit_z())),
+        (CubeLayer::PositiveZ, Matrix4::look_at_rh(eye, eye + Vector3::unit_z(), -Vector3::unit_y())),
+        (CubeLayer::NegativeZ, Matrix4::look_at_rh(eye, eye - Vector3::unit_z(), -Vector3::unit_y())),
+    ]
+}
+
 // 加载立方体贴图
 pub fn load_cubemap(dir: &str, suffix: &str, display: &Display, dimensions: i32) -> SrgbCubemap {
     let cube_texture = SrgbCubemap::empty(display, dimensions as u32).unwrap();
@@ -258,15 +746,94 @@ pub fn load_cubemap(dir: &str, suffix: &str, display: &Display, dimensions: i32)
     cube_texture
 }
 
+/**
+ * 创建边长为dimensions的深度立方体贴图，六个面的深度都清空为1.0（最远）
+ * glium不支持分层帧缓冲，需要通过`main_level().image(layer)`将每个面分别作为深度附件渲染，见`shadow::PointShadow`
+ * 创建失败时直接panic，需要处理错误时使用`try_depth_cubemap`
+ */
 pub fn depth_cubemap(display: &Display, dimensions: u32) -> DepthCubemap {
-    let cube = DepthCubemap::empty(display, dimensions).unwrap();
+    try_depth_cubemap(display, dimensions).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_depth_cubemap(display: &Display, dimensions: u32) -> Result<DepthCubemap, LoadError> {
+    let cube = DepthCubemap::empty(display, dimensions)?;
 
     for item in CUBEMAP_FILES.iter() {
-        let texture = DepthTexture2d::empty(display, dimensions, dimensions).unwrap();
-        let framebuffer = SimpleFrameBuffer::depth_only(display, cube.main_level().image(item.1)).unwrap();
+        let mut framebuffer = SimpleFrameBuffer::depth_only(display, cube.main_level().image(item.1))
+            .map_err(|err| LoadError::gpu("帧缓冲", err))?;
+        framebuffer.clear_depth(1.0);
+    }
+
+    Ok(cube)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        
+    #[test]
+    fn pbr_factors() {
+        let source = "newmtl metal\nKd 0.5 0.5 0.5\nKe 1 0.5 0\nPr 0.25\nPm 1\n";
+        let extensions = parse_mtl_pbr_extensions(source);
+        assert_eq!(extensions["metal"], MtlPbrExtension { roughness: Some(0.25), metallic: Some(1.0), ..Default::default() });
+
+        // Ke由obj库解析，两者读取同一份MTL
+        let mut mtl = Mtl::new("test.mtl".to_string());
+        mtl.reload(strip_mtl_extensions(source).as_bytes()).unwrap();
+        assert_eq!(mtl.materials[0].ke, Some([1.0, 0.5, 0.0]));
+    }
+
+    #[test]
+    fn extensions_are_stripped_for_obj_crate() {
+        let source = "newmtl a\nKd 1 1 1\nPr 0.5\nmap_Ke glow.png\nnorm n.png\n# Pm 1\n\nnewmtl b\nPm 1\n";
+        assert!(Mtl::new("test.mtl".to_string()).reload(source.as_bytes()).is_err());
+
+        let stripped = strip_mtl_extensions(source);
+        assert_eq!(stripped, "newmtl a\nKd 1 1 1\n# Pm 1\n\nnewmtl b\n");
+        let mut mtl = Mtl::new("test.mtl".to_string());
+        mtl.reload(stripped.as_bytes()).unwrap();
+        assert_eq!(mtl.materials.len(), 2);
+        assert_eq!(parse_mtl_pbr_extensions(source)["a"].emissive_map.as_deref(), Some("glow.png"));
+    }
+
+    #[test]
+    fn map_options_are_skipped() {
+        let source = "newmtl a\n\
+            map_Pr -clamp on rough.png\n\
+            map_Pm -o 0.5 0.5 -s 2 metal.png\n\
+            norm -bm 1.0 -mm 0 1 normal.png\n";
+        let extension = &parse_mtl_pbr_extensions(source)["a"];
+        assert_eq!(extension.roughness_map.as_deref(), Some("rough.png"));
+        assert_eq!(extension.metallic_map.as_deref(), Some("metal.png"));
+        assert_eq!(extension.normal_map.as_deref(), Some("normal.png"));
+        assert_eq!(extension.roughness, None);
     }
 
-    cube
-}
\ No newline at end of file
+    #[test]
+    fn file_names_with_spaces() {
+        let source = "newmtl a\nmap_norm -bm 0.5 brick wall normal.png\nmap_Pr my roughness.png\n";
+        let extension = &parse_mtl_pbr_extensions(source)["a"];
+        assert_eq!(extension.normal_map.as_deref(), Some("brick wall normal.png"));
+        assert_eq!(extension.roughness_map.as_deref(), Some("my roughness.png"));
+    }
+
+    #[test]
+    fn comments_blank_lines_and_multiple_materials() {
+        let source = "# 导出工具生成\n\
+            \n\
+            newmtl first\n\
+            \tPr 0.5 # 行尾注释\n\
+            \n\
+            # Pm 1.0\n\
+            newmtl plain\n\
+            Kd 1 1 1\n\
+            newmtl second\n\
+            Pm 0.75\n\
+            Pr nan-ish\n";
+        let extensions = parse_mtl_pbr_extensions(source);
+        // 没有扩展指令的材质不出现在结果中
+        assert_eq!(extensions.len(), 2);
+        assert_eq!(extensions["first"], MtlPbrExtension { roughness: Some(0.5), ..Default::default() });
+        assert_eq!(extensions["second"], MtlPbrExtension { metallic: Some(0.75), ..Default::default() });
+    }
+}
@@ -0,0 +1,391 @@
+use glium::{Display, VertexBuffer, IndexBuffer, index::{PrimitiveType, IndexBufferAny}, vertex::VertexBufferAny};
+
+use crate::{Vertex, ColorVertex, create_index_buffer, error::LoadError, geometry::{bounds::{Aabb, BoundingSphere}, tangent::generate_tangents, simplify::simplify, vcache::{self, DEFAULT_CACHE_SIZE}}, objectsv2::{RawVertexP, RawVertexPT, RawVertexPC, RawVertexPNC, RawVertexPNTTB, RawVertexPNTJW}};
+
+/**
+ * 能够提供三维位置的顶点类型，CPU端几何处理（包围盒等）通过它读取顶点位置
+ */
+pub trait VertexPosition {
+    fn position(&self) -> [f32; 3];
+}
+
+impl VertexPosition for Vertex {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+impl VertexPosition for ColorVertex {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+impl VertexPosition for RawVertexP {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+impl VertexPosition for RawVertexPT {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+impl VertexPosition for RawVertexPC {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+impl VertexPosition for RawVertexPNC {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+impl VertexPosition for RawVertexPNTTB {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+impl VertexPosition for RawVertexPNTJW {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+/**
+ * CPU端网格数据
+ * 模型加载与几何体生成只产出该结构，上传到GPU是单独的一步，因此几何处理可以在没有OpenGL上下文的环境中进行和测试
+ */
+#[derive(Clone, Debug)]
+pub struct MeshData<V> {
+    pub vertices: Vec<V>,
+    /// 索引统一使用u32保存，上传时再根据顶点数量选择索引类型
+    pub indices: Vec<u32>,
+    pub primitive_type: PrimitiveType,
+    /// 材质名称，上传时由材质加载器解析为实际材质
+    pub material: Option<String>,
+    /// 来源对象名称，例如OBJ中`o`语句的名称
+    pub name: Option<String>,
+    /// 来源组名称，例如OBJ中`g`语句的名称
+    pub group: Option<String>,
+    /// 按材质合并后各对象在索引数组中的范围，未合并时为空
+    pub ranges: Vec<MeshRange>,
+    /// 局部坐标系下的包围盒
+    pub bounds: Aabb,
+    /// 局部坐标系下的包围球
+    pub sphere: BoundingSphere,
+    /// 简化后的各级LOD索引，与原网格共享顶点数组，第0级（原始索引）不在其中
+    pub lods: Vec<Vec<u32>>,
+}
+
+/**
+ * 合并网格中一个对象的索引范围
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshRange {
+    pub name: String,
+    /// 起始索引位置
+    pub start: usize,
+    /// 索引数量
+    pub count: usize,
+}
+
+impl<V: VertexPosition> MeshData<V> {
+
+    pub fn new(vertices: Vec<V>, indices: Vec<u32>, primitive_type: PrimitiveType) -> Self {
+        let bounds = Aabb::from_points(vertices.iter().map(|v| v.position()));
+        let sphere = BoundingSphere::from_points(vertices.iter().map(|v| v.position()));
+        MeshData { vertices, indices, primitive_type, material: None, name: None, group: None, ranges: Vec::new(), bounds, sphere, lods: Vec::new() }
+    }
+
+    pub fn with_material(mut self, material: Option<String>) -> Self {
+        self.material = material;
+        self
+    }
+
+    pub fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_group(mut self, group: Option<String>) -> Self {
+        self.group = group;
+        self
+    }
+
+    /**
+     * 使用二次误差度量简化生成各级LOD，ratios为每级相对原始三角形数量的比例，例如`[0.5, 0.25, 0.1]`
+     * 只支持三角形列表，其他图元类型不生成LOD
+     */
+    pub fn build_lods(&mut self, ratios: &[f32]) {
+        self.lods.clear();
+        if self.primitive_type != PrimitiveType::TrianglesList {
+            return;
+        }
+        let positions: Vec<[f32; 3]> = self.vertices.iter().map(|v| v.position()).collect();
+        // 每级在上一级基础上继续简化，比例应递减
+        let mut previous = self.indices.clone();
+        for ratio in ratios {
+            let target = (self.indices.len() as f32 * ratio) as usize;
+            let lod = simplify(&positions, &previous, target);
+            previous = lod.clone();
+            self.lods.push(lod);
+        }
+    }
+
+    /// 修改顶点后重新计算包围盒与包围球
+    pub fn recompute_bounds(&mut self) {
+        self.bounds = Aabb::from_points(self.vertices.iter().map(|v| v.position()));
+        self.sphere = BoundingSphere::from_points(self.vertices.iter().map(|v| v.position()));
+    }
+}
+
+/**
+ * 顶点缓存优化前后的平均缓存未命中率
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct CacheReport {
+    pub acmr_before: f32,
+    pub acmr_after: f32,
+}
+
+impl<V: Clone> MeshData<V> {
+
+    /**
+     * 重排三角形提高顶点后变换缓存命中率，再按使用顺序重排顶点提高读取局部性
+     * 有对象子范围时只在各范围内部重排，LOD索引同样重排并随顶点一起重映射，仅支持三角形列表
+     */
+    pub fn optimize_vertex_cache(&mut self) -> CacheReport {
+        let acmr_before = vcache::acmr(&self.indices, DEFAULT_CACHE_SIZE);
+        if self.primitive_type != PrimitiveType::TrianglesList {
+            return CacheReport { acmr_before, acmr_after: acmr_before };
+        }
+
+        let vertex_count = self.vertices.len();
+        if self.ranges.is_empty() {
+            self.indices = vcache::optimize_vertex_cache(&self.indices, vertex_count, DEFAULT_CACHE_SIZE);
+        } else {
+            for range in self.ranges.iter() {
+                let slice = &mut self.indices[range.start..range.start + range.count];
+                let optimized = vcache::optimize_vertex_cache(slice, vertex_count, DEFAULT_CACHE_SIZE);
+                slice.copy_from_slice(&optimized);
+            }
+        }
+        for lod in self.lods.iter_mut() {
+            *lod = vcache::optimize_vertex_cache(lod, vertex_count, DEFAULT_CACHE_SIZE);
+        }
+
+        let remap = vcache::vertex_fetch_remap(&self.indices, vertex_count);
+        // remap是顶点的一个排列（未引用的顶点也被分配了位置），取逆映射按新顺序收集顶点
+        let mut order = vec![0usize; vertex_count];
+        for (old_index, new_index) in remap.iter().enumerate() {
+            order[*new_index as usize] = old_index;
+        }
+        self.vertices = order.iter().map(|old_index| self.vertices[*old_index].clone()).collect();
+        for index in self.indices.iter_mut().chain(self.lods.iter_mut().flatten()) {
+            *index = remap[*index as usize];
+        }
+
+        CacheReport { acmr_before, acmr_after: vcache::acmr(&self.indices, DEFAULT_CACHE_SIZE) }
+    }
+}
+
+impl MeshData<Vertex> {
+
+    /// 计算切线与副切线，转换为可用于法线贴图的网格，仅支持三角形列表
+    pub fn with_tangents(&self) -> MeshData<RawVertexPNTTB> {
+        MeshData {
+            vertices: generate_tangents(&self.vertices, &self.indices),
+            indices: self.indices.clone(),
+            primitive_type: self.primitive_type,
+            material: self.material.clone(),
+            name: self.name.clone(),
+            group: self.group.clone(),
+            ranges: self.ranges.clone(),
+            bounds: self.bounds,
+            sphere: self.sphere,
+            lods: self.lods.clone(),
+        }
+    }
+}
+
+/**
+ * 将材质相同的三角形列表网格合并为一个，减少绘制调用
+ * 合并结果按材质首次出现的顺序排列；record_ranges为true时记录每个来源对象的索引范围，
+ * 同一对象的相邻网格合并为一个范围
+ */
+pub fn merge_by_material<V: VertexPosition + Clone>(meshes: Vec<MeshData<V>>, record_ranges: bool) -> Vec<MeshData<V>> {
+    let mut merged: Vec<MeshData<V>> = Vec::new();
+    for mesh in meshes {
+        // 只合并三角形列表，其他图元类型首尾相接会改变拓扑
+        let target = if mesh.primitive_type == PrimitiveType::TrianglesList {
+            merged.iter().position(|m| m.primitive_type == PrimitiveType::TrianglesList && m.material == mesh.material)
+        } else {
+            None
+        };
+        let target = match target {
+            Some(target) => &mut merged[target],
+            None => {
+                merged.push(MeshData {
+                    vertices: Vec::new(),
+                    indices: Vec::new(),
+                    primitive_type: mesh.primitive_type,
+                    material: mesh.material.clone(),
+                    name: None,
+                    group: None,
+                    ranges: Vec::new(),
+                    bounds: Aabb::empty(),
+                    sphere: BoundingSphere::empty(),
+                    lods: Vec::new(),
+                });
+                merged.last_mut().unwrap()
+            },
+        };
+
+        let offset = target.vertices.len() as u32;
+        let start = target.indices.len();
+        target.indices.extend(mesh.indices.iter().map(|i| i + offset));
+        target.vertices.extend(mesh.vertices);
+        target.bounds = target.bounds.merge(&mesh.bounds);
+        target.sphere = target.sphere.merge(&mesh.sphere);
+
+        if record_ranges {
+            let name = mesh.name.unwrap_or_default();
+            let count = mesh.indices.len();
+            match target.ranges.last_mut() {
+                Some(last) if last.name == name && last.start + last.count == start => last.count += count,
+                _ => target.ranges.push(MeshRange { name, start, count }),
+            }
+        }
+    }
+    merged
+}
+
+impl<V: glium::Vertex + Send + 'static> MeshData<V> {
+
+    pub fn vertex_buffer(&self, display: &Display) -> Result<VertexBuffer<V>, LoadError> {
+        Ok(VertexBuffer::new(display, &self.vertices)?)
+    }
+
+    /// 上传为u16索引缓冲，顶点数超出u16范围时返回错误
+    pub fn index_buffer_u16(&self, display: &Display) -> Result<IndexBuffer<u16>, LoadError> {
+        if self.vertices.len() > u16::MAX as usize {
+            return Err(LoadError::gpu("索引缓冲", format!("{}个顶点超出u16索引范围", self.vertices.len())));
+        }
+        let indices: Vec<u16> = self.indices.iter().map(|i| *i as u16).collect();
+        Ok(IndexBuffer::new(display, self.primitive_type, &indices)?)
+    }
+
+    pub fn index_buffer_u32(&self, display: &Display) -> Result<IndexBuffer<u32>, LoadError> {
+        Ok(IndexBuffer::new(display, self.primitive_type, &self.indices)?)
+    }
+
+    /// 上传顶点与索引，索引类型根据顶点数量自动选择
+    pub fn upload(&self, display: &Display) -> Result<(VertexBufferAny, IndexBufferAny), LoadError> {
+        let vertex_buffer = self.vertex_buffer(display)?.into();
+        let index_buffer = create_index_buffer(display, self.primitive_type, self.vertices.len(), &self.indices)?;
+        Ok((vertex_buffer, index_buffer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Point3;
+
+    use super::*;
+    use crate::objects::{Cube, Plane};
+
+    fn triangle(name: &str, material: &str, offset: f32) -> MeshData<Vertex> {
+        let vertex = |x: f32, y: f32| Vertex { position: [x + offset, y, 0.0], normal: [0.0, 0.0, 1.0], texture: [0.0, 0.0] };
+        MeshData::new(vec![vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0)], vec![0, 1, 2], PrimitiveType::TrianglesList)
+            .with_material(Some(material.to_string()))
+            .with_name(Some(name.to_string()))
+    }
+
+    #[test]
+    fn cube_mesh_counts_and_bounds() {
+        let mesh = Cube::mesh(2.0);
+        assert_eq!(mesh.vertices.len(), 36);
+        assert_eq!(mesh.indices.len(), 36);
+        assert!(mesh.indices.iter().all(|i| (*i as usize) < mesh.vertices.len()));
+        assert_eq!(mesh.primitive_type, PrimitiveType::TrianglesList);
+        assert_eq!(mesh.bounds.min, Point3::new(-1.0, -1.0, -1.0));
+        assert_eq!(mesh.bounds.max, Point3::new(1.0, 1.0, 1.0));
+        assert!(mesh.sphere.radius >= 3.0_f32.sqrt() - 1e-5);
+    }
+
+    #[test]
+    fn plane_mesh_2d_counts_and_bounds() {
+        let mesh = Plane::mesh_2d(2.0, 4.0);
+        assert_eq!(mesh.vertices.len(), 6);
+        assert_eq!(mesh.indices, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(mesh.bounds.min, Point3::new(-2.0, -1.0, 0.0));
+        assert_eq!(mesh.bounds.max, Point3::new(2.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn merge_by_material_offsets_indices_and_records_ranges() {
+        let meshes = vec![
+            triangle("a", "red", 0.0),
+            triangle("b", "blue", 1.0),
+            triangle("a", "red", 2.0),
+            triangle("c", "red", 3.0),
+        ];
+        let merged = merge_by_material(meshes, true);
+        assert_eq!(merged.len(), 2);
+
+        // 按材质首次出现的顺序排列
+        let red = &merged[0];
+        assert_eq!(red.material.as_deref(), Some("red"));
+        assert_eq!(red.vertices.len(), 9);
+        assert_eq!(red.indices, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        // 同一对象的相邻网格合并为一个范围
+        assert_eq!(red.ranges, vec![
+            MeshRange { name: "a".to_string(), start: 0, count: 6 },
+            MeshRange { name: "c".to_string(), start: 6, count: 3 },
+        ]);
+        assert_eq!(red.bounds.min, Point3::new(0.0, 0.0, 0.0));
+        assert_eq!(red.bounds.max, Point3::new(4.0, 1.0, 0.0));
+
+        let blue = &merged[1];
+        assert_eq!(blue.material.as_deref(), Some("blue"));
+        assert_eq!(blue.indices, vec![0, 1, 2]);
+        assert_eq!(blue.ranges, vec![MeshRange { name: "b".to_string(), start: 0, count: 3 }]);
+
+        let merged = merge_by_material(vec![triangle("a", "red", 0.0), triangle("b", "red", 1.0)], false);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].indices, vec![0, 1, 2, 3, 4, 5]);
+        assert!(merged[0].ranges.is_empty());
+    }
+
+    #[test]
+    fn merge_by_material_keeps_strips_separate() {
+        let strip = |material: &str| MeshData::new(Plane::mesh_2d(1.0, 1.0).vertices, vec![0, 1, 2, 5], PrimitiveType::TriangleStrip)
+            .with_material(Some(material.to_string()));
+        let merged = merge_by_material(vec![strip("red"), strip("red")], false);
+        assert_eq!(merged.len(), 2);
+        assert!(merged.iter().all(|mesh| mesh.indices == vec![0, 1, 2, 5]));
+    }
+
+    #[test]
+    fn optimize_vertex_cache_keeps_unreferenced_vertices() {
+        let vertex = |x: f32| Vertex { position: [x, 0.0, 0.0], normal: [0.0, 0.0, 1.0], texture: [0.0, 0.0] };
+        // 顶点1未被索引引用，重排后仍保留在末尾
+        let vertices = (0..5).map(|i| vertex(i as f32)).collect();
+        let mut mesh = MeshData::new(vertices, vec![4, 2, 0, 0, 2, 3], PrimitiveType::TrianglesList);
+        let mut before: Vec<[f32; 3]> = mesh.indices.iter().map(|i| mesh.vertices[*i as usize].position).collect();
+
+        mesh.optimize_vertex_cache();
+        assert_eq!(mesh.vertices.len(), 5);
+        assert_eq!(mesh.vertices[4].position, [1.0, 0.0, 0.0]);
+        let mut after: Vec<[f32; 3]> = mesh.indices.iter().map(|i| mesh.vertices[*i as usize].position).collect();
+        before.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        after.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        assert_eq!(after, before);
+    }
+}
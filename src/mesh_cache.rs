@@ -0,0 +1,398 @@
+//! OBJ解析结果的二进制缓存
+//!
+//! 首次加载时将三角化、去重后的网格写入OBJ旁边的`.meshcache`文件，之后通过内存映射直接读取，
+//! 跳过文本解析与顶点去重。缓存中保存OBJ与其引用的MTL文件内容的哈希，源文件变化后缓存自动失效
+//! 源文件的大小与修改时间都与缓存记录一致时直接使用缓存，不一致时才重新计算内容哈希
+//!
+//! 文件格式（小端序）：
+//! ```text
+//! magic "RMSH" | version u32 | 选项哈希 u64 | 内容哈希 u64
+//! mtl数量 u32 | 每个mtl文件名: 长度u32 + UTF-8
+//! OBJ及每个mtl的文件状态: 大小u64 + 修改时间u64（纳秒，文件不存在时为u64::MAX）
+//! 网格数量 u32 | 每个网格: 材质标记u8 (+ 长度u32 + UTF-8) | 对象名称标记u8 (+ 长度u32 + UTF-8) | 组名称标记u8 (+ 长度u32 + UTF-8) | 顶点数u32 | 索引数u32 | 顶点(8 x f32) | 索引(u32)
+//! ```
+
+use std::{fs::{self, File}, io::{self, Write, BufWriter}, path::{Path, PathBuf}, time::UNIX_EPOCH};
+
+use glium::index::PrimitiveType;
+use memmap2::Mmap;
+
+use crate::{Vertex, mesh::MeshData, geometry::normals::NormalMode};
+
+const MAGIC: &[u8; 4] = b"RMSH";
+/// 格式或网格生成逻辑变化时递增，使旧缓存失效
+const VERSION: u32 = 4;
+/// 顶点在缓存中的字节数：位置、法向量、纹理坐标共8个f32
+const VERTEX_SIZE: usize = 32;
+
+/// 从缓存读出的网格，mtl_files为OBJ中`mtllib`引用的文件名
+pub struct CachedMeshes {
+    pub meshes: Vec<MeshData<Vertex>>,
+    pub mtl_files: Vec<String>,
+}
+
+/// OBJ文件对应的缓存文件路径
+pub fn cache_path(obj_path: &Path) -> PathBuf {
+    let mut path = obj_path.as_os_str().to_owned();
+    path.push(".meshcache");
+    PathBuf::from(path)
+}
+
+/// 影响网格生成的选项的哈希
+fn options_hash(normals: NormalMode) -> u64 {
+    let mut hasher = Fnv64::new();
+    hasher.write(&VERSION.to_le_bytes());
+    hasher.write(format!("{:?}", normals).as_bytes());
+    hasher.finish()
+}
+
+/**
+ * 计算源文件内容哈希，包含OBJ与MTL文件内容
+ * MTL文件名相对于OBJ所在目录
+ */
+pub fn source_hash(obj_path: &Path, mtl_files: &[String]) -> io::Result<u64> {
+    let mut hasher = Fnv64::new();
+    hasher.write(&fs::read(obj_path)?);
+    let dir = obj_path.parent().unwrap_or_else(|| Path::new(""));
+    for mtl in mtl_files {
+        hasher.write(mtl.as_bytes());
+        // MTL文件缺失时也能产生稳定的哈希，材质加载阶段会报告具体错误
+        if let Ok(bytes) = fs::read(dir.join(mtl)) {
+            hasher.write(&bytes);
+        }
+    }
+    Ok(hasher.finish())
+}
+
+/// 源文件的大小与修改时间，依次为OBJ和各个MTL文件
+fn source_stamps(obj_path: &Path, mtl_files: &[String]) -> Vec<(u64, u64)> {
+    let stamp = |path: &Path| match fs::metadata(path) {
+        Ok(metadata) => {
+            let modified = metadata.modified().ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_nanos() as u64)
+                .unwrap_or(0);
+            (metadata.len(), modified)
+        },
+        Err(_) => (u64::MAX, u64::MAX),
+    };
+    let dir = obj_path.parent().unwrap_or_else(|| Path::new(""));
+    std::iter::once(stamp(obj_path)).chain(mtl_files.iter().map(|mtl| stamp(&dir.join(mtl)))).collect()
+}
+
+/**
+ * 读取缓存，缓存不存在、已损坏或源文件已变化时返回None
+ */
+pub fn read(obj_path: &Path, normals: NormalMode) -> Option<CachedMeshes> {
+    let file = File::open(cache_path(obj_path)).ok()?;
+    // 缓存文件只由本程序写入，读取期间不会被截断
+    let mmap = unsafe { Mmap::map(&file) }.ok()?;
+    let mut reader = Reader { data: &mmap, offset: 0 };
+
+    if reader.bytes(4)? != MAGIC || reader.u32()? != VERSION || reader.u64()? != options_hash(normals) {
+        return None;
+    }
+    let hash = reader.u64()?;
+    let mtl_count = reader.u32()?;
+    let mut mtl_files = Vec::with_capacity(mtl_count as usize);
+    for _ in 0..mtl_count {
+        mtl_files.push(reader.string()?);
+    }
+    let mut stamps = Vec::with_capacity(mtl_files.len() + 1);
+    for _ in 0..=mtl_count {
+        stamps.push((reader.u64()?, reader.u64()?));
+    }
+    // 大小或修改时间变化时才读取源文件计算哈希，内容未变（例如只是touch）时缓存仍然有效
+    if stamps != source_stamps(obj_path, &mtl_files) && source_hash(obj_path, &mtl_files).ok()? != hash {
+        return None;
+    }
+
+    let mesh_count = reader.u32()?;
+    let mut meshes = Vec::with_capacity(mesh_count as usize);
+    for _ in 0..mesh_count {
+        let material = reader.optional_string()?;
+        let name = reader.optional_string()?;
+        let group = reader.optional_string()?;
+        let vertex_count = reader.u32()? as usize;
+        let index_count = reader.u32()? as usize;
+
+        let vertices = read_vertices(reader.bytes(vertex_count.checked_mul(VERTEX_SIZE)?)?);
+        let indices = read_indices(reader.bytes(index_count.checked_mul(4)?)?);
+        if indices.iter().any(|i| *i as usize >= vertex_count) {
+            return None;
+        }
+
+        meshes.push(MeshData::new(vertices, indices, PrimitiveType::TrianglesList).with_material(material).with_name(name).with_group(group));
+    }
+    Some(CachedMeshes { meshes, mtl_files })
+}
+
+/**
+ * 写入缓存，仅支持三角形列表
+ */
+pub fn write(obj_path: &Path, mtl_files: &[String], normals: NormalMode, meshes: &[MeshData<Vertex>]) -> io::Result<()> {
+    let hash = source_hash(obj_path, mtl_files)?;
+    let stamps = source_stamps(obj_path, mtl_files);
+    // 先写临时文件再重命名，避免中断后留下不完整的缓存
+    let path = cache_path(obj_path);
+    let mut tmp = path.clone().into_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let mut writer = BufWriter::new(File::create(&tmp)?);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&options_hash(normals).to_le_bytes())?;
+    writer.write_all(&hash.to_le_bytes())?;
+    writer.write_all(&(mtl_files.len() as u32).to_le_bytes())?;
+    for mtl in mtl_files {
+        write_string(&mut writer, mtl)?;
+    }
+    for (len, modified) in stamps {
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(&modified.to_le_bytes())?;
+    }
+    writer.write_all(&(meshes.len() as u32).to_le_bytes())?;
+    for mesh in meshes {
+        write_optional_string(&mut writer, &mesh.material)?;
+        write_optional_string(&mut writer, &mesh.name)?;
+        write_optional_string(&mut writer, &mesh.group)?;
+        writer.write_all(&(mesh.vertices.len() as u32).to_le_bytes())?;
+        writer.write_all(&(mesh.indices.len() as u32).to_le_bytes())?;
+        for vertex in mesh.vertices.iter() {
+            for value in vertex.position.iter().chain(vertex.normal.iter()).chain(vertex.texture.iter()) {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        for index in mesh.indices.iter() {
+            writer.write_all(&index.to_le_bytes())?;
+        }
+    }
+    writer.flush()?;
+    drop(writer);
+    fs::rename(tmp, path)
+}
+
+/// 从映射的字节中一次性复制顶点数据，小端序平台上缓存布局与`Vertex`的内存布局一致
+fn read_vertices(bytes: &[u8]) -> Vec<Vertex> {
+    let count = bytes.len() / VERTEX_SIZE;
+    if cfg!(target_endian = "little") && std::mem::size_of::<Vertex>() == VERTEX_SIZE {
+        let mut vertices = Vec::<Vertex>::with_capacity(count);
+        // Vertex为repr(C)且只包含f32，任意字节都是合法的值；映射中的数据不保证对齐，按字节复制
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), vertices.as_mut_ptr() as *mut u8, count * VERTEX_SIZE);
+            vertices.set_len(count);
+        }
+        return vertices;
+    }
+    bytes.chunks_exact(VERTEX_SIZE).map(|v| {
+        let f = |i: usize| f32::from_le_bytes([v[i * 4], v[i * 4 + 1], v[i * 4 + 2], v[i * 4 + 3]]);
+        Vertex { position: [f(0), f(1), f(2)], normal: [f(3), f(4), f(5)], texture: [f(6), f(7)] }
+    }).collect()
+}
+
+/// 从映射的字节中一次性复制索引数据
+fn read_indices(bytes: &[u8]) -> Vec<u32> {
+    let count = bytes.len() / 4;
+    if cfg!(target_endian = "little") {
+        let mut indices = Vec::<u32>::with_capacity(count);
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), indices.as_mut_ptr() as *mut u8, count * 4);
+            indices.set_len(count);
+        }
+        return indices;
+    }
+    bytes.chunks_exact(4).map(|i| u32::from_le_bytes([i[0], i[1], i[2], i[3]])).collect()
+}
+
+fn write_string<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    writer.write_all(&(value.len() as u32).to_le_bytes())?;
+    writer.write_all(value.as_bytes())
+}
+
+fn write_optional_string<W: Write>(writer: &mut W, value: &Option<String>) -> io::Result<()> {
+    match value {
+        Some(value) => {
+            writer.write_all(&[1])?;
+            write_string(writer, value)
+        },
+        None => writer.write_all(&[0]),
+    }
+}
+
+/// 按顺序读取字节切片，越界时返回None
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.offset.checked_add(len)?;
+        let bytes = self.data.get(self.offset..end)?;
+        self.offset = end;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.bytes(8).map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.bytes(len)?.to_vec()).ok()
+    }
+
+    /// 读取带标记的可选字符串，外层None表示数据损坏
+    fn optional_string(&mut self) -> Option<Option<String>> {
+        match self.u8()? {
+            0 => Some(None),
+            _ => self.string().map(Some),
+        }
+    }
+}
+
+/// FNV-1a 64位哈希，结果与Rust版本无关，适合持久化
+struct Fnv64(u64);
+
+impl Fnv64 {
+
+    fn new() -> Self {
+        Fnv64(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    const OBJ: &str = "mtllib scene.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl red\nf 1 2 3\n";
+    const MTL: &str = "newmtl red\nKd 1 0 0\n";
+
+    /// 每个测试使用独立的临时目录
+    fn scene(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mesh_cache_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("scene.obj"), OBJ).unwrap();
+        fs::write(dir.join("scene.mtl"), MTL).unwrap();
+        dir.join("scene.obj")
+    }
+
+    fn meshes() -> Vec<MeshData<Vertex>> {
+        let vertex = |x: f32, y: f32| Vertex { position: [x, y, 0.0], normal: [0.0, 0.0, 1.0], texture: [x, y] };
+        vec![
+            MeshData::new(vec![vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0)], vec![0, 1, 2], PrimitiveType::TrianglesList)
+                .with_material(Some("red".to_string()))
+                .with_name(Some("tri".to_string())),
+            MeshData::new(vec![vertex(2.0, 0.0), vertex(3.0, 0.0), vertex(2.0, 1.0), vertex(3.0, 1.0)], vec![0, 1, 2, 2, 1, 3], PrimitiveType::TrianglesList)
+                .with_group(Some("quad".to_string())),
+        ]
+    }
+
+    fn write_scene(obj: &Path) {
+        write(obj, &["scene.mtl".to_string()], NormalMode::Smooth, &meshes()).unwrap();
+    }
+
+    /// 修改文件后把修改时间设为明显不同的值，避免文件系统时间精度导致时间戳不变
+    fn touch(path: &Path, offset: u64) {
+        let file = File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(offset)).unwrap();
+    }
+
+    #[test]
+    fn round_trip() {
+        let obj = scene("round_trip");
+        write_scene(&obj);
+        let cached = read(&obj, NormalMode::Smooth).expect("缓存应当有效");
+        assert_eq!(cached.mtl_files, vec!["scene.mtl".to_string()]);
+
+        let expected = meshes();
+        assert_eq!(cached.meshes.len(), expected.len());
+        for (mesh, expected) in cached.meshes.iter().zip(expected.iter()) {
+            assert_eq!(mesh.indices, expected.indices);
+            assert_eq!(mesh.material, expected.material);
+            assert_eq!(mesh.name, expected.name);
+            assert_eq!(mesh.group, expected.group);
+            assert_eq!(mesh.vertices.len(), expected.vertices.len());
+            for (a, b) in mesh.vertices.iter().zip(expected.vertices.iter()) {
+                assert_eq!((a.position, a.normal, a.texture), (b.position, b.normal, b.texture));
+            }
+        }
+        fs::remove_dir_all(obj.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn normals_option_invalidates() {
+        let obj = scene("normals");
+        write_scene(&obj);
+        assert!(read(&obj, NormalMode::Flat).is_none());
+        assert!(read(&obj, NormalMode::Smooth).is_some());
+        fs::remove_dir_all(obj.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn source_edits_invalidate() {
+        let obj = scene("edits");
+        write_scene(&obj);
+        fs::write(&obj, format!("{}f 3 2 1\n", OBJ)).unwrap();
+        touch(&obj, 10);
+        assert!(read(&obj, NormalMode::Smooth).is_none());
+
+        let obj = scene("edits");
+        write_scene(&obj);
+        let mtl = obj.with_file_name("scene.mtl");
+        fs::write(&mtl, "newmtl red\nKd 0 1 0\n").unwrap();
+        touch(&mtl, 10);
+        assert!(read(&obj, NormalMode::Smooth).is_none());
+
+        fs::remove_file(&mtl).unwrap();
+        assert!(read(&obj, NormalMode::Smooth).is_none());
+        fs::remove_dir_all(obj.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn touched_sources_keep_cache() {
+        // 只修改时间不修改内容时，通过内容哈希确认缓存仍然有效
+        let obj = scene("touch");
+        write_scene(&obj);
+        touch(&obj, 10);
+        touch(&obj.with_file_name("scene.mtl"), 10);
+        assert!(read(&obj, NormalMode::Smooth).is_some());
+        fs::remove_dir_all(obj.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn matching_stamps_skip_hashing() {
+        // 大小与修改时间都未变化时不会读取源文件内容
+        let obj = scene("stamps");
+        write_scene(&obj);
+        let modified = fs::metadata(&obj).unwrap().modified().unwrap();
+        fs::write(&obj, OBJ.replace("v 1 0 0", "v 2 0 0")).unwrap();
+        File::options().write(true).open(&obj).unwrap().set_modified(modified).unwrap();
+        assert!(read(&obj, NormalMode::Smooth).is_some());
+        fs::remove_dir_all(obj.parent().unwrap()).unwrap();
+    }
+}
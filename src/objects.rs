@@ -3,10 +3,10 @@ use std::f32::consts::PI;
 use cgmath::{Point3, Matrix4, Vector3, Transform, SquareMatrix, Point2, Rad, Angle};
 use glium::{VertexBuffer, IndexBuffer, index::PrimitiveType};
 
-use crate::{Vertex, objectsv2::RawVertexPNTTB};
+use crate::{Vertex, objectsv2::RawVertexPNTTB, mesh::MeshData, geometry::bounds::{Aabb, BoundingSphere}};
 
 
-static CUBE_INDEX_ARRAY: [u16; 36] = [0u16, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35];
+static CUBE_INDEX_ARRAY: [u32; 36] = [0u32, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35];
 
 
 /**
@@ -19,6 +19,10 @@ pub struct Cube {
     pub color: [f32; 3],
     position: Point3<f32>,
     pub model: Matrix4<f32>,
+    /// 局部坐标系下的包围盒
+    pub bounds: Aabb,
+    /// 局部坐标系下的包围球
+    pub sphere: BoundingSphere,
 }
 
 impl Cube {
@@ -27,116 +31,135 @@ impl Cube {
      * 顶点顺序均为逆时针
      */
     pub fn new(id: &str, side_len: f32, display: &glium::Display, color: [f32; 3], position: Point3<f32>, model: Matrix4<f32>) -> Cube {
-        let half = side_len / 2_f32;
+        let mesh = Self::mesh(side_len);
         Cube {
             id: id.to_string(),
-            vertex_buffer: glium::VertexBuffer::new(display, &[
-                // 前
-                Vertex { position: [-half, half, half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [-half, -half, half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [half, -half, half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-half, half, half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [half, -half, half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [half, half, half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 1.0] },
-                // 后
-                Vertex { position: [half, half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 1.0] },
-                Vertex { position: [half, -half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-half, -half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [half, half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 1.0] },
-                Vertex { position: [-half, -half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [-half, half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 1.0] },
-                // 左
-                Vertex { position: [-half, half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [-half, -half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [-half, -half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-half, half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [-half, -half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-half, half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 1.0] },
-                // 右
-                Vertex { position: [half, half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [half, -half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [half, -half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [half, half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [half, -half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [half, half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 1.0] },
-                // 上
-                Vertex { position: [-half, half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [-half, half, half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [half, half, half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-half, half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [half, half, half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [half, half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 1.0] },
-                // 下
-                Vertex { position: [-half, -half, half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [-half, -half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [half, -half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 1.0] },
-                Vertex { position: [-half, -half, half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [half, -half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 1.0] },
-                Vertex { position: [half, -half, half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 0.0] },
-            ]).unwrap(),
-            index_buffer: glium::IndexBuffer::new(display, PrimitiveType::TrianglesList, &CUBE_INDEX_ARRAY).unwrap(),
+            vertex_buffer: mesh.vertex_buffer(display).unwrap(),
+            index_buffer: mesh.index_buffer_u16(display).unwrap(),
+            bounds: mesh.bounds,
+            sphere: mesh.sphere,
             color: color,
             position: position,
             model: model,
         }
     }
 
-    // 创建天空盒box
-    pub fn new_skybox(id: &str, side_len: f32, display: &glium::Display) -> Cube {
+    /**
+     * 正方体的CPU端网格数据，顶点顺序均为逆时针
+     */
+    pub fn mesh(side_len: f32) -> MeshData<Vertex> {
         let half = side_len / 2_f32;
+        MeshData::new(vec![
+            // 前
+            Vertex { position: [-half, half, half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [-half, -half, half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [half, -half, half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-half, half, half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [half, -half, half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [half, half, half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 1.0] },
+            // 后
+            Vertex { position: [half, half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 1.0] },
+            Vertex { position: [half, -half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-half, -half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [half, half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 1.0] },
+            Vertex { position: [-half, -half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [-half, half, -half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 1.0] },
+            // 左
+            Vertex { position: [-half, half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [-half, -half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [-half, -half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-half, half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [-half, -half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-half, half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 1.0] },
+            // 右
+            Vertex { position: [half, half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [half, -half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [half, -half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [half, half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [half, -half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [half, half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 1.0] },
+            // 上
+            Vertex { position: [-half, half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [-half, half, half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [half, half, half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-half, half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [half, half, half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [half, half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 1.0] },
+            // 下
+            Vertex { position: [-half, -half, half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [-half, -half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [half, -half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 1.0] },
+            Vertex { position: [-half, -half, half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [half, -half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 1.0] },
+            Vertex { position: [half, -half, half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 0.0] },
+        ], CUBE_INDEX_ARRAY.to_vec(), PrimitiveType::TrianglesList)
+    }
+
+    pub fn new_skybox(id: &str, side_len: f32, display: &glium::Display) -> Cube {
+        let mesh = Self::skybox_mesh(side_len);
         Cube {
             id: id.to_string(),
-            vertex_buffer: glium::VertexBuffer::new(display, &[
-                // 前
-                Vertex { position: [-half, half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [-half, -half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [half, -half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-half, half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [half, -half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [half, half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 1.0] },
-                // 后
-                Vertex { position: [half, half, half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 1.0] },
-                Vertex { position: [half, -half, half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-half, -half, half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [half, half, half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 1.0] },
-                Vertex { position: [-half, -half, half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [-half, half, half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 1.0] },
-                // 左
-                Vertex { position: [-half, half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [-half, -half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [-half, -half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-half, half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [-half, -half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-half, half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 1.0] },
-                // 右
-                Vertex { position: [half, half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [half, -half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [half, -half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [half, half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [half, -half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [half, half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 1.0] },
-                // 上
-                Vertex { position: [-half, half, half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [-half, half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [half, half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 1.0] },
-                Vertex { position: [-half, half, half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [half, half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 1.0] },
-                Vertex { position: [half, half, half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                // 下
-                Vertex { position: [-half, -half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [-half, -half, half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 0.0] },
-                Vertex { position: [half, -half, half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-half, -half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 1.0] },
-                Vertex { position: [half, -half, half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 0.0] },
-                Vertex { position: [half, -half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 1.0] },
-            ]).unwrap(),
-            index_buffer: glium::IndexBuffer::new(display, PrimitiveType::TrianglesList, &CUBE_INDEX_ARRAY).unwrap(),
+            vertex_buffer: mesh.vertex_buffer(display).unwrap(),
+            index_buffer: mesh.index_buffer_u16(display).unwrap(),
+            bounds: mesh.bounds,
+            sphere: mesh.sphere,
             color: [0.0, 0.0, 0.0],
             position: Point3::new(0.0, 0.0, 0.0),
             model: Matrix4::identity(),
         }
     }
 
+    /**
+     * 天空盒的CPU端网格数据
+     */
+    pub fn skybox_mesh(side_len: f32) -> MeshData<Vertex> {
+        let half = side_len / 2_f32;
+        MeshData::new(vec![
+            // 前
+            Vertex { position: [-half, half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [-half, -half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [half, -half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-half, half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [half, -half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [half, half, -half], normal: [0_f32, 0_f32, 1_f32], texture: [1.0_f32, 1.0] },
+            // 后
+            Vertex { position: [half, half, half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 1.0] },
+            Vertex { position: [half, -half, half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-half, -half, half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [half, half, half], normal: [0_f32, 0_f32, -1_f32], texture: [1.0_f32, 1.0] },
+            Vertex { position: [-half, -half, half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [-half, half, half], normal: [0_f32, 0_f32, -1_f32], texture: [0.0_f32, 1.0] },
+            // 左
+            Vertex { position: [-half, half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [-half, -half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [-half, -half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-half, half, half], normal: [1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [-half, -half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-half, half, -half], normal: [1_f32, 0_f32, 0_f32], texture: [1.0_f32, 1.0] },
+            // 右
+            Vertex { position: [half, half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [half, -half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [half, -half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [half, half, -half], normal: [-1_f32, 0_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [half, -half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [half, half, half], normal: [-1_f32, 0_f32, 0_f32], texture: [1.0_f32, 1.0] },
+            // 上
+            Vertex { position: [-half, half, half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [-half, half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [half, half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 1.0] },
+            Vertex { position: [-half, half, half], normal: [0_f32, -1_f32, 0_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [half, half, -half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 1.0] },
+            Vertex { position: [half, half, half], normal: [0_f32, -1_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            // 下
+            Vertex { position: [-half, -half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [-half, -half, half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 0.0] },
+            Vertex { position: [half, -half, half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-half, -half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [0.0_f32, 1.0] },
+            Vertex { position: [half, -half, half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 0.0] },
+            Vertex { position: [half, -half, -half], normal: [0_f32, 1_f32, 0_f32], texture: [1.0_f32, 1.0] },
+        ], CUBE_INDEX_ARRAY.to_vec(), PrimitiveType::TrianglesList)
+    }
+
     pub fn position_matrix(&self) -> Matrix4<f32> {
         Matrix4::from_translation(Vector3::new(self.position.x, self.position.y, self.position.z))
     }
@@ -148,6 +171,16 @@ impl Cube {
     pub fn calc_model(&self) -> Matrix4<f32> {
         Matrix4::from_translation(Vector3::new(self.position.x, self.position.y, self.position.z)) * self.model
     }
+
+    /// 世界坐标系下的包围盒
+    pub fn world_bounds(&self) -> Aabb {
+        self.bounds.transform(&self.calc_model())
+    }
+
+    /// 世界坐标系下的包围球
+    pub fn world_sphere(&self) -> BoundingSphere {
+        self.sphere.transform(&self.calc_model())
+    }
 }
 
 
@@ -159,6 +192,10 @@ pub struct Plane {
     pub index_buffer: IndexBuffer<u16>,
     pub position: Point3<f32>,
     model: Matrix4<f32>,
+    /// 局部坐标系下的包围盒
+    pub bounds: Aabb,
+    /// 局部坐标系下的包围球
+    pub sphere: BoundingSphere,
 }
 
 
@@ -166,26 +203,36 @@ impl Plane {
 
     // y: 4个顶点的共同y值
     pub fn new(id: &str, length: f32, width: f32, y: f32, display: &glium::Display, position: Point3<f32>, model: Matrix4<f32>) -> Plane {
-        let x = length / 2.0_f32;
-        let z = width / 2.0_f32;
+        let mesh = Self::mesh(length, width, y);
         Plane {
             id: id.to_string(),
-            vertex_buffer: glium::VertexBuffer::new(display, &[
-                // texture Coords (note we set these higher than 1 (together with GL_REPEAT as texture wrapping mode). this will cause the floor texture to repeat)
-                Vertex { position: [-x, y, z], normal: [0_f32, 1.0, 0.0], texture: [0.0_f32, 0.0] },
-                Vertex { position: [-x, y, -z], normal: [0_f32, 1.0, 0.0], texture: [0.0_f32, 2.0] },
-                Vertex { position: [x, y, -z], normal: [0_f32, 1.0, 0.0], texture: [2.0_f32, 2.0] },
-
-                Vertex { position: [-x, y, z], normal: [0_f32, 1.0, 0.0], texture: [0.0_f32, 0.0] },
-                Vertex { position: [x, y, -z], normal: [0_f32, 1.0, 0.0], texture: [2.0_f32, 2.0] },
-                Vertex { position: [x, y, z], normal: [0_f32, 1.0, 0.0], texture: [2.0_f32, 0.0] },
-            ]).unwrap(),
-            index_buffer: glium::IndexBuffer::new(display, PrimitiveType::TrianglesList, &[0u16, 1, 2, 3, 4, 5]).unwrap(),
+            vertex_buffer: mesh.vertex_buffer(display).unwrap(),
+            index_buffer: mesh.index_buffer_u16(display).unwrap(),
+            bounds: mesh.bounds,
+            sphere: mesh.sphere,
             position: position,
             model: model,
         }
     }
 
+    /**
+     * 水平平面的CPU端网格数据，y为4个顶点的共同y值
+     */
+    pub fn mesh(length: f32, width: f32, y: f32) -> MeshData<Vertex> {
+        let x = length / 2.0_f32;
+        let z = width / 2.0_f32;
+        MeshData::new(vec![
+            // texture Coords (note we set these higher than 1 (together with GL_REPEAT as texture wrapping mode). this will cause the floor texture to repeat)
+            Vertex { position: [-x, y, z], normal: [0_f32, 1.0, 0.0], texture: [0.0_f32, 0.0] },
+            Vertex { position: [-x, y, -z], normal: [0_f32, 1.0, 0.0], texture: [0.0_f32, 2.0] },
+            Vertex { position: [x, y, -z], normal: [0_f32, 1.0, 0.0], texture: [2.0_f32, 2.0] },
+
+            Vertex { position: [-x, y, z], normal: [0_f32, 1.0, 0.0], texture: [0.0_f32, 0.0] },
+            Vertex { position: [x, y, -z], normal: [0_f32, 1.0, 0.0], texture: [2.0_f32, 2.0] },
+            Vertex { position: [x, y, z], normal: [0_f32, 1.0, 0.0], texture: [2.0_f32, 0.0] },
+        ], vec![0, 1, 2, 3, 4, 5], PrimitiveType::TrianglesList)
+    }
+
     pub fn calc_model_with(&self, other: Matrix4<f32>) -> Matrix4<f32> {
         Matrix4::from_translation(Vector3::new(self.position.x, self.position.y, self.position.z)) * other * self.model
     }
@@ -194,60 +241,100 @@ impl Plane {
         Matrix4::from_translation(Vector3::new(self.position.x, self.position.y, self.position.z)) * self.model
     }
 
+    /// 世界坐标系下的包围盒
+    pub fn world_bounds(&self) -> Aabb {
+        self.bounds.transform(&self.calc_model())
+    }
+
+    /// 世界坐标系下的包围球
+    pub fn world_sphere(&self) -> BoundingSphere {
+        self.sphere.transform(&self.calc_model())
+    }
+
     pub fn new_vertical_plane(id: &str, height: f32, width: f32, display: &glium::Display, position: Point3<f32>, model: Matrix4<f32>) -> Plane {
-        let x = width / 2.0_f32;
+        let mesh = Self::vertical_mesh(height, width);
         Plane {
             id: id.to_string(),
-            vertex_buffer: glium::VertexBuffer::new(display, &[
-                // texture Coords (note we set these higher than 1 (together with GL_REPEAT as texture wrapping mode). this will cause the floor texture to repeat)
-                Vertex { position: [-x, 0.0, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 0.0] },
-                Vertex { position: [x, 0.0, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-x, height, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 1.0] },
-                Vertex { position: [x, height, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 1.0] },
-            ]).unwrap(),
-            index_buffer: glium::IndexBuffer::new(display, PrimitiveType::TrianglesList, &[0u16, 1, 2, 1, 2, 3]).unwrap(),
+            vertex_buffer: mesh.vertex_buffer(display).unwrap(),
+            index_buffer: mesh.index_buffer_u16(display).unwrap(),
+            bounds: mesh.bounds,
+            sphere: mesh.sphere,
             position: position,
             model: model,
         }
     }
 
-    pub fn new_vertical_center_plane(id: &str, height: f32, width: f32, display: &glium::Display, primitive_type: PrimitiveType) -> Plane {
+    /**
+     * 底边中点位于原点的竖直平面的CPU端网格数据
+     */
+    pub fn vertical_mesh(height: f32, width: f32) -> MeshData<Vertex> {
         let x = width / 2.0_f32;
-        let y = height / 2.0_f32;
+        MeshData::new(vec![
+            // texture Coords (note we set these higher than 1 (together with GL_REPEAT as texture wrapping mode). this will cause the floor texture to repeat)
+            Vertex { position: [-x, 0.0, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 0.0] },
+            Vertex { position: [x, 0.0, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-x, height, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 1.0] },
+            Vertex { position: [x, height, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 1.0] },
+        ], vec![0, 1, 2, 1, 2, 3], PrimitiveType::TrianglesList)
+    }
+
+    pub fn new_vertical_center_plane(id: &str, height: f32, width: f32, display: &glium::Display, primitive_type: PrimitiveType) -> Plane {
+        let mesh = Self::vertical_center_mesh(height, width, primitive_type);
         Plane {
             id: id.to_string(),
-            vertex_buffer: glium::VertexBuffer::new(display, &[
-                // texture Coords (note we set these higher than 1 (together with GL_REPEAT as texture wrapping mode). this will cause the floor texture to repeat)
-                Vertex { position: [-x, y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 1.0] },
-                Vertex { position: [-x, -y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 0.0] },
-                Vertex { position: [x, -y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 0.0] },
-                Vertex { position: [x, y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 1.0] },
-            ]).unwrap(),
-            index_buffer: glium::IndexBuffer::new(display, primitive_type, &[0u16, 1, 2, 0, 2, 3]).unwrap(),
+            vertex_buffer: mesh.vertex_buffer(display).unwrap(),
+            index_buffer: mesh.index_buffer_u16(display).unwrap(),
+            bounds: mesh.bounds,
+            sphere: mesh.sphere,
             position: Point3::new(0.0, 0.0, 0.0),
             model: Matrix4::identity(),
         }
     }
 
-    // 以原点为中心创建2d平面
-    pub fn new_2d_plane(id: &str, height: f32, width: f32, display: &glium::Display) -> Plane {
+    /**
+     * 中心位于原点的竖直平面的CPU端网格数据
+     */
+    pub fn vertical_center_mesh(height: f32, width: f32, primitive_type: PrimitiveType) -> MeshData<Vertex> {
         let x = width / 2.0_f32;
         let y = height / 2.0_f32;
+        MeshData::new(vec![
+            // texture Coords (note we set these higher than 1 (together with GL_REPEAT as texture wrapping mode). this will cause the floor texture to repeat)
+            Vertex { position: [-x, y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 1.0] },
+            Vertex { position: [-x, -y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 0.0] },
+            Vertex { position: [x, -y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 0.0] },
+            Vertex { position: [x, y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 1.0] },
+        ], vec![0, 1, 2, 0, 2, 3], primitive_type)
+    }
+
+    // 以原点为中心创建2d平面
+    pub fn new_2d_plane(id: &str, height: f32, width: f32, display: &glium::Display) -> Plane {
+        let mesh = Self::mesh_2d(height, width);
         Plane {
             id: id.to_string(),
-            vertex_buffer: glium::VertexBuffer::new(display, &[
-                Vertex { position: [-x, y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 1.0] },
-                Vertex { position: [-x, -y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 0.0] },
-                Vertex { position: [x, -y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 0.0] },
-                Vertex { position: [-x, y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 1.0] },
-                Vertex { position: [x, -y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 0.0] },
-                Vertex { position: [x, y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 1.0] },
-            ]).unwrap(),
-            index_buffer: glium::IndexBuffer::new(display, PrimitiveType::TrianglesList, &[0u16, 1, 2, 3, 4, 5]).unwrap(),
+            vertex_buffer: mesh.vertex_buffer(display).unwrap(),
+            index_buffer: mesh.index_buffer_u16(display).unwrap(),
+            bounds: mesh.bounds,
+            sphere: mesh.sphere,
             position: Point3 { x: 0.0_f32, y: 0.0, z: 0.0 },
             model: Matrix4::identity(),
         }
     }
+
+    /**
+     * 以原点为中心的2d平面的CPU端网格数据
+     */
+    pub fn mesh_2d(height: f32, width: f32) -> MeshData<Vertex> {
+        let x = width / 2.0_f32;
+        let y = height / 2.0_f32;
+        MeshData::new(vec![
+            Vertex { position: [-x, y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 1.0] },
+            Vertex { position: [-x, -y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 0.0] },
+            Vertex { position: [x, -y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 0.0] },
+            Vertex { position: [-x, y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [0.0_f32, 1.0] },
+            Vertex { position: [x, -y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 0.0] },
+            Vertex { position: [x, y, 0.0], normal: [0_f32, 0.0, 1.0], texture: [1.0_f32, 1.0] },
+        ], vec![0, 1, 2, 3, 4, 5], PrimitiveType::TrianglesList)
+    }
 }
 
 
@@ -255,11 +342,26 @@ impl Plane {
 pub struct PlaneV2 {
     pub vertex_buffer: VertexBuffer<RawVertexPNTTB>,
     pub index_buffer: IndexBuffer<u16>,
+    pub bounds: Aabb,
+    pub sphere: BoundingSphere,
 }
 
 impl PlaneV2 {
 
     pub fn new_vertical(width: f32, height: f32, display: &glium::Display) -> PlaneV2 {
+        let mesh = Self::vertical_mesh(width, height);
+        PlaneV2 {
+            vertex_buffer: mesh.vertex_buffer(display).unwrap(),
+            index_buffer: mesh.index_buffer_u16(display).unwrap(),
+            bounds: mesh.bounds,
+            sphere: mesh.sphere,
+        }
+    }
+
+    /**
+     * 竖直平面的CPU端网格数据，带切线与副切线
+     */
+    pub fn vertical_mesh(width: f32, height: f32) -> MeshData<RawVertexPNTTB> {
         let x = width / 2.0;
         let y = height / 2.0;
 
@@ -311,18 +413,15 @@ impl PlaneV2 {
             f * (-delta_uv2.x * edge1.z + delta_uv1.x * edge2.z),
         );
 
-        PlaneV2 {
-            vertex_buffer: glium::VertexBuffer::new(display, &[
-                RawVertexPNTTB { position: point1.into(), normal: normal.into(), tex_coords: tex_coords1.into(), tangent: tangent1.into(), bitangent: bitangent1.into() },
-                RawVertexPNTTB { position: point2.into(), normal: normal.into(), tex_coords: tex_coords2.into(), tangent: tangent1.into(), bitangent: bitangent1.into() },
-                RawVertexPNTTB { position: point3.into(), normal: normal.into(), tex_coords: tex_coords3.into(), tangent: tangent1.into(), bitangent: bitangent1.into() },
-
-                RawVertexPNTTB { position: point1.into(), normal: normal.into(), tex_coords: tex_coords1.into(), tangent: tangent2.into(), bitangent: bitangent2.into() },
-                RawVertexPNTTB { position: point3.into(), normal: normal.into(), tex_coords: tex_coords3.into(), tangent: tangent2.into(), bitangent: bitangent2.into() },
-                RawVertexPNTTB { position: point4.into(), normal: normal.into(), tex_coords: tex_coords4.into(), tangent: tangent2.into(), bitangent: bitangent2.into() },
-            ]).unwrap(),
-            index_buffer: glium::IndexBuffer::new(display, PrimitiveType::TrianglesList, &[0u16, 1, 2, 3, 4, 5]).unwrap(),
-        }
+        MeshData::new(vec![
+            RawVertexPNTTB { position: point1.into(), normal: normal.into(), tex_coords: tex_coords1.into(), tangent: tangent1.into(), bitangent: bitangent1.into() },
+            RawVertexPNTTB { position: point2.into(), normal: normal.into(), tex_coords: tex_coords2.into(), tangent: tangent1.into(), bitangent: bitangent1.into() },
+            RawVertexPNTTB { position: point3.into(), normal: normal.into(), tex_coords: tex_coords3.into(), tangent: tangent1.into(), bitangent: bitangent1.into() },
+
+            RawVertexPNTTB { position: point1.into(), normal: normal.into(), tex_coords: tex_coords1.into(), tangent: tangent2.into(), bitangent: bitangent2.into() },
+            RawVertexPNTTB { position: point3.into(), normal: normal.into(), tex_coords: tex_coords3.into(), tangent: tangent2.into(), bitangent: bitangent2.into() },
+            RawVertexPNTTB { position: point4.into(), normal: normal.into(), tex_coords: tex_coords4.into(), tangent: tangent2.into(), bitangent: bitangent2.into() },
+        ], vec![0, 1, 2, 3, 4, 5], PrimitiveType::TrianglesList)
     }
 }
 
@@ -330,6 +429,8 @@ impl PlaneV2 {
 pub struct Sphere {
     pub vertex_buffer: VertexBuffer<Vertex>,
     pub index_buffer: IndexBuffer<u32>,
+    pub bounds: Aabb,
+    pub sphere: BoundingSphere,
 }
 
 impl Sphere {
@@ -341,6 +442,19 @@ impl Sphere {
     /// radius：半径
     /// 顶点数：(x_segments + 1) * (y_segments + 1)
     pub fn new(radius: f32, x_segments: u32, y_segments: u32, display: &glium::Display) -> Self {
+        let mesh = Self::mesh(radius, x_segments, y_segments);
+        Self {
+            vertex_buffer: mesh.vertex_buffer(display).unwrap(),
+            index_buffer: mesh.index_buffer_u32(display).unwrap(),
+            bounds: mesh.bounds,
+            sphere: mesh.sphere,
+        }
+    }
+
+    /**
+     * 球体的CPU端网格数据，图元类型为三角形带
+     */
+    pub fn mesh(radius: f32, x_segments: u32, y_segments: u32) -> MeshData<Vertex> {
         let (positions, uv, normals) = {
             let mut vec = Vec::with_capacity((x_segments * y_segments) as usize);
             let mut uv = Vec::with_capacity((x_segments * y_segments) as usize);
@@ -395,10 +509,7 @@ impl Sphere {
         };
 
 
-        Self {
-            vertex_buffer: glium::VertexBuffer::new(display, &data.as_slice()).unwrap(),
-            index_buffer: glium::IndexBuffer::new(display, PrimitiveType::TriangleStrip, &indices.as_slice()).unwrap(),
-        }
+        MeshData::new(data, indices, PrimitiveType::TriangleStrip)
     }
 
     pub fn new_simple(display: &glium::Display) -> Self {
@@ -32,6 +32,16 @@ pub struct RawVertexPC {
 
 implement_vertex!(RawVertexPC, position, color);
 
+/// 顶点：位置，法向量，顶点颜色
+#[derive(Copy, Clone)]
+pub struct RawVertexPNC {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub color: [f32; 3],
+}
+
+implement_vertex!(RawVertexPNC, position, normal, color);
+
 #[derive(Copy, Clone)]
 pub struct RawVertexP2C {
     pub position: [f32; 2],
@@ -53,6 +63,19 @@ pub struct RawVertexPNTTB {
 
 implement_vertex!(RawVertexPNTTB, position, normal, tex_coords, tangent, bitangent);
 
+/// 蒙皮顶点：位置，法向量，贴图坐标，影响该顶点的4个关节索引及其权重
+/// 关节索引在着色器中声明为uvec4，权重之和为1
+#[derive(Copy, Clone)]
+pub struct RawVertexPNTJW {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub texture: [f32; 2],
+    pub joints: [u32; 4],
+    pub weights: [f32; 4],
+}
+
+implement_vertex!(RawVertexPNTJW, position, normal, texture, joints, weights);
+
 
 #[derive(Copy, Clone)]
 pub struct RawInstanceOffsetO2 {
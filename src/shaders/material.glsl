@@ -0,0 +1,75 @@
+// 与material::Material::add_to_uniforms对应的材质块
+// 在片段着色器中通过 #include 引入（路径相对于引入它的着色器文件），之后即可使用material与Material*函数
+// 每个通道有贴图时采样贴图，否则使用MTL中的颜色
+
+struct Material {
+    vec3 ambient_color;
+    vec3 diffuse_color;
+    vec3 specular_color;
+    vec3 emissive_color;
+    vec3 transmission_filter;
+    float shininess;
+    float dissolve;
+    float optical_density;
+    int illumination_model;
+
+    bool has_diffuse;
+    sampler2D diffuse;
+    bool has_specular;
+    sampler2D specular;
+    bool has_reflection;
+    sampler2D reflection;
+    bool has_ambient_map;
+    sampler2D ambient_map;
+    bool has_emissive_map;
+    sampler2D emissive_map;
+    bool has_specular_highlight_map;
+    sampler2D specular_highlight_map;
+    bool has_dissolve_map;
+    sampler2D dissolve_map;
+    bool has_bump_map;
+    sampler2D bump_map;
+};
+
+uniform Material material;
+
+vec3 MaterialDiffuse(vec2 uv)
+{
+    return material.has_diffuse ? texture(material.diffuse, uv).rgb : material.diffuse_color;
+}
+
+vec3 MaterialAmbient(vec2 uv)
+{
+    return material.has_ambient_map ? texture(material.ambient_map, uv).rgb : material.ambient_color;
+}
+
+vec3 MaterialSpecular(vec2 uv)
+{
+    return material.has_specular ? texture(material.specular, uv).rgb : material.specular_color;
+}
+
+vec3 MaterialEmissive(vec2 uv)
+{
+    return material.has_emissive_map ? texture(material.emissive_map, uv).rgb : material.emissive_color;
+}
+
+// 反光度，高光贴图（map_Ns）为灰度图，取值映射到[0, shininess]
+float MaterialShininess(vec2 uv)
+{
+    return material.has_specular_highlight_map ? texture(material.specular_highlight_map, uv).r * material.shininess : material.shininess;
+}
+
+// 不透明度，漫反射贴图的alpha通道与dissolve相乘
+float MaterialAlpha(vec2 uv)
+{
+    float alpha = material.has_dissolve_map ? texture(material.dissolve_map, uv).r : material.dissolve;
+    if (material.has_diffuse)
+        alpha *= texture(material.diffuse, uv).a;
+    return alpha;
+}
+
+// 反射强度，没有反射贴图时不反射
+vec3 MaterialReflection(vec2 uv)
+{
+    return material.has_reflection ? texture(material.reflection, uv).rgb : vec3(0.0);
+}
@@ -0,0 +1,152 @@
+use cgmath::{Deg, Matrix4, Point3};
+use glium::{Display, DrawError, DrawParameters, Program, Surface, framebuffer::SimpleFrameBuffer, index::IndicesSource, texture::{CubeLayer, DepthCubemap}, uniforms::{MagnifySamplerFilter, MinifySamplerFilter, Sampler, SamplerWrapFunction, UniformValue}, vertex::MultiVerticesSource};
+
+use crate::{error::LoadError, lights::PointLight, material::{cube_face_views, try_depth_cubemap}, uniforms::DynamicUniforms};
+
+/**
+ * 点光源阴影（全向阴影贴图）
+ * 深度立方体贴图中保存片段到光源的距离除以远平面的值，光照阶段用片段到光源的方向采样并乘以远平面还原距离
+ * glium不支持分层帧缓冲，六个面分别作为深度附件渲染
+ *
+ * 阴影阶段传给着色器程序的uniform：
+ * `lightSpaceMatrix`（当前面的光空间矩阵）、`lightPos`、`far_plane`、`model`
+ */
+pub struct PointShadow {
+    cubemap: DepthCubemap,
+    near_plane: f32,
+    far_plane: f32,
+    light_position: Point3<f32>,
+    light_space_matrixes: [[[f32; 4]; 4]; 6],
+}
+
+impl PointShadow {
+
+    /**
+     * 创建边长为size的阴影立方体贴图，far_plane以外的物体不产生阴影
+     * 创建失败时直接panic，需要处理错误时使用`try_new`
+     */
+    pub fn new(display: &Display, size: u32, far_plane: f32) -> Self {
+        Self::try_new(display, size, far_plane).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new(display: &Display, size: u32, far_plane: f32) -> Result<Self, LoadError> {
+        let cubemap = try_depth_cubemap(display, size)?;
+        // 提前检查立方体贴图的面是否可作为深度附件，渲染时不再处理该错误
+        SimpleFrameBuffer::depth_only(display, cubemap.main_level().image(CubeLayer::PositiveX))
+            .map_err(|err| LoadError::gpu("阴影帧缓冲", err))?;
+        let mut shadow = PointShadow {
+            cubemap,
+            near_plane: 1.0,
+            far_plane,
+            light_position: Point3::new(0.0, 0.0, 0.0),
+            light_space_matrixes: [[[0.0; 4]; 4]; 6],
+        };
+        shadow.update_matrixes();
+        Ok(shadow)
+    }
+
+    /// 指定近平面，默认为1.0，比近平面更靠近光源的物体不产生阴影
+    pub fn with_near_plane(mut self, near_plane: f32) -> Self {
+        self.near_plane = near_plane;
+        self.update_matrixes();
+        self
+    }
+
+    /// 光源移动后重新计算六个面的光空间矩阵
+    pub fn update(&mut self, light: &PointLight) {
+        self.light_position = Point3::from(light.position);
+        self.update_matrixes();
+    }
+
+    fn update_matrixes(&mut self) {
+        // 六个面都是90°视野的正方形视锥
+        let projection = cgmath::perspective(Deg(90.0), 1.0, self.near_plane, self.far_plane);
+        for (matrix, (_, view)) in self.light_space_matrixes.iter_mut().zip(cube_face_views(self.light_position).iter()) {
+            *matrix = (projection * *view).into();
+        }
+    }
+
+    pub fn cubemap(&self) -> &DepthCubemap {
+        &self.cubemap
+    }
+
+    pub fn far_plane(&self) -> f32 {
+        self.far_plane
+    }
+
+    pub fn light_position(&self) -> Point3<f32> {
+        self.light_position
+    }
+
+    /// 顺序为+X、-X、+Y、-Y、+Z、-Z
+    pub fn light_space_matrixes(&self) -> &[[[f32; 4]; 4]; 6] {
+        &self.light_space_matrixes
+    }
+
+    /**
+     * 依次对六个面清空深度后通过program渲染所有投射阴影的物体
+     * draw_casters对每个面调用一次，其中对每个物体调用`ShadowPass::draw`
+     */
+    pub fn render<F>(&self, display: &Display, program: &Program, mut draw_casters: F) -> Result<(), DrawError>
+        where F: FnMut(&mut ShadowPass<'_>) -> Result<(), DrawError> {
+        let faces = [
+            CubeLayer::PositiveX, CubeLayer::NegativeX,
+            CubeLayer::PositiveY, CubeLayer::NegativeY,
+            CubeLayer::PositiveZ, CubeLayer::NegativeZ,
+        ];
+        for (face, light_space_matrix) in faces.iter().zip(self.light_space_matrixes.iter()) {
+            let mut framebuffer = SimpleFrameBuffer::depth_only(display, self.cubemap.main_level().image(*face))
+                .expect("阴影立方体贴图无法作为深度附件");
+            framebuffer.clear_depth(1.0);
+
+            let mut uniforms = DynamicUniforms::new();
+            uniforms.add_str_key_value("lightSpaceMatrix", UniformValue::Mat4(*light_space_matrix));
+            uniforms.add_str_key_value("lightPos", UniformValue::Vec3(self.light_position.into()));
+            uniforms.add_str_key_value("far_plane", UniformValue::Float(self.far_plane));
+
+            let mut pass = ShadowPass {
+                framebuffer,
+                program,
+                uniforms,
+                draw_parameters: DrawParameters {
+                    depth: glium::Depth {
+                        test: glium::draw_parameters::DepthTest::IfLess,
+                        write: true,
+                        .. Default::default()
+                    },
+                    .. Default::default()
+                },
+            };
+            draw_casters(&mut pass)?;
+        }
+        Ok(())
+    }
+
+    /// 光照阶段采样阴影立方体贴图的采样器，不做过滤，在边缘截断
+    pub fn sampler(&self) -> Sampler<'_, DepthCubemap> {
+        Sampler::new(&self.cubemap)
+            .magnify_filter(MagnifySamplerFilter::Nearest)
+            .minify_filter(MinifySamplerFilter::Nearest)
+            .wrap_function(SamplerWrapFunction::Clamp)
+    }
+}
+
+/**
+ * 一个面的阴影渲染，由`PointShadow::render`创建
+ */
+pub struct ShadowPass<'a> {
+    framebuffer: SimpleFrameBuffer<'a>,
+    program: &'a Program,
+    uniforms: DynamicUniforms<'a>,
+    draw_parameters: DrawParameters<'a>,
+}
+
+impl<'a> ShadowPass<'a> {
+
+    /// 以model为模型矩阵渲染一个投射阴影的物体
+    pub fn draw<'b, 'c, V, I>(&mut self, vertices: V, indices: I, model: Matrix4<f32>) -> Result<(), DrawError>
+        where V: MultiVerticesSource<'b>, I: Into<IndicesSource<'c>> {
+        self.uniforms.add_str_key_value("model", UniformValue::Mat4(model.into()));
+        self.framebuffer.draw(vertices, indices, self.program, &self.uniforms, &self.draw_parameters)
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use glium::uniforms::{UniformValue, AsUniformValue, Uniforms};
+use glium::{uniforms::{UniformValue, AsUniformValue, Uniforms, SamplerBehavior, SamplerWrapFunction, MinifySamplerFilter, MagnifySamplerFilter}, texture::MipmapsOption};
 
 #[macro_export]
 macro_rules! dynamic_uniform{
@@ -75,4 +75,121 @@ pub fn add_to_uniforms<'a: 'b, 'b>(key_prefix: &str, key_suffix: &str, value: &'
     let mut key = String::from(key_prefix);
     key.push_str(key_suffix);
     uniforms.add(key, value);
-}
\ No newline at end of file
+}
+
+/**
+ * 将指定的UniformValue按指定key添加到uniforms，用于布尔标记等临时值
+ */
+pub fn add_value_to_uniforms<'a>(key_prefix: &str, key_suffix: &str, value: UniformValue<'a>, uniforms: &mut DynamicUniforms<'a>) {
+    let mut key = String::from(key_prefix);
+    key.push_str(key_suffix);
+    uniforms.add_str_key_value(&key, value);
+}
+
+/**
+ * 纹理采样描述：S/T方向的环绕方式、缩小/放大过滤、是否生成mipmap以及最大各向异性
+ * mipmap在纹理上传时生成，其余状态在绑定uniform时通过glium的采样器设置
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextureSampler {
+    pub wrap_s: SamplerWrapFunction,
+    pub wrap_t: SamplerWrapFunction,
+    pub min_filter: MinifySamplerFilter,
+    pub mag_filter: MagnifySamplerFilter,
+    /// 上传时是否生成mipmap
+    pub mipmaps: bool,
+    /// 最大各向异性，1为关闭，超出显卡支持范围时由驱动截断
+    pub max_anisotropy: u16,
+}
+
+impl Default for TextureSampler {
+    fn default() -> Self {
+        TextureSampler {
+            wrap_s: SamplerWrapFunction::Repeat,
+            wrap_t: SamplerWrapFunction::Repeat,
+            min_filter: MinifySamplerFilter::LinearMipmapLinear,
+            mag_filter: MagnifySamplerFilter::Linear,
+            mipmaps: true,
+            max_anisotropy: 1,
+        }
+    }
+}
+
+impl TextureSampler {
+
+    pub fn with_wrap(mut self, wrap_s: SamplerWrapFunction, wrap_t: SamplerWrapFunction) -> Self {
+        self.wrap_s = wrap_s;
+        self.wrap_t = wrap_t;
+        self
+    }
+
+    pub fn with_filters(mut self, min_filter: MinifySamplerFilter, mag_filter: MagnifySamplerFilter) -> Self {
+        self.min_filter = min_filter;
+        self.mag_filter = mag_filter;
+        self
+    }
+
+    pub fn with_mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    pub fn with_anisotropy(mut self, max_anisotropy: u16) -> Self {
+        self.max_anisotropy = max_anisotropy.max(1);
+        self
+    }
+
+    /// 上传纹理时使用的mipmap选项
+    pub fn mipmaps_option(&self) -> MipmapsOption {
+        if self.mipmaps {
+            MipmapsOption::AutoGeneratedMipmaps
+        } else {
+            MipmapsOption::NoMipmap
+        }
+    }
+
+    /// glium采样器状态，没有mipmap时缩小过滤退化为对应的非mipmap过滤，否则纹理不完整
+    pub fn behavior(&self) -> SamplerBehavior {
+        let minify_filter = match (self.mipmaps, self.min_filter) {
+            (false, MinifySamplerFilter::NearestMipmapNearest) | (false, MinifySamplerFilter::NearestMipmapLinear) => MinifySamplerFilter::Nearest,
+            (false, MinifySamplerFilter::LinearMipmapNearest) | (false, MinifySamplerFilter::LinearMipmapLinear) => MinifySamplerFilter::Linear,
+            (_, filter) => filter,
+        };
+        SamplerBehavior {
+            wrap_function: (self.wrap_s, self.wrap_t, self.wrap_t),
+            minify_filter,
+            magnify_filter: self.mag_filter,
+            depth_texture_comparison: None,
+            max_anisotropy: self.max_anisotropy,
+        }
+    }
+}
+
+/**
+ * 为纹理类型的uniform值指定采样器状态，非纹理值原样返回
+ */
+pub fn with_sampler(value: UniformValue<'_>, behavior: SamplerBehavior) -> UniformValue<'_> {
+    match value {
+        UniformValue::Texture2d(texture, _) => UniformValue::Texture2d(texture, Some(behavior)),
+        UniformValue::CompressedTexture2d(texture, _) => UniformValue::CompressedTexture2d(texture, Some(behavior)),
+        UniformValue::SrgbTexture2d(texture, _) => UniformValue::SrgbTexture2d(texture, Some(behavior)),
+        UniformValue::CompressedSrgbTexture2d(texture, _) => UniformValue::CompressedSrgbTexture2d(texture, Some(behavior)),
+        UniformValue::Cubemap(texture, _) => UniformValue::Cubemap(texture, Some(behavior)),
+        UniformValue::SrgbCubemap(texture, _) => UniformValue::SrgbCubemap(texture, Some(behavior)),
+        other => other,
+    }
+}
+
+/**
+ * 带采样描述的纹理，作为uniform绑定时使用自身的采样器状态
+ */
+pub struct SampledTexture<T> {
+    pub texture: T,
+    pub sampler: TextureSampler,
+}
+
+impl<T: AsUniformValue> AsUniformValue for SampledTexture<T> {
+    fn as_uniform_value(&self) -> UniformValue<'_> {
+        with_sampler(self.texture.as_uniform_value(), self.sampler.behavior())
+    }
+}
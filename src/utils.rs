@@ -1,6 +1,6 @@
 use glium::{Display, vertex::VertexBufferAny};
 
-use crate::Vertex;
+use crate::{Vertex, geometry::triangulate::triangulate};
 
 
 pub fn matrix4_to_raw<S>(matrix: cgmath::Matrix4<S>) -> [[S; 4]; 4] {
@@ -23,19 +23,24 @@ pub fn load_wavefront(display: &Display, data: &[u8]) -> VertexBufferAny {
         for polygon in object.groups.iter().flat_map(|g| g.polys.iter()) {
             match polygon {
                 obj::SimplePolygon(indices) => {
-                    for v in indices.iter() {
-                        let position = data.position[v.0];
-                        let texture = v.1.map(|index| data.texture[index]);
-                        let normal = v.2.map(|index| data.normal[index]);
+                    let positions: Vec<[f32; 3]> = indices.iter().map(|v| data.position[v.0]).collect();
+                    // 四边形及多边形需要先三角化
+                    for triangle in triangulate(&positions) {
+                        for i in triangle {
+                            let v = &indices[i];
+                            let position = data.position[v.0];
+                            let texture = v.1.map(|index| data.texture[index]);
+                            let normal = v.2.map(|index| data.normal[index]);
 
-                        let texture = texture.unwrap_or([0.0, 0.0]);
-                        let normal = normal.unwrap_or([0.0, 0.0, 0.0]);
+                            let texture = texture.unwrap_or([0.0, 0.0]);
+                            let normal = normal.unwrap_or([0.0, 0.0, 0.0]);
 
-                        vertex_data.push(Vertex {
-                            position,
-                            normal,
-                            texture,
-                        })
+                            vertex_data.push(Vertex {
+                                position,
+                                normal,
+                                texture,
+                            })
+                        }
                     }
                 },
             }